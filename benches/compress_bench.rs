@@ -0,0 +1,145 @@
+//! Criterion benchmarks for compression and decompression
+//!
+//! Run with `cargo bench`. Reports are written to `target/criterion`.
+
+use compress_json_rs::{Decoder, compress, compress_shared, decompress, key_for_index};
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+use serde_json::{Value, json};
+use std::rc::Rc;
+
+// The sample's own unit tests are not built into this harness-less target
+#[allow(unused_imports)]
+#[path = "../tests/sample.rs"]
+mod sample;
+
+/// A large array of distinct, similarly shaped records
+fn large_array() -> Value {
+    let items: Vec<Value> = (0..5_000)
+        .map(|i| {
+            json!({
+                "id": i,
+                "name": format!("user-{i}"),
+                "active": i % 3 == 0,
+                "score": i as f64 / 7.0,
+                "tags": ["alpha", "beta", format!("group-{}", i % 10)]
+            })
+        })
+        .collect();
+    Value::Array(items)
+}
+
+/// The same nested subtree repeated many times, so the store is tiny but the
+/// expanded document is large
+fn repeated_subtree() -> Value {
+    let leaf: Vec<Value> = (0..50)
+        .map(|i| json!({"k": i, "v": format!("value-{i}")}))
+        .collect();
+    let subtree = json!({"rows": leaf, "meta": {"kind": "shared"}});
+    Value::Array(vec![subtree; 500])
+}
+
+/// Named inputs shared by the compress and decompress groups
+fn inputs() -> Vec<(&'static str, Value)> {
+    vec![
+        ("collection", sample::get_sample("collection")),
+        ("rich", sample::get_sample("rich")),
+        ("large_array", large_array()),
+    ]
+}
+
+fn bench_compress(c: &mut Criterion) {
+    let mut group = c.benchmark_group("compress");
+    for (name, data) in inputs() {
+        group.bench_with_input(BenchmarkId::from_parameter(name), &data, |b, data| {
+            b.iter(|| compress(black_box(data)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_decompress(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decompress");
+    for (name, data) in inputs() {
+        let compressed = compress(&data);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(name),
+            &compressed,
+            |b, compressed| b.iter(|| decompress(black_box(compressed.clone()))),
+        );
+    }
+    group.finish();
+}
+
+/// Decode a heavily shared subtree with and without memoization
+///
+/// `decoder_cold` starts from an empty cache on every iteration, while
+/// `decoder_warm` reuses one decoder the way a server holding a shared store
+/// would, so only the first decode pays for expanding the entries.
+fn bench_repeated_subtree(c: &mut Criterion) {
+    let compressed = compress(&repeated_subtree());
+    let mut group = c.benchmark_group("repeated_subtree");
+    group.bench_function("decompress", |b| {
+        b.iter(|| decompress(black_box(compressed.clone())))
+    });
+    group.bench_function("decoder_cold", |b| {
+        b.iter(|| {
+            let mut decoder = Decoder::new(black_box(&compressed.values));
+            decoder.decode_cached(&compressed.root)
+        })
+    });
+    let mut decoder = Decoder::new(&compressed.values);
+    group.bench_function("decoder_warm", |b| {
+        b.iter(|| decoder.decode_cached(black_box(&compressed.root)))
+    });
+    group.finish();
+}
+
+/// Compress the repeated subtree as one `Rc` shared by every element
+///
+/// `structural` hashes each copy to find it in the value cache, while
+/// `shared` recognizes the repeats by pointer and walks the subtree once.
+fn bench_shared_subtree(c: &mut Criterion) {
+    let data = repeated_subtree();
+    let subtree = Rc::new(data[0].clone());
+    let items: Vec<Rc<Value>> = (0..500).map(|_| Rc::clone(&subtree)).collect();
+    let mut group = c.benchmark_group("shared_subtree");
+    group.bench_function("structural", |b| b.iter(|| compress(black_box(&data))));
+    group.bench_function("shared", |b| b.iter(|| compress_shared(black_box(&items))));
+    group.finish();
+}
+
+/// Compress many copies of a few long strings
+///
+/// Every occurrence is looked up in the value cache, so the cost of hashing
+/// a long string dominates.
+fn bench_long_strings(c: &mut Criterion) {
+    let long: Vec<String> = (0..4)
+        .map(|i| format!("{i}:{}", "lorem ipsum ".repeat(90)))
+        .collect();
+    let data = Value::Array((0..5000).map(|i| json!(long[i % 4])).collect());
+    c.bench_function("long_strings/5000", |b| {
+        b.iter(|| compress(black_box(&data)))
+    });
+}
+
+/// Key generation, which runs once per stored value
+fn bench_keys(c: &mut Criterion) {
+    c.bench_function("key_for_index/100k", |b| {
+        b.iter(|| {
+            for i in 0..100_000 {
+                black_box(key_for_index(black_box(i)));
+            }
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_compress,
+    bench_decompress,
+    bench_repeated_subtree,
+    bench_shared_subtree,
+    bench_long_strings,
+    bench_keys
+);
+criterion_main!(benches);
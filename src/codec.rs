@@ -0,0 +1,45 @@
+//! Per-type encoders and decoders for store entries.
+//!
+//! These are the building blocks [`compress`](crate::compress) and
+//! [`decompress`](crate::decompress) use for individual values. They are
+//! useful when writing a custom decoder, for example one that walks a store
+//! produced by merging several [`Memory`](crate::Memory) instances.
+//!
+//! | Entry | Encode | Decode |
+//! |-------|--------|--------|
+//! | `b\|T`, `b\|F` | [`encode_bool`] | [`decode_bool`] |
+//! | `n\|<number>` | [`encode_num`] | [`decode_num`] |
+//! | `N\|+`, `N\|-`, `N\|0` | - | [`decode_special`] (check with [`is_special_value`]) |
+//! | Plain or `s\|` string | [`encode_str`], [`encode_string`] | [`decode_str`] |
+//! | Base-62 reference | [`key_for_index`](crate::key_for_index) | [`decode_key`] |
+//!
+//! Strings starting with any of the [`RESERVED_PREFIXES`] are escaped.
+//! Container entries (`a|`, `o|`, `c|`) hold `|`-separated references that
+//! are decoded with [`decode_key`].
+//!
+//! # Example
+//!
+//! ```rust
+//! use compress_json_rs::codec::{decode_bool, decode_num, decode_str, is_special_value};
+//! use compress_json_rs::compress;
+//! use serde_json::json;
+//!
+//! let c = compress(&json!([true, 1.5, "s|x"]));
+//! let decoded: Vec<String> = c
+//!     .values
+//!     .iter()
+//!     .map(|entry| match entry.split_once('|').map(|(prefix, _)| prefix) {
+//!         Some("b") => decode_bool(entry).to_string(),
+//!         Some("n") => decode_num(entry).to_string(),
+//!         Some("a") => "array".to_string(),
+//!         _ if is_special_value(entry) => "special".to_string(),
+//!         _ => decode_str(entry),
+//!     })
+//!     .collect();
+//! assert_eq!(decoded, ["true", "1.5", "s|x", "array"]);
+//! ```
+
+pub use crate::encode::{
+    RESERVED_PREFIXES, decode_bool, decode_key, decode_num, decode_special, decode_str,
+    encode_bool, encode_num, encode_str, encode_string, is_special_value,
+};
@@ -3,27 +3,43 @@
 //! This module provides the [`Config`] struct and the global [`CONFIG`] constant
 //! that controls how JSON values are compressed.
 //!
-//! # Current Limitations
+//! # Runtime Configuration
 //!
-//! Configuration is currently compile-time only via the [`CONFIG`] constant.
-//! Runtime configuration may be added in future versions.
+//! The free [`compress`](crate::compress) function always uses [`CONFIG`].
+//! To compress with different settings, build a [`Config`] and pass it to
+//! [`compress_with_config`](crate::compress_with_config).
+//!
+//! Decode limits for untrusted input are read by
+//! [`try_decompress_with_config`](crate::try_decompress_with_config);
+//! [`Config::untrusted`] bundles them into a single preset.
+//! [`Config::strict`] does the same for rejecting lossy conversions while
+//! compressing.
 //!
 //! # Example
 //!
 //! ```rust
-//! use compress_json_rs::CONFIG;
+//! use compress_json_rs::{CONFIG, Config, compress_with_config, decompress};
+//! use serde_json::json;
 //!
 //! // Check current configuration
 //! println!("Sort keys: {}", CONFIG.sort_key);
 //! println!("Preserve NaN: {}", CONFIG.preserve_nan);
 //! println!("Preserve Infinity: {}", CONFIG.preserve_infinite);
+//!
+//! // Compress with a custom configuration
+//! let config = Config { sort_key: true, ..Config::default() };
+//! let data = json!({"b": 1, "a": 2});
+//! let restored = decompress(compress_with_config(&data, &config));
+//! assert_eq!(restored, data);
 //! ```
 
+use serde::{Deserialize, Serialize};
+
 /// Global configuration for compression behavior.
 ///
 /// This struct defines options that control how JSON values are processed
-/// during compression. The library uses a compile-time constant [`CONFIG`]
-/// with these settings.
+/// during compression. The free functions use the constant [`CONFIG`];
+/// a custom value can be passed to [`compress_with_config`](crate::compress_with_config).
 ///
 /// # Fields
 ///
@@ -34,6 +50,27 @@
 /// | `error_on_nan` | `false` | Panic on NaN (only if `preserve_nan` is false) |
 /// | `preserve_infinite` | `false` | Encode Infinity as `N\|+`/`N\|-` (vs convert to null) |
 /// | `error_on_infinite` | `false` | Panic on Infinity (only if `preserve_infinite` is false) |
+/// | `numeric_strings` | `false` | Store integer-looking strings as numbers |
+/// | `strict_duplicate_keys` | `false` | Reject duplicate object keys when parsing text |
+/// | `chunk_strings` | `None` | Split longer strings into separately stored chunks |
+/// | `max_depth` | `None` | Reject nesting deeper than this in fallible decoding |
+/// | `max_nodes` | `None` | Reject outputs with more nodes than this in fallible decoding |
+/// | `max_array_len` | `None` | Reject arrays longer than this in fallible decoding |
+/// | `max_object_keys` | `None` | Reject objects with more keys than this in fallible decoding |
+/// | `lenient` | `false` | Accept stray trailing empty references and `+42` / `1_000` numbers in fallible decoding |
+/// | `frequency_keys` | `false` | Give the most referenced values the shortest keys |
+/// | `sort_values` | `false` | Assign keys in sorted value order, for byte-identical output where small edits move few keys |
+/// | `dedup` | `true` | Store equal values once (vs one entry per occurrence) |
+/// | `share_schemas` | `true` | Share one key-list entry between objects with the same keys |
+/// | `verify_on_compress` | `false` | Decompress the output and panic unless it matches the input |
+/// | `typed_sections` | `false` | Group numbers, strings, booleans and composites into contiguous ranges |
+/// | `error_on_precision_loss` | `false` | Panic on integers that `f64` cannot represent exactly |
+/// | `extend_schemas` | `false` | Store a schema as an earlier schema plus extra keys |
+/// | `pack_int_arrays` | `false` | Store arrays of integers as one entry of base-62 deltas |
+/// | `max_value_len` | `None` | Reject strings whose store entry would be longer than this |
+/// | `null_object_policy` | `Empty` | How `null` object values are stored (see [`NullObjectPolicy`]) |
+/// | `treat_empty_string_as_null` | `false` | Store (and decode) empty-string values as `null` |
+/// | `max_dictionary_len` | `None` | Bound the deduplication cache, evicting the least recently used values |
 ///
 /// # Key Sorting
 ///
@@ -57,6 +94,12 @@
 /// Note: `error_on_nan` and `error_on_infinite` only take effect when
 /// their corresponding `preserve_*` option is `false`.
 ///
+/// # Serialization
+///
+/// `Config` implements `Serialize` and `Deserialize` so it can be persisted
+/// alongside a [`MemorySnapshot`](crate::MemorySnapshot). Missing fields
+/// deserialize to their [`CONFIG`] values.
+///
 /// # Example
 ///
 /// ```rust
@@ -69,7 +112,8 @@
 /// assert_eq!(CONFIG.preserve_infinite, false);
 /// assert_eq!(CONFIG.error_on_infinite, false);
 /// ```
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
     /// Whether to sort object keys alphabetically.
     ///
@@ -118,6 +162,349 @@ pub struct Config {
     ///
     /// **Default:** `false`
     pub error_on_infinite: bool,
+
+    /// Whether to store strictly numeric strings as numbers.
+    ///
+    /// Some producers send big integers as strings (`"9007199254740993"`)
+    /// to avoid precision loss in JavaScript. When `true`, a string that is
+    /// a canonical integer (optional `-`, no leading zeros) fitting in an
+    /// `i64` or `u64` is encoded with the `n|` prefix, so it decompresses
+    /// as an exact integer `Number` rather than a string.
+    ///
+    /// **Warning:** this is lossy with respect to type. A value that was
+    /// genuinely meant to be a string (an id, a zip code) comes back as a
+    /// number. Only enable it when every numeric string should be a number.
+    ///
+    /// **Default:** `false`
+    pub numeric_strings: bool,
+
+    /// Whether to reject duplicate object keys when compressing JSON text.
+    ///
+    /// Only affects [`compress_str_with_config`](crate::compress_str_with_config).
+    /// `serde_json` normally keeps the last value for a repeated key; when
+    /// `true`, parsing fails instead, surfacing malformed producers.
+    /// A `serde_json::Map` cannot hold duplicates, so compressing an
+    /// existing `Value` is unaffected.
+    ///
+    /// **Default:** `false`
+    pub strict_duplicate_keys: bool,
+
+    /// Split strings longer than this many bytes into chunks.
+    ///
+    /// When `Some(n)`, a string longer than `n` bytes is cut into chunks of at
+    /// most `n` bytes (on character boundaries). Each chunk is stored as its own
+    /// string entry and the string itself becomes a `c|<ref>|<ref>...` list that
+    /// is concatenated on decode. Large strings that share a long prefix (such as
+    /// text blobs with a common header) then share the chunk entries for it.
+    ///
+    /// The `c|` entry is an extension of this crate and is not understood by
+    /// other compress-json implementations.
+    ///
+    /// **Default:** `None`
+    pub chunk_strings: Option<usize>,
+
+    /// Maximum nesting depth accepted by fallible decoding.
+    ///
+    /// Only affects [`try_decompress_with_config`](crate::try_decompress_with_config).
+    /// The root value is at depth 1 and each array or object adds one level.
+    /// Decoding fails with [`DecompressError::MaxDepthExceeded`](crate::DecompressError::MaxDepthExceeded)
+    /// beyond this depth, which keeps crafted deeply nested payloads from
+    /// exhausting the stack.
+    ///
+    /// **Default:** `None` (see [`Config::untrusted`] for a bounded preset)
+    pub max_depth: Option<usize>,
+
+    /// Maximum number of values produced by fallible decoding.
+    ///
+    /// Only affects [`try_decompress_with_config`](crate::try_decompress_with_config).
+    /// Every scalar, array and object in the output counts as one node. Shared
+    /// entries count each time they are expanded, so a small store whose
+    /// references fan out exponentially fails with
+    /// [`DecompressError::MaxNodesExceeded`](crate::DecompressError::MaxNodesExceeded)
+    /// instead of allocating a huge tree.
+    ///
+    /// **Default:** `None` (see [`Config::untrusted`] for a bounded preset)
+    pub max_nodes: Option<usize>,
+
+    /// Maximum number of elements in one array during fallible decoding.
+    ///
+    /// Only affects [`try_decompress_with_config`](crate::try_decompress_with_config).
+    /// An `a|` entry with more references than this fails with
+    /// [`DecompressError::MaxArrayLenExceeded`](crate::DecompressError::MaxArrayLenExceeded)
+    /// before any element is decoded or the array is allocated, which caps
+    /// the memory a single crafted entry can claim.
+    ///
+    /// **Default:** `None` (see [`Config::untrusted`] for a bounded preset)
+    pub max_array_len: Option<usize>,
+
+    /// Maximum number of keys in one object during fallible decoding.
+    ///
+    /// Only affects [`try_decompress_with_config`](crate::try_decompress_with_config).
+    /// An `o|` entry with more references, or a schema with more keys, than
+    /// this fails with
+    /// [`DecompressError::MaxObjectKeysExceeded`](crate::DecompressError::MaxObjectKeysExceeded)
+    /// before the map is allocated.
+    ///
+    /// **Default:** `None` (see [`Config::untrusted`] for a bounded preset)
+    pub max_object_keys: Option<usize>,
+
+    /// Whether fallible decoding accepts the looser output of other encoders.
+    ///
+    /// Only affects [`try_decompress_with_config`](crate::try_decompress_with_config).
+    /// Some third-party encoders leave a trailing separator, as in `o|0|1|2|`.
+    /// In strict mode (the default) an object with more references than its
+    /// schema has keys fails with
+    /// [`DecompressError::InvalidEntry`](crate::DecompressError::InvalidEntry).
+    /// When `true`, empty references past the end of an object's schema are
+    /// dropped. A non-empty surplus reference is still an error, and arrays
+    /// are left alone: a trailing empty reference there is a `null` element,
+    /// as in `a|0|` for `[x, null]`.
+    ///
+    /// Numbers are also parsed leniently: a leading `+` and `_` separators
+    /// between digits are stripped, so `n|+42` and `n|1_000` decode as `42`
+    /// and `1000`. In strict mode such entries fail with `InvalidEntry`.
+    ///
+    /// The panicking decoders ([`decompress`](crate::decompress) and the
+    /// like) ignore this option and parse numbers with Rust's own parsers:
+    /// they accept a leading `+`, so `n|+42` decodes as `42` there even in
+    /// strict mode, and panic on `_` separators.
+    ///
+    /// **Default:** `false`
+    pub lenient: bool,
+
+    /// Whether to assign keys by reference count instead of first-seen order.
+    ///
+    /// Only affects [`compress_with_config`](crate::compress_with_config) and
+    /// [`compress_str_with_config`](crate::compress_str_with_config). After the
+    /// store is built, a second pass counts how often each entry is referenced and
+    /// renumbers the store so the most referenced entries get the lowest indices,
+    /// and thus the shortest base-62 keys. Every reference string is rewritten to
+    /// the new keys.
+    ///
+    /// The output is smaller when many values are referenced often (e.g. a
+    /// schema shared by thousands of objects), at the cost of a slower
+    /// compression. Decompression is unaffected.
+    ///
+    /// **Default:** `false`
+    pub frequency_keys: bool,
+
+    /// Whether keys are assigned in sorted value order.
+    ///
+    /// Only affects [`compress_with_config`](crate::compress_with_config) and
+    /// [`compress_str_with_config`](crate::compress_str_with_config). Keys are
+    /// normally handed out in first-seen order, so inserting a value near the
+    /// start of a document shifts the key of every value after it. When
+    /// `true`, the store is renumbered after it is built: scalar entries come
+    /// first, ordered by their stored form, followed by composites ordered by
+    /// a hash of their contents. An entry's key then depends on which values
+    /// the document holds rather than where they appear, so a small edit to the
+    /// input changes few keys and the compressed forms diff cleanly.
+    ///
+    /// This is the option for golden files and snapshot tests that check
+    /// compressed output in: compressing the same input with the same
+    /// configuration always yields byte-identical `values`, whatever order
+    /// the encoder happened to add them in. It is distinct from
+    /// [`sort_key`](Config::sort_key), which only orders the keys within each
+    /// object schema; set both to make documents that differ only in key
+    /// order compress identically.
+    ///
+    /// Applied before [`frequency_keys`](Config::frequency_keys) and
+    /// [`typed_sections`](Config::typed_sections), which keep this order among
+    /// equal entries. Decompression is unaffected.
+    ///
+    /// **Default:** `false`
+    pub sort_values: bool,
+
+    /// Whether equal values share one store entry.
+    ///
+    /// When `false`, every value added gets a new entry without consulting the
+    /// value cache, so repeated strings, numbers and containers are stored once
+    /// per occurrence. The output is larger but cheaper to build, which is useful
+    /// to measure how much deduplication contributes. Object schemas are still
+    /// shared (see [`share_schemas`](Config::share_schemas)). Decoding is
+    /// index-based and reads either form.
+    ///
+    /// **Default:** `true`
+    pub dedup: bool,
+
+    /// Whether objects with the same keys share one schema entry.
+    ///
+    /// When `false`, every object stores its own key list: a single-key object
+    /// references the key's string entry directly, and any other object gets a
+    /// new `a|` entry of its keys. Data whose objects rarely share a shape can
+    /// skip the schema cache this way; compare the output sizes to see whether
+    /// sharing pays off. Both forms decode the same.
+    ///
+    /// **Default:** `true`
+    pub share_schemas: bool,
+
+    /// Whether to check the output by decompressing it again.
+    ///
+    /// Only affects [`compress_with_config`](crate::compress_with_config) and
+    /// [`compress_str_with_config`](crate::compress_str_with_config). After the
+    /// store is built, it is decoded and compared with the input; compression
+    /// panics if they differ, so a latent encoding bug is caught before the data
+    /// leaves the process. Conversions the configuration asks for are not
+    /// mismatches: numbers are compared by value after the `f64` round trip, and
+    /// with `numeric_strings` an integer string may come back as that number.
+    ///
+    /// This roughly doubles the cost of compression, so it is meant for canaries
+    /// and tests rather than production paths.
+    ///
+    /// **Default:** `false`
+    pub verify_on_compress: bool,
+
+    /// Whether to group store entries by type.
+    ///
+    /// Only affects [`compress_with_config`](crate::compress_with_config) and
+    /// [`compress_str_with_config`](crate::compress_str_with_config). After the
+    /// store is built, it is renumbered into contiguous sections: numbers
+    /// (`n|`, `N|`), strings (including `s|` and `x|` entries), booleans, and
+    /// finally composites (`a|`, `o|`, `c|`). Every reference and the root are
+    /// rewritten to match. A decoder walking one type touches one region of the
+    /// values array, and each section can be handed to a type-specific
+    /// compressor downstream.
+    ///
+    /// The output is an ordinary store (decoders only follow references), so no
+    /// format marker is written and other implementations read it unchanged.
+    /// Within a section, entries keep first-seen order, or frequency order with
+    /// [`frequency_keys`](Config::frequency_keys).
+    ///
+    /// **Default:** `false`
+    pub typed_sections: bool,
+
+    /// Whether to panic on integers that would be rounded when stored.
+    ///
+    /// Numbers are stored through `f64`, which holds integers exactly only up to
+    /// `2^53` in magnitude. When `true`, compressing a larger `i64`/`u64` whose
+    /// value `f64` cannot represent panics instead of storing the nearest `f64`.
+    /// With the `arbitrary_precision` feature such integers are stored exactly,
+    /// so nothing is rejected.
+    ///
+    /// Decimal fractions are not checked: `serde_json` has already parsed them
+    /// into an `f64` (or, with `arbitrary_precision`, kept them exactly).
+    ///
+    /// **Default:** `false`
+    pub error_on_precision_loss: bool,
+
+    /// Whether a schema may extend an earlier schema instead of listing every key.
+    ///
+    /// Heterogeneous collections often hold objects whose key sets overlap,
+    /// such as `{a, b}` and `{a, b, c}`. When `true`, a new schema whose keys
+    /// start with those of an already stored schema (of at least two keys) is
+    /// written as a reference to that schema followed by the extra keys:
+    /// `a|<base>|<c>` rather than `a|<a>|<b>|<c>`. The longest such base is
+    /// used. Only takes effect with [`share_schemas`](Config::share_schemas).
+    ///
+    /// The nested schema reference is an extension of this crate and is not
+    /// understood by other compress-json implementations.
+    ///
+    /// **Default:** `false`
+    pub extend_schemas: bool,
+
+    /// Whether arrays of integers are packed into a single entry.
+    ///
+    /// An array normally stores a reference per element and an `n|` entry
+    /// per distinct value, which is costly for the large IDs of database
+    /// exports. When `true`, a non-empty array whose elements are all
+    /// integers is stored as one `d|` entry holding the first element and
+    /// the difference of each element from the previous one, in base 62
+    /// (`[1000, 1001, 1005]` becomes `d|G8|1|4`). Sequential or clustered
+    /// IDs then cost a character or two each. Elements are kept exactly,
+    /// even beyond `2^53`, so
+    /// [`error_on_precision_loss`](Config::error_on_precision_loss) does not
+    /// apply to them, but they are no longer shared with equal numbers
+    /// elsewhere in the document.
+    ///
+    /// The `d|` entry is an extension of this crate and is not understood by
+    /// other compress-json implementations.
+    ///
+    /// **Default:** `false`
+    pub pack_int_arrays: bool,
+
+    /// Maximum length in bytes of a string's store entry.
+    ///
+    /// When `Some(n)`, compressing a string value or object key whose encoded
+    /// entry (the string plus any `s|` escape) is longer than `n` bytes panics
+    /// before the string is copied into the store;
+    /// [`try_compress_with_config`](crate::try_compress_with_config) reports a
+    /// [`CompressError::ValueTooLong`](crate::CompressError::ValueTooLong)
+    /// instead. Strings are measured whole, even when `chunk_strings` would
+    /// split them. Pathological inputs such as a 100 MB string are rejected
+    /// this way without limiting the size of the document as a whole.
+    ///
+    /// **Default:** `None`
+    pub max_value_len: Option<usize>,
+
+    /// How a `null` value inside an object is stored.
+    ///
+    /// Array elements that are `null` are always stored as the `_` marker;
+    /// object values default to an empty reference. See [`NullObjectPolicy`]
+    /// for the choices. Decoders read both `""` and `_` as `null`, so the
+    /// output of every policy decompresses without any format flag.
+    ///
+    /// **Default:** [`NullObjectPolicy::Empty`]
+    pub null_object_policy: NullObjectPolicy,
+
+    /// Whether empty-string values are treated as `null`.
+    ///
+    /// `null` is written as an empty reference, but an empty string *value*
+    /// never collides with it: it is stored as an entry of its own, and
+    /// decodes as `""`. Some lenient upstream producers make no distinction
+    /// between the two; when `true`, compressing stores every empty-string
+    /// value as `null` (dropped from objects under
+    /// [`NullObjectPolicy::Drop`]), and
+    /// [`try_decompress_with_config`](crate::try_decompress_with_config)
+    /// decodes empty-string entries from other encoders as `null`. Empty
+    /// object keys are unaffected.
+    ///
+    /// **Default:** `false`
+    pub treat_empty_string_as_null: bool,
+
+    /// Maximum number of values kept in the deduplication cache.
+    ///
+    /// A [`Memory`](crate::Memory) remembers every value it has stored so
+    /// that repeats can reference the existing entry. In long-running
+    /// streaming compression (e.g. an [`ArrayWriter`](crate::ArrayWriter)
+    /// fed an endless NDJSON source), the cache grows with every new unique
+    /// value even though the store itself is written out. When `Some(n)`,
+    /// the cache holds at most `n` values: once full, the least recently
+    /// referenced value is evicted, and is stored again as a new entry if
+    /// it is seen later. This bounds memory use at some cost in size.
+    ///
+    /// Evicted entries stay in the store, so keys already handed out remain
+    /// valid and the output decodes as usual. Object schemas are cached
+    /// separately and are not evicted. Has no effect with
+    /// [`dedup`](Config::dedup) off, which caches nothing.
+    ///
+    /// **Default:** `None` (unbounded)
+    pub max_dictionary_len: Option<usize>,
+}
+
+/// How [`Config::null_object_policy`] stores `null` object values.
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{Config, NullObjectPolicy, compress_with_config, decompress};
+/// use serde_json::json;
+///
+/// let data = json!({"id": 1, "parent": null});
+/// let config = Config {
+///     null_object_policy: NullObjectPolicy::Drop,
+///     ..Config::default()
+/// };
+/// assert_eq!(decompress(compress_with_config(&data, &config)), json!({"id": 1}));
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NullObjectPolicy {
+    /// Store an empty reference (`o|0|1|`), as the JavaScript library does
+    #[default]
+    Empty,
+    /// Store the `_` marker (`o|0|1|_`), the same as `null` array elements
+    Marker,
+    /// Leave the key out of the object; it is missing after decompression
+    Drop,
 }
 
 /// Default configuration matching the TypeScript implementation.
@@ -132,14 +519,35 @@ pub struct Config {
 /// # Values
 ///
 /// ```rust
-/// use compress_json_rs::CONFIG;
+/// use compress_json_rs::{CONFIG, NullObjectPolicy};
 ///
-/// // All options default to false
+/// // All options default to false, except deduplication and schema sharing
 /// assert!(!CONFIG.sort_key);
 /// assert!(!CONFIG.preserve_nan);
 /// assert!(!CONFIG.error_on_nan);
 /// assert!(!CONFIG.preserve_infinite);
 /// assert!(!CONFIG.error_on_infinite);
+/// assert!(!CONFIG.numeric_strings);
+/// assert!(!CONFIG.strict_duplicate_keys);
+/// assert_eq!(CONFIG.chunk_strings, None);
+/// assert_eq!(CONFIG.max_depth, None);
+/// assert_eq!(CONFIG.max_nodes, None);
+/// assert_eq!(CONFIG.max_array_len, None);
+/// assert_eq!(CONFIG.max_object_keys, None);
+/// assert!(!CONFIG.lenient);
+/// assert!(!CONFIG.frequency_keys);
+/// assert!(!CONFIG.sort_values);
+/// assert!(CONFIG.dedup);
+/// assert!(CONFIG.share_schemas);
+/// assert!(!CONFIG.verify_on_compress);
+/// assert!(!CONFIG.typed_sections);
+/// assert!(!CONFIG.error_on_precision_loss);
+/// assert!(!CONFIG.extend_schemas);
+/// assert!(!CONFIG.pack_int_arrays);
+/// assert_eq!(CONFIG.max_value_len, None);
+/// assert_eq!(CONFIG.null_object_policy, NullObjectPolicy::Empty);
+/// assert!(!CONFIG.treat_empty_string_as_null);
+/// assert_eq!(CONFIG.max_dictionary_len, None);
 /// ```
 ///
 /// # Compatibility
@@ -152,4 +560,113 @@ pub const CONFIG: Config = Config {
     error_on_nan: false,
     preserve_infinite: false,
     error_on_infinite: false,
+    numeric_strings: false,
+    strict_duplicate_keys: false,
+    chunk_strings: None,
+    max_depth: None,
+    max_nodes: None,
+    max_array_len: None,
+    max_object_keys: None,
+    lenient: false,
+    frequency_keys: false,
+    sort_values: false,
+    dedup: true,
+    share_schemas: true,
+    verify_on_compress: false,
+    typed_sections: false,
+    error_on_precision_loss: false,
+    extend_schemas: false,
+    pack_int_arrays: false,
+    max_value_len: None,
+    null_object_policy: NullObjectPolicy::Empty,
+    treat_empty_string_as_null: false,
+    max_dictionary_len: None,
 };
+
+impl Config {
+    /// Preset for decompressing untrusted input.
+    ///
+    /// Starts from [`CONFIG`] and bounds the work a single payload can cause
+    /// in [`try_decompress_with_config`](crate::try_decompress_with_config):
+    ///
+    /// | Limit | Value |
+    /// |-------|-------|
+    /// | `max_depth` | `Some(128)` |
+    /// | `max_nodes` | `Some(1_000_000)` |
+    /// | `max_array_len` | `Some(100_000)` |
+    /// | `max_object_keys` | `Some(10_000)` |
+    ///
+    /// Reference bounds checks and cycle detection are always performed by
+    /// the fallible decoder and need no configuration.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use compress_json_rs::{Config, compress, try_decompress_with_config};
+    /// use serde_json::json;
+    ///
+    /// let config = Config::untrusted();
+    /// assert_eq!(config.max_depth, Some(128));
+    ///
+    /// let data = json!({"id": 1});
+    /// assert_eq!(try_decompress_with_config(&compress(&data), &config), Ok(data));
+    /// ```
+    pub const fn untrusted() -> Self {
+        Config {
+            max_depth: Some(128),
+            max_nodes: Some(1_000_000),
+            max_array_len: Some(100_000),
+            max_object_keys: Some(10_000),
+            ..CONFIG
+        }
+    }
+
+    /// Preset that rejects every lossy conversion during compression.
+    ///
+    /// Starts from [`CONFIG`] and turns the silent conversions of
+    /// [`compress_with_config`](crate::compress_with_config) into panics:
+    ///
+    /// | Option | Rejects |
+    /// |--------|---------|
+    /// | `error_on_nan` | NaN, which would otherwise become `null` |
+    /// | `error_on_infinite` | `Infinity` / `-Infinity`, which would otherwise become `null` |
+    /// | `error_on_precision_loss` | Integers beyond `2^53` that `f64` would round |
+    ///
+    /// `numeric_strings` stays off, so strings are never turned into numbers.
+    /// Only `simd_json` values can hold NaN or infinities; for those,
+    /// `try_compress_simd` reports the rejected value as an error.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use compress_json_rs::{Config, compress_with_config, decompress};
+    /// use serde_json::json;
+    ///
+    /// let config = Config::strict();
+    /// let data = json!({"id": 9007199254740992u64, "ratio": 0.5});
+    /// assert_eq!(decompress(compress_with_config(&data, &config)), data);
+    /// ```
+    pub const fn strict() -> Self {
+        Config {
+            error_on_nan: true,
+            error_on_infinite: true,
+            error_on_precision_loss: true,
+            ..CONFIG
+        }
+    }
+}
+
+impl Default for Config {
+    /// Returns the default configuration, identical to [`CONFIG`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use compress_json_rs::{Config, CONFIG};
+    ///
+    /// assert_eq!(Config::default(), CONFIG);
+    /// ```
+    fn default() -> Self {
+        CONFIG
+    }
+}
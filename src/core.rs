@@ -4,12 +4,12 @@
 //!
 //! # Compression
 //!
-//! The [`compress`] function takes a `serde_json::Value` and produces a [`Compressed`] tuple
-//! containing the deduplicated value store and a root key.
+//! The [`compress`] function takes a `serde_json::Value` and produces a [`Compressed`] struct
+//! holding the deduplicated value store (`values`) and the key of the root value (`root`).
 //!
 //! # Decompression
 //!
-//! The [`decompress`] function takes a [`Compressed`] tuple and reconstructs the original
+//! The [`decompress`] function takes a [`Compressed`] struct and reconstructs the original
 //! JSON value.
 //!
 //! # Format
@@ -23,19 +23,49 @@
 //! - `s|<str>` - escaped string (for strings that look like encoded values)
 //! - `a|<refs>` - array with pipe-separated element references
 //! - `o|<schema>|<refs>` - object with schema reference and value references
+//!   (a schema is an `a|` list of keys, optionally led by a base schema with
+//!   `extend_schemas`)
+//! - `c|<refs>` - string split into chunks (with `chunk_strings`)
+//! - `d|<ints>` - integer array packed as base-62 deltas (with `pack_int_arrays`)
+//! - `x|<base64>` - binary blob (decodes to its base64 text in plain JSON)
 //! - Plain string - unescaped string value
 //! - Empty string or `_` - null value
 
+use crate::config::{CONFIG, Config};
+#[cfg(feature = "arbitrary_precision")]
+use crate::encode::f64_loses_precision;
 use crate::encode::{
-    decode_bool, decode_key, decode_num, decode_special, decode_str, is_special_value,
+    decode_bool, decode_key, decode_num, decode_special, decode_str, is_special_value, needs_escape,
 };
-use crate::memory::{Key, add_value, make_memory, mem_to_values};
+use crate::error::{CompressError, Warning};
+use crate::frequency::{frequency_order, renumber};
+use crate::key::Key;
+use crate::memory::{
+    JsonNode, Memory, NodeKind, add_value, add_value_owned, exceeds_max_value_len,
+    get_value_key_owned, kept_fields, make_memory, make_memory_with_config, mem_to_values,
+    packs_ints, stored_int,
+};
+use crate::number::checked_s_to_int;
+use crate::packed::{decode_packed, packed_elements};
+use crate::parse::parse_json;
+use crate::path::escape_token;
+use crate::sections::section_order;
+use crate::sorted::sorted_order;
+use serde::de::{self, DeserializeOwned};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::value::RawValue;
 use serde_json::{Map, Number, Value};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
 
-/// Compressed representation: (values array, root key).
+/// Compressed representation: values array and root key.
+///
+/// `values` holds the encoded strings representing all unique values, and
+/// `root` is the base-62 key pointing to the root value in that array.
 ///
-/// The first element is a vector of encoded strings representing all unique values.
-/// The second element is a base-62 key pointing to the root value in the array.
+/// Serialized with serde as the two-element array `[values, root]`, the same
+/// wire format produced by the JavaScript implementation.
 ///
 /// # Example
 ///
@@ -46,11 +76,144 @@ use serde_json::{Map, Number, Value};
 /// let data = json!({"name": "Alice"});
 /// let compressed: Compressed = compress(&data);
 ///
-/// let (values, root) = compressed;
-/// assert!(!values.is_empty());
-/// assert!(!root.is_empty());
+/// assert!(!compressed.values.is_empty());
+/// assert!(!compressed.root.is_null());
+///
+/// // Iterate the stored values directly
+/// assert_eq!((&compressed).into_iter().count(), compressed.values.len());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Compressed {
+    /// Encoded store entries
+    pub values: Vec<String>,
+    /// Key of the root value
+    pub root: Key,
+}
+
+impl Compressed {
+    /// Create a compressed representation from its parts.
+    ///
+    /// `root` accepts a [`Key`] or anything convertible into one, such as a
+    /// `String` or `&str`.
+    pub fn new(values: Vec<String>, root: impl Into<Key>) -> Self {
+        Compressed {
+            values,
+            root: root.into(),
+        }
+    }
+
+    /// Total bytes of all stored strings plus the root key.
+    ///
+    /// A cheap size metric for logging; the serialized JSON form is larger
+    /// by the quotes, commas and escapes around each string.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use compress_json_rs::compress;
+    /// use serde_json::json;
+    ///
+    /// let c = compress(&json!(["ab", "ab", "cde"]));
+    /// // "ab" + "cde" + "a|0|0|1" + root "2"
+    /// assert_eq!(c.byte_len(), 2 + 3 + 7 + 1);
+    /// ```
+    pub fn byte_len(&self) -> usize {
+        self.values.iter().map(String::len).sum::<usize>() + self.root.as_str().len()
+    }
+
+    /// Number of stored entries.
+    ///
+    /// Each distinct value is stored once, so this is the number of unique
+    /// values (and schemas), not the size of the decompressed tree; see
+    /// [`expanded_node_count`](crate::expanded_node_count) for that.
+    pub fn store_len(&self) -> usize {
+        self.values.len()
+    }
+}
+
+impl From<(Vec<String>, Key)> for Compressed {
+    fn from((values, root): (Vec<String>, Key)) -> Self {
+        Compressed { values, root }
+    }
+}
+
+impl From<Compressed> for (Vec<String>, Key) {
+    fn from(c: Compressed) -> Self {
+        (c.values, c.root)
+    }
+}
+
+impl<'a> IntoIterator for &'a Compressed {
+    type Item = &'a String;
+    type IntoIter = std::slice::Iter<'a, String>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.values.iter()
+    }
+}
+
+impl Serialize for Compressed {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (&self.values, &self.root).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Compressed {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <(Vec<String>, Key)>::deserialize(deserializer).map(Compressed::from)
+    }
+}
+
+/// Convert into the wire form `[[...values...], root]` as a JSON value.
+impl From<Compressed> for Value {
+    fn from(c: Compressed) -> Self {
+        let values = c.values.into_iter().map(Value::String).collect();
+        Value::Array(vec![
+            Value::Array(values),
+            Value::String(c.root.into_string()),
+        ])
+    }
+}
+
+/// Parse the wire form `[[...values...], root]` from a JSON value.
+///
+/// Besides the shape (a two-element array of a string array and a string),
+/// the root must be a valid key that is null or points into the values, or
+/// a scalar written inline with an empty store (see [`decode`]).
+/// The entries themselves are not checked; use
+/// [`try_decompress`](crate::try_decompress) for that.
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{Compressed, compress};
+/// use serde_json::{Value, json};
+///
+/// let compressed = compress(&json!({"a": [1, 2]}));
+/// let wire = Value::from(compressed.clone());
+/// assert_eq!(Compressed::try_from(wire).unwrap(), compressed);
+///
+/// assert!(Compressed::try_from(json!([["a|0"], "5"])).is_err());
+/// assert!(Compressed::try_from(json!({"values": []})).is_err());
 /// ```
-pub type Compressed = (Vec<String>, Key);
+impl TryFrom<Value> for Compressed {
+    type Error = serde_json::Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let c: Compressed = serde_json::from_value(value)?;
+        if !c.root.is_null()
+            && !is_inline_root(&c.values, c.root.as_str())
+            && checked_s_to_int(c.root.as_str()).is_none_or(|i| i >= c.values.len())
+        {
+            return Err(serde::de::Error::custom(format_args!(
+                "root key {:?} does not reference one of the {} values",
+                c.root.as_str(),
+                c.values.len()
+            )));
+        }
+        Ok(c)
+    }
+}
 
 /// Compress a JSON value into its compressed representation.
 ///
@@ -63,14 +226,14 @@ pub type Compressed = (Vec<String>, Key);
 ///
 /// # Returns
 ///
-/// A [`Compressed`] tuple containing:
-/// - `Vec<String>` - The deduplicated value store
-/// - `String` - The base-62 key of the root value
+/// A [`Compressed`] struct with the fields:
+/// - `values: Vec<String>` - The deduplicated value store
+/// - `root: Key` - The base-62 key of the root value
 ///
 /// # Example
 ///
 /// ```rust
-/// use compress_json_rs::compress;
+/// use compress_json_rs::{Compressed, compress};
 /// use serde_json::json;
 ///
 /// let data = json!({
@@ -80,7 +243,7 @@ pub type Compressed = (Vec<String>, Key);
 ///     ]
 /// });
 ///
-/// let (values, root) = compress(&data);
+/// let Compressed { values, root } = compress(&data);
 ///
 /// // Values are deduplicated - the schema "id,name" appears once
 /// println!("Compressed to {} values", values.len());
@@ -102,93 +265,663 @@ pub fn compress(o: &Value) -> Compressed {
     let mut mem = make_memory();
     let root = add_value(&mut mem, o);
     let values = mem_to_values(&mem);
-    (values, root)
+    Compressed { values, root }
 }
 
-/// Decode an object from its encoded string representation.
-fn decode_object(values: &Vec<String>, s: &str) -> Value {
-    if s == "o|" {
-        return Value::Object(Map::new());
-    }
-    let parts: Vec<&str> = s.split('|').collect();
-    let key_id = parts[1];
-    let keys_val = decode(values, key_id);
-    let keys: Vec<String> = match keys_val {
-        Value::String(ref k) => vec![k.clone()],
-        Value::Array(arr) => arr
-            .into_iter()
-            .map(|v| match v {
-                Value::String(s) => s,
-                other => panic!("Invalid key type in decode_object: {other:?}"),
-            })
-            .collect(),
-        other => panic!("Invalid keys in decode_object: {other:?}"),
+/// Compress an owned JSON value into its compressed representation.
+///
+/// Produces exactly the same output as [`compress`], but takes the value by
+/// ownership so string contents can be moved into the store instead of
+/// being cloned. Prefer this for string-heavy documents that are not needed
+/// after compression.
+///
+/// # Arguments
+///
+/// * `o` - The JSON value to compress, consumed by the call
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{compress, compress_owned};
+/// use serde_json::json;
+///
+/// let data = json!({"name": "Alice", "tags": ["a", "b"]});
+/// let expected = compress(&data);
+///
+/// assert_eq!(compress_owned(data), expected);
+/// ```
+pub fn compress_owned(o: Value) -> Compressed {
+    let mut mem = make_memory();
+    let root = add_value_owned(&mut mem, o);
+    let values = mem_to_values(&mem);
+    Compressed { values, root }
+}
+
+/// Compress a JSON value using a custom configuration.
+///
+/// Behaves like [`compress`], but applies `config` instead of the default
+/// [`CONFIG`](crate::CONFIG).
+///
+/// # Arguments
+///
+/// * `o` - A reference to the JSON value to compress
+/// * `config` - The configuration to compress with
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{Config, compress_with_config, decompress};
+/// use serde_json::json;
+///
+/// let config = Config { sort_key: true, ..Config::default() };
+/// let data = json!({"b": 1, "a": 2});
+///
+/// let compressed = compress_with_config(&data, &config);
+/// assert_eq!(decompress(compressed), data);
+/// ```
+///
+/// # Panics
+///
+/// Panics on NaN or infinite numbers when the configuration asks to reject
+/// them, on values [`try_compress_with_config`] would report, and, with
+/// [`Config::verify_on_compress`], if the output does not decompress to `o`
+/// (where [`try_compress_with_config`] returns
+/// [`CompressError::VerifyFailed`]).
+pub fn compress_with_config(o: &Value, config: &Config) -> Compressed {
+    compress_in(make_memory_with_config(config), o).unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// Compress `o` into `mem`, then apply the store passes of the memory's
+/// configuration.
+///
+/// Fails only with [`Config::verify_on_compress`], if the output does not
+/// decompress to `o`.
+pub(crate) fn compress_in(mut mem: Memory, o: &Value) -> Result<Compressed, CompressError> {
+    let config = mem.config;
+    let root = add_value(&mut mem, o);
+    let values = mem_to_values(&mem);
+    finish_store(o, Compressed { values, root }, &config).map(|(compressed, _)| compressed)
+}
+
+/// Apply the store passes of `config` to a store just built from `o`.
+///
+/// Returns the arranged store with the order of [`arrange_order`], if any
+/// pass applied. Fails only with [`Config::verify_on_compress`], if the
+/// arranged store does not decompress to `o`.
+pub(crate) fn finish_store(
+    o: &Value,
+    compressed: Compressed,
+    config: &Config,
+) -> Result<(Compressed, Option<Vec<usize>>), CompressError> {
+    let order = arrange_order(&compressed, config);
+    let compressed = match &order {
+        Some(order) => renumber(compressed, order),
+        None => compressed,
     };
-    let mut map = Map::new();
-    for (i, part) in parts.iter().enumerate().skip(2) {
-        let v = decode(values, part);
-        let key = keys[i - 2].clone();
-        map.insert(key, v);
+    if config.verify_on_compress {
+        verify_compressed(o, &compressed, config)?;
     }
-    Value::Object(map)
+    Ok((compressed, order))
 }
 
-/// Decode an array from its encoded string representation.
-fn decode_array(values: &Vec<String>, s: &str) -> Value {
-    if s == "a|" {
-        return Value::Array(Vec::new());
+/// Renumber a finished store as `config` asks, see [`arrange_order`].
+pub(crate) fn arrange_store(compressed: Compressed, config: &Config) -> Compressed {
+    match arrange_order(&compressed, config) {
+        Some(order) => renumber(compressed, &order),
+        None => compressed,
     }
-    let parts: Vec<&str> = s.split('|').collect();
-    let mut arr = Vec::with_capacity(parts.len() - 1);
-    for part in parts.iter().skip(1) {
-        let v = decode(values, part);
-        arr.push(v);
+}
+
+/// The entry order, as taken by [`renumber`], that the store passes of
+/// `config` put a finished store in: [`Config::sort_values`], then
+/// [`Config::frequency_keys`], then [`Config::typed_sections`]. `None` if no pass is enabled.
+pub(crate) fn arrange_order(c: &Compressed, config: &Config) -> Option<Vec<usize>> {
+    type Pass = fn(&Compressed) -> Vec<usize>;
+    let passes: [(bool, Pass); 3] = [
+        (config.sort_values, sorted_order),
+        (config.frequency_keys, frequency_order),
+        (config.typed_sections, section_order),
+    ];
+    let mut order: Option<Vec<usize>> = None;
+    for (_, pass) in passes.into_iter().filter(|&(enabled, _)| enabled) {
+        // Each pass orders the store as renumbered by the passes before it
+        order = Some(match order {
+            None => pass(c),
+            Some(order) => pass(&renumber(c.clone(), &order))
+                .into_iter()
+                .map(|i| order[i])
+                .collect(),
+        });
     }
-    Value::Array(arr)
+    order
 }
 
-/// Decode a single key into a JSON Value.
+/// Compress a JSON value, reporting rejected values instead of panicking.
 ///
-/// This is a lower-level function that decodes a single reference key
-/// from the values array. It's used internally by [`decompress`] but
-/// can also be used directly for custom decoding scenarios.
+/// Scans `o` for values that `config` rejects before compressing anything:
+/// integers `f64` would round (with [`Config::error_on_precision_loss`]) and
+/// strings or object keys longer than [`Config::max_value_len`]. The first
+/// one found is returned as a [`CompressError`] with its JSON Pointer;
+/// otherwise the result is the same as [`compress_with_config`].
 ///
 /// # Arguments
 ///
-/// * `values` - The values array from a compressed representation
-/// * `key` - A base-62 encoded key string
+/// * `o` - A reference to the JSON value to compress
+/// * `config` - The configuration to compress with
 ///
 /// # Returns
 ///
-/// The decoded `serde_json::Value`
+/// The compressed form, or the [`CompressError`] for the first rejected
+/// value in document order. With [`Config::verify_on_compress`], an output
+/// that does not decompress to `o` is reported as
+/// [`CompressError::VerifyFailed`].
 ///
 /// # Example
 ///
 /// ```rust
-/// use compress_json_rs::{compress, decode};
+/// use compress_json_rs::{CompressError, Config, try_compress_with_config};
 /// use serde_json::json;
 ///
-/// let data = json!("hello");
-/// let (values, root) = compress(&data);
+/// let config = Config { max_value_len: Some(8), ..Config::default() };
+/// let data = json!({"tags": ["short", "far too long"]});
 ///
-/// let decoded = decode(&values, &root);
-/// assert_eq!(decoded, json!("hello"));
+/// assert_eq!(
+///     try_compress_with_config(&data, &config),
+///     Err(CompressError::ValueTooLong {
+///         path: "/tags/1".to_string(),
+///         len: 12,
+///         limit: 8,
+///     })
+/// );
+/// ```
+pub fn try_compress_with_config(o: &Value, config: &Config) -> Result<Compressed, CompressError> {
+    check_value(o, config, &mut String::new())?;
+    compress_in(make_memory_with_config(config), o)
+}
+
+/// Find the first value in `o` that `config` rejects, tracking its path.
+pub(crate) fn check_value<'a, N: JsonNode<'a>>(
+    o: N,
+    config: &Config,
+    path: &mut String,
+) -> Result<(), CompressError> {
+    match o.into_kind() {
+        // Integers kept as text (with `arbitrary_precision`) are exact
+        NodeKind::Int(i) if config.error_on_precision_loss && stored_int(i) != i => {
+            Err(CompressError::UnsupportedValue {
+                path: path.clone(),
+                value: i.to_string(),
+            })
+        }
+        NodeKind::Float(f) => {
+            let value = if f.is_nan() && !config.preserve_nan && config.error_on_nan {
+                "NaN"
+            } else if f.is_infinite() && !config.preserve_infinite && config.error_on_infinite {
+                special_name(f)
+            } else {
+                return Ok(());
+            };
+            Err(CompressError::UnsupportedValue {
+                path: path.clone(),
+                value: value.to_string(),
+            })
+        }
+        NodeKind::Str(s) => check_len(&s, config, path),
+        // Packed integers are stored exactly
+        NodeKind::Array(arr) if packs_ints::<N>(config, &arr) => Ok(()),
+        NodeKind::Array(arr) => {
+            for (i, v) in arr.into_iter().enumerate() {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&i.to_string());
+                check_value(v, config, path)?;
+                path.truncate(len);
+            }
+            Ok(())
+        }
+        NodeKind::Object(map) => {
+            for (k, v) in kept_fields(config, map, N::stores_null) {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&escape_token(k.as_ref()));
+                check_len(k.as_ref(), config, path)?;
+                check_value(v, config, path)?;
+                path.truncate(len);
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The name of a NaN or infinite number in errors and warnings.
+fn special_name(f: f64) -> &'static str {
+    if f.is_nan() {
+        "NaN"
+    } else if f.is_sign_positive() {
+        "Infinity"
+    } else {
+        "-Infinity"
+    }
+}
+
+/// Compress a JSON value, reporting each lossy change made to it.
+///
+/// Values that the configuration neither preserves nor rejects are changed
+/// silently by [`compress_with_config`]; this compresses the same way and
+/// also returns a [`Warning`] for each of them, in document order. For a
+/// `serde_json::Value` that means integers beyond `2^53` rounded through
+/// `f64` (unless the `arbitrary_precision` feature keeps their digits).
+/// With the `simd-json` feature, `compress_simd_with_report` also reports
+/// NaN and infinities turned into `null`.
+///
+/// # Arguments
+///
+/// * `o` - A reference to the JSON value to compress
+/// * `config` - The configuration to compress with
+///
+/// # Returns
+///
+/// The compressed form and the warnings, empty if nothing was lost
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{CONFIG, compress_with_report};
+/// use serde_json::json;
+///
+/// let (_, warnings) = compress_with_report(&json!({"id": 9007199254740993u64}), &CONFIG);
+/// # #[cfg(not(feature = "arbitrary_precision"))]
+/// assert_eq!(
+///     warnings[0].to_string(),
+///     "9007199254740993 at /id rounded to 9007199254740992"
+/// );
 /// ```
 ///
 /// # Panics
 ///
-/// Panics if the key references an invalid index or the encoded value is malformed.
-pub fn decode(values: &Vec<String>, key: &str) -> Value {
-    if key.is_empty() || key == "_" {
-        return Value::Null;
+/// Panics where [`compress_with_config`] does, e.g. on a rejected integer
+/// with [`Config::error_on_precision_loss`].
+pub fn compress_with_report(o: &Value, config: &Config) -> (Compressed, Vec<Warning>) {
+    let compressed = compress_with_config(o, config);
+    let mut warnings = Vec::new();
+    report_value(o, config, &mut String::new(), &mut warnings);
+    (compressed, warnings)
+}
+
+/// Collect a [`Warning`] for each lossy value in `o`, tracking its path.
+pub(crate) fn report_value<'a, N: JsonNode<'a>>(
+    o: N,
+    config: &Config,
+    path: &mut String,
+    warnings: &mut Vec<Warning>,
+) {
+    match o.into_kind() {
+        // Integers kept as text (with `arbitrary_precision`) are exact
+        NodeKind::Int(i) => warnings.extend(rounded_integer(i, path)),
+        NodeKind::Float(f)
+            if (f.is_nan() && !config.preserve_nan)
+                || (f.is_infinite() && !config.preserve_infinite) =>
+        {
+            warnings.push(Warning::ReplacedWithNull {
+                path: path.clone(),
+                value: special_name(f).to_string(),
+            });
+        }
+        // Packed integers are stored exactly
+        NodeKind::Array(arr) if packs_ints::<N>(config, &arr) => {}
+        NodeKind::Array(arr) => {
+            for (i, v) in arr.into_iter().enumerate() {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&i.to_string());
+                report_value(v, config, path, warnings);
+                path.truncate(len);
+            }
+        }
+        NodeKind::Object(map) => {
+            for (k, v) in map {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&escape_token(k.as_ref()));
+                report_value(v, config, path, warnings);
+                path.truncate(len);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The warning for an integer that `f64` rounds, if it does.
+pub(crate) fn rounded_integer(i: i128, path: &str) -> Option<Warning> {
+    let stored = stored_int(i);
+    (stored != i).then(|| Warning::Rounded {
+        path: path.to_string(),
+        value: i.to_string(),
+        stored: stored.to_string(),
+    })
+}
+
+/// Reject a string longer than [`Config::max_value_len`] at `path`.
+pub(crate) fn check_len(s: &str, config: &Config, path: &str) -> Result<(), CompressError> {
+    match exceeds_max_value_len(config, s) {
+        Some((len, limit)) => Err(CompressError::ValueTooLong {
+            path: path.to_string(),
+            len,
+            limit,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Check that `c` decompresses to `o`.
+///
+/// Used by [`Config::verify_on_compress`].
+pub(crate) fn verify_compressed(
+    o: &Value,
+    c: &Compressed,
+    config: &Config,
+) -> Result<(), CompressError> {
+    verify_entry(o, &c.values, &c.root, config)
+}
+
+/// Check that the entry `key` of `values` decodes to `o`, reporting the
+/// path of the first difference relative to `o`.
+pub(crate) fn verify_entry(
+    o: &Value,
+    values: &[String],
+    key: &Key,
+    config: &Config,
+) -> Result<(), CompressError> {
+    let restored = decode(values, key);
+    let mut path = String::new();
+    if same_value(o, &restored, config, &mut path) {
+        Ok(())
+    } else {
+        Err(CompressError::VerifyFailed { path })
+    }
+}
+
+/// Compare an input value with its decompressed form, leaving `path` at the
+/// first difference if they are not the same.
+///
+/// Numbers are equal if their `f64` values are, since compression stores
+/// them that way; with `numeric_strings`, an integer string matches the
+/// number it was stored as, and with `treat_empty_string_as_null` an empty
+/// string matches `null`.
+fn same_value(input: &Value, output: &Value, config: &Config, path: &mut String) -> bool {
+    match (input, output) {
+        (Value::Number(a), Value::Number(b)) => a == b || a.as_f64() == b.as_f64(),
+        (Value::String(s), Value::Number(n)) if config.numeric_strings => *s == n.to_string(),
+        (Value::String(s), Value::Null) if config.treat_empty_string_as_null => s.is_empty(),
+        (Value::Array(a), Value::Array(b)) => {
+            if a.len() != b.len() {
+                return false;
+            }
+            for (i, (x, y)) in a.iter().zip(b).enumerate() {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&i.to_string());
+                if !same_value(x, y, config, path) {
+                    return false;
+                }
+                path.truncate(len);
+            }
+            true
+        }
+        (Value::Object(a), Value::Object(b)) => {
+            // Dropped `null` fields are expected to be missing
+            let kept: Vec<(&String, &Value)> =
+                kept_fields(config, a, <&Value>::stores_null).collect();
+            if kept.len() != b.len() {
+                return false;
+            }
+            for (k, x) in kept {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&escape_token(k));
+                if !b.get(k).is_some_and(|y| same_value(x, y, config, path)) {
+                    return false;
+                }
+                path.truncate(len);
+            }
+            true
+        }
+        _ => input == output,
+    }
+}
+
+/// Compress several independent documents into one shared store.
+///
+/// Values, schemas and subtrees repeated across documents are stored once,
+/// so the batch is smaller than compressing each document on its own. The
+/// returned keys give each document's root in the shared values array, in
+/// input order; the batch's own root is an array of all documents, so
+/// [`decompress`] returns them as one `Value::Array`.
+///
+/// # Arguments
+///
+/// * `docs` - The documents to compress
+///
+/// # Returns
+///
+/// The shared compressed form and one root key per document
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{compress_batch, decode};
+/// use serde_json::json;
+///
+/// let docs = [json!({"status": "active"}), json!(["active", "idle"])];
+/// let (batch, roots) = compress_batch(&docs);
+///
+/// assert_eq!(decode(&batch.values, &roots[0]), docs[0]);
+/// assert_eq!(decode(&batch.values, &roots[1]), docs[1]);
+/// ```
+pub fn compress_batch(docs: &[Value]) -> (Compressed, Vec<Key>) {
+    let mut mem = make_memory();
+    let roots: Vec<Key> = docs.iter().map(|doc| add_value(&mut mem, doc)).collect();
+    let mut acc = String::from("a|");
+    for (i, root) in roots.iter().enumerate() {
+        if i > 0 {
+            acc.push('|');
+        }
+        acc.push_str(if root.is_null() { "_" } else { root.as_str() });
+    }
+    let root = get_value_key_owned(&mut mem, acc);
+    let values = mem_to_values(&mem);
+    (Compressed { values, root }, roots)
+}
+
+/// Compress an array of shared subtrees, storing each `Rc` once by identity.
+///
+/// `serde_json::Value` owns its children, so pointer sharing can only live
+/// where the caller holds the `Rc`s: here, the elements of the root array.
+/// Elements pointing at the same allocation are encoded once and reuse its
+/// key without walking or hashing the subtree again; distinct allocations
+/// still fall back to the usual structural dedup.
+///
+/// # Arguments
+///
+/// * `items` - The root array's elements
+///
+/// # Returns
+///
+/// The compressed form, which decompresses to a `Value::Array` of the items
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{compress_shared, decompress};
+/// use serde_json::json;
+/// use std::rc::Rc;
+///
+/// let user = Rc::new(json!({"name": "Alice", "roles": ["admin"]}));
+/// let compressed = compress_shared(&[Rc::clone(&user), Rc::clone(&user)]);
+///
+/// assert_eq!(decompress(compressed), json!([*user, *user]));
+/// ```
+pub fn compress_shared(items: &[Rc<Value>]) -> Compressed {
+    let mut mem = make_memory();
+    let mut seen: HashMap<*const Value, Key> = HashMap::new();
+    let mut acc = String::from("a|");
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            acc.push('|');
+        }
+        let key = seen
+            .entry(Rc::as_ptr(item))
+            .or_insert_with(|| add_value(&mut mem, item));
+        acc.push_str(if key.is_null() { "_" } else { key.as_str() });
     }
-    let id = decode_key(key);
-    let v_str = &values[id];
+    let root = get_value_key_owned(&mut mem, acc);
+    let values = mem_to_values(&mem);
+    Compressed { values, root }
+}
+
+/// Parse JSON text and compress it.
+///
+/// Equivalent to parsing with `serde_json::from_str` and calling [`compress`].
+///
+/// # Arguments
+///
+/// * `json` - JSON text to parse and compress
+///
+/// # Returns
+///
+/// The [`Compressed`] representation, or the parse error
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{compress_str, decompress};
+/// use serde_json::json;
+///
+/// let compressed = compress_str(r#"{"name": "Alice"}"#).unwrap();
+/// assert_eq!(decompress(compressed), json!({"name": "Alice"}));
+/// ```
+pub fn compress_str(json: &str) -> Result<Compressed, serde_json::Error> {
+    compress_str_with_config(json, &CONFIG)
+}
+
+/// Parse JSON text and compress it using a custom configuration.
+///
+/// When [`Config::strict_duplicate_keys`] is set, text containing an object
+/// with a repeated key is rejected instead of silently keeping the last value.
+///
+/// # Arguments
+///
+/// * `json` - JSON text to parse and compress
+/// * `config` - The configuration to parse and compress with
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{Config, compress_str_with_config};
+///
+/// let config = Config { strict_duplicate_keys: true, ..Config::default() };
+/// assert!(compress_str_with_config(r#"{"a": 1, "a": 2}"#, &config).is_err());
+/// ```
+pub fn compress_str_with_config(
+    json: &str,
+    config: &Config,
+) -> Result<Compressed, serde_json::Error> {
+    let value = parse_json(json, config)?;
+    Ok(compress_with_config(&value, config))
+}
+
+/// Decode an object from its encoded string representation.
+///
+/// Nested references are resolved through `resolve`, so callers can plug in
+/// caching or other strategies while sharing the parsing logic.
+fn decode_object(s: &str, resolve: &mut impl FnMut(&str) -> Value) -> Value {
+    if s == "o|" {
+        return Value::Object(Map::new());
+    }
+    let parts: Vec<&str> = s.split('|').collect();
+    let key_id = parts[1];
+    let keys = schema_keys(resolve(key_id));
+    // Pre-size for the schema's keys to avoid rehashing wide objects
+    let mut map = Map::with_capacity(keys.len());
+    for (i, part) in parts.iter().enumerate().skip(2) {
+        let v = resolve(part);
+        let key = keys[i - 2].clone();
+        map.insert(key, v);
+    }
+    Value::Object(map)
+}
+
+/// Turn a decoded schema into the list of object keys.
+///
+/// A schema is either an array of key strings or, for single-key objects
+/// whose key is already stored, the key string itself. With
+/// [`Config::extend_schemas`], the array may start with a base schema,
+/// which decodes as a nested array whose keys come first.
+pub(crate) fn schema_keys(keys_val: Value) -> Vec<String> {
+    match keys_val {
+        Value::String(k) => vec![k],
+        Value::Array(arr) => {
+            let mut keys = Vec::with_capacity(arr.len());
+            for (i, v) in arr.into_iter().enumerate() {
+                match v {
+                    Value::String(s) => keys.push(s),
+                    base @ Value::Array(_) if i == 0 => keys.extend(schema_keys(base)),
+                    other => panic!("Invalid key type in decode_object: {other:?}"),
+                }
+            }
+            keys
+        }
+        other => panic!("Invalid keys in decode_object: {other:?}"),
+    }
+}
+
+/// Decode an array from its encoded string representation.
+fn decode_array(s: &str, resolve: &mut impl FnMut(&str) -> Value) -> Value {
+    if s == "a|" {
+        return Value::Array(Vec::new());
+    }
+    let parts: Vec<&str> = s.split('|').collect();
+    let mut arr = Vec::with_capacity(parts.len() - 1);
+    for part in parts.iter().skip(1) {
+        let v = resolve(part);
+        arr.push(v);
+    }
+    Value::Array(arr)
+}
+
+/// Decode a chunked string by concatenating its chunk entries.
+fn decode_chunks(s: &str, resolve: &mut impl FnMut(&str) -> Value) -> Value {
+    let mut out = String::new();
+    for part in s.split('|').skip(1) {
+        match resolve(part) {
+            Value::String(chunk) => out.push_str(&chunk),
+            other => panic!("Invalid chunk in decode_chunks: {other:?}"),
+        }
+    }
+    Value::String(out)
+}
+
+/// Decode a single stored entry, resolving nested references via `resolve`.
+pub(crate) fn decode_entry(v_str: &str, resolve: &mut impl FnMut(&str) -> Value) -> Value {
+    match v_str.get(..2) {
+        Some("o|") => decode_object(v_str, resolve),
+        Some("a|") => decode_array(v_str, resolve),
+        Some("c|") => decode_chunks(v_str, resolve),
+        _ => decode_scalar(v_str),
+    }
+}
+
+/// Whether an entry references other entries (`a|`, `o|` or `c|`).
+pub(crate) fn is_composite(v_str: &str) -> bool {
+    matches!(v_str.get(..2), Some("a|" | "o|" | "c|"))
+}
+
+/// Decode an entry that references no other entries.
+fn decode_scalar(v_str: &str) -> Value {
     // Determine value type by prefix and decode accordingly
     if v_str.starts_with("b|") {
         Value::Bool(decode_bool(v_str))
-    } else if v_str.starts_with("o|") {
-        decode_object(values, v_str)
     } else if is_special_value(v_str) {
         // Handle special values: N|+, N|-, N|0 (v3.2.0+)
         // Note: serde_json doesn't support Infinity/NaN directly,
@@ -213,25 +946,152 @@ pub fn decode(values: &Vec<String>, key: &str) -> Value {
                 return Value::Number(Number::from(u));
             }
         }
+        // Exact text, including digits an f64 cannot hold
+        #[cfg(feature = "arbitrary_precision")]
+        if f64_loses_precision(num_str)
+            && let Ok(n) = num_str.parse::<Number>()
+        {
+            return Value::Number(n);
+        }
         // fallback to float
         let num = decode_num(v_str);
         Value::Number(Number::from_f64(num).expect("Invalid number"))
-    } else if v_str.starts_with("a|") {
-        decode_array(values, v_str)
+    } else if v_str.starts_with("d|") {
+        decode_packed(v_str)
+    } else if let Some(encoded) = v_str.strip_prefix("x|") {
+        // Binary blob: plain JSON has no bytes type, so keep the base64 text
+        Value::String(encoded.to_string())
     } else {
         // default to string
         Value::String(decode_str(v_str))
     }
 }
 
+/// Collect the reference keys held by a stored entry.
+///
+/// Arrays (`a|`), objects (`o|`, including the schema reference) and
+/// chunked strings (`c|`) refer to other entries; every other entry is a
+/// leaf and yields no references.
+/// Null markers (empty or `_`) are skipped.
+pub(crate) fn entry_refs(v_str: &str) -> Vec<&str> {
+    if v_str.starts_with("a|") || v_str.starts_with("o|") || v_str.starts_with("c|") {
+        v_str
+            .split('|')
+            .skip(1)
+            .filter(|k| !is_null_key(k))
+            .collect()
+    } else {
+        Vec::new()
+    }
+}
+
+/// Check whether a reference key denotes `null` (empty or the `_` marker).
+pub(crate) fn is_null_key(key: &str) -> bool {
+    key.is_empty() || key == "_"
+}
+
+/// Decode a single key into a JSON Value.
+///
+/// This is a lower-level function that decodes a single reference key
+/// from the values array. It's used internally by [`decompress`] but
+/// can also be used directly for custom decoding scenarios.
+///
+/// # Arguments
+///
+/// * `values` - The values array from a compressed representation
+/// * `key` - A base-62 encoded key string
+///
+/// # Returns
+///
+/// The decoded `serde_json::Value`
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{Compressed, compress, decode};
+/// use serde_json::json;
+///
+/// let data = json!("hello");
+/// let Compressed { values, root } = compress(&data);
+///
+/// let decoded = decode(&values, &root);
+/// assert_eq!(decoded, json!("hello"));
+/// ```
+///
+/// # Inline Roots
+///
+/// As a compatibility extension, a root that is not a key, with an empty
+/// `values` array, is decoded as a scalar entry itself: some minimal
+/// encoders write a lone scalar as `[[], "b|T"]` instead of storing it.
+/// A string made only of key characters must be escaped (`s|abc`) to be
+/// read this way. No key can reference an empty store, so this never
+/// changes how a regular payload decodes.
+///
+/// ```rust
+/// use compress_json_rs::{Key, decode};
+/// use serde_json::json;
+///
+/// assert_eq!(decode(&[], &Key::from("b|T")), json!(true));
+/// assert_eq!(decode(&[], &Key::from("n|2.5")), json!(2.5));
+/// ```
+///
+/// # Panics
+///
+/// Panics if the key references an invalid index or the encoded value is malformed.
+pub fn decode(values: &[String], key: &Key) -> Value {
+    if is_inline_root(values, key.as_str()) {
+        return decode_scalar(key.as_str());
+    }
+    decode_at(values, key.as_str())
+}
+
+/// Whether `key` is a scalar entry written in place of a root key: it
+/// has a character outside the base-62 alphabet and there is no store for
+/// it to reference.
+pub(crate) fn is_inline_root(values: &[String], key: &str) -> bool {
+    values.is_empty()
+        && !is_null_key(key)
+        && !is_composite(key)
+        && !key.bytes().all(|b| b.is_ascii_alphanumeric())
+}
+
+/// The store to look the root up in, and the key to look it up by.
+///
+/// An inline scalar root (see [`is_inline_root`]) becomes the only entry of
+/// a store of its own, so indexing the returned store with the returned key
+/// works for every root [`decode`] accepts.
+pub(crate) fn root_store<'a>(values: &'a [String], root: &'a str) -> (Cow<'a, [String]>, &'a str) {
+    if is_inline_root(values, root) {
+        (Cow::Owned(vec![root.to_string()]), "0")
+    } else {
+        (Cow::Borrowed(values), root)
+    }
+}
+
+/// Decode the value referenced by a raw reference segment.
+///
+/// Used internally where references are sliced out of `a|`/`o|`/`c|`
+/// entries rather than held as [`Key`]s.
+pub(crate) fn decode_at(values: &[String], key: &str) -> Value {
+    if is_null_key(key) {
+        return Value::Null;
+    }
+    let v_str = values[decode_key(key)].as_str();
+    // Leaves (and primitive roots) need no resolver
+    if !is_composite(v_str) {
+        return decode_scalar(v_str);
+    }
+    decode_entry(v_str, &mut |k| decode_at(values, k))
+}
+
 /// Decompress a compressed representation back into JSON.
 ///
-/// Takes a [`Compressed`] tuple produced by [`compress`] and reconstructs
+/// Takes a [`Compressed`] struct produced by [`compress`] and reconstructs
 /// the original JSON value.
 ///
 /// # Arguments
 ///
-/// * `c` - The compressed representation tuple
+/// * `c` - The compressed representation (`values` store and `root` key)
 ///
 /// # Returns
 ///
@@ -265,7 +1125,336 @@ pub fn decode(values: &Vec<String>, key: &str) -> Value {
 /// Note: The compressed form preserves `Infinity`, `-Infinity`, and `NaN`
 /// with special encodings (`N|+`, `N|-`, `N|0`) for cross-platform
 /// compatibility with JavaScript and Python implementations.
+///
+/// A scalar root with an empty store (e.g. `[[], "b|T"]`) is accepted as an
+/// inline value, as described on [`decode`].
 pub fn decompress(c: Compressed) -> Value {
-    let (values, root) = c;
+    let Compressed { mut values, root } = c;
+    // Fast path for a plain string root (e.g. a token): move it out of the
+    // store instead of copying it
+    if !is_null_key(root.as_str()) && !values.is_empty() {
+        let id = decode_key(root.as_str());
+        if values.get(id).is_some_and(|v| !needs_escape(v)) {
+            return Value::String(values.swap_remove(id));
+        }
+    }
     decode(&values, &root)
 }
+
+/// Decompress into an existing value, reusing its allocations.
+///
+/// Containers in `target` that already have the right type are updated in
+/// place: arrays are truncated or extended to the new length and objects
+/// drop the keys the new value lacks, then each element or field is filled
+/// recursively. Only leaves and mismatched subtrees are replaced. When
+/// decompressing a stream of similar snapshots into one value (e.g. UI
+/// state), most of the existing tree is kept instead of rebuilt.
+///
+/// Afterwards `target` equals what [`decompress`] would return.
+///
+/// # Arguments
+///
+/// * `c` - The compressed representation
+/// * `target` - The value to overwrite
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{compress, decompress_into_value};
+/// use serde_json::{Value, json};
+///
+/// let mut state = Value::Null;
+/// decompress_into_value(&compress(&json!({"items": [1, 2, 3], "page": 1})), &mut state);
+/// decompress_into_value(&compress(&json!({"items": [1, 2, 4], "page": 2})), &mut state);
+/// assert_eq!(state, json!({"items": [1, 2, 4], "page": 2}));
+/// ```
+///
+/// # Panics
+///
+/// Panics if the root key references an invalid index or the encoded value
+/// is malformed.
+pub fn decompress_into_value(c: &Compressed, target: &mut Value) {
+    let (values, root) = root_store(&c.values, c.root.as_str());
+    fill_at(&values, root, target);
+}
+
+/// Overwrite `target` with the value referenced by `key`, in place where
+/// the container types match.
+fn fill_at(values: &[String], key: &str, target: &mut Value) {
+    if is_null_key(key) {
+        *target = Value::Null;
+        return;
+    }
+    let v_str = values[decode_key(key)].as_str();
+    match target {
+        Value::Array(arr) if v_str.starts_with("a|") => {
+            let refs: Vec<&str> = match &v_str[2..] {
+                "" => Vec::new(),
+                body => body.split('|').collect(),
+            };
+            arr.truncate(refs.len());
+            arr.resize(refs.len(), Value::Null);
+            for (r, item) in refs.into_iter().zip(arr.iter_mut()) {
+                fill_at(values, r, item);
+            }
+        }
+        Value::Object(map) if v_str.starts_with("o|") => {
+            if v_str == "o|" {
+                map.clear();
+                return;
+            }
+            let mut parts = v_str[2..].split('|');
+            let keys = schema_keys(decode_at(values, parts.next().unwrap_or_default()));
+            let wanted: HashSet<&str> = keys.iter().map(String::as_str).collect();
+            map.retain(|k, _| wanted.contains(k.as_str()));
+            for (k, r) in keys.into_iter().zip(parts) {
+                match map.get_mut(&k) {
+                    Some(field) => fill_at(values, r, field),
+                    None => {
+                        map.insert(k, decode_at(values, r));
+                    }
+                }
+            }
+        }
+        _ => *target = decode_entry(v_str, &mut |k| decode_at(values, k)),
+    }
+}
+
+/// Decompress a compressed representation straight into a [`RawValue`].
+///
+/// This is useful when the decompressed JSON is going to be embedded into a
+/// larger document without being inspected, e.g. when splicing a stored
+/// sub-document into an API response.
+///
+/// # Arguments
+///
+/// * `c` - The compressed representation (`values` store and `root` key)
+///
+/// # Returns
+///
+/// The serialized JSON text wrapped in a `RawValue`, or the serialization error
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{compress, decompress_to_raw};
+/// use serde_json::json;
+///
+/// let data = json!({"name": "Alice"});
+/// let raw = decompress_to_raw(compress(&data)).unwrap();
+///
+/// assert_eq!(raw.get(), r#"{"name":"Alice"}"#);
+/// ```
+pub fn decompress_to_raw(c: Compressed) -> Result<Box<RawValue>, serde_json::Error> {
+    serde_json::value::to_raw_value(&decompress(c))
+}
+
+/// Decompress an object root into its fields, in stored order.
+///
+/// `serde_json::Map` sorts keys unless the crate-wide `preserve_order`
+/// feature is on, so [`decompress`] loses the order in which a payload's
+/// schema lists them (e.g. the insertion order of a JavaScript object).
+/// This returns the root object's fields as pairs in schema order instead,
+/// without pulling in `indexmap`. Field values are decoded as usual, so
+/// nested objects are plain `Value`s.
+///
+/// # Arguments
+///
+/// * `c` - The compressed representation (`values` store and `root` key)
+///
+/// # Returns
+///
+/// The root object's `(key, value)` pairs in schema order, or `None` if the
+/// root is not an object
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{Compressed, decompress_ordered};
+/// use serde_json::json;
+///
+/// // As stored by compress-json for `{"zeta": 1, "alpha": 2}`
+/// let values = ["zeta", "alpha", "a|0|1", "n|1", "n|2", "o|2|3|4"];
+/// let c = Compressed::new(values.map(String::from).to_vec(), "5");
+///
+/// assert_eq!(
+///     decompress_ordered(c),
+///     Some(vec![("zeta".to_string(), json!(1)), ("alpha".to_string(), json!(2))])
+/// );
+/// ```
+///
+/// # Panics
+///
+/// Panics if the root key references an invalid index or the encoded value
+/// is malformed.
+pub fn decompress_ordered(c: Compressed) -> Option<Vec<(String, Value)>> {
+    let (values, root) = root_store(&c.values, c.root.as_str());
+    if is_null_key(root) {
+        return None;
+    }
+    let rest = values[decode_key(root)].strip_prefix("o|")?;
+    if rest.is_empty() {
+        return Some(Vec::new());
+    }
+    let mut parts = rest.split('|');
+    let keys = schema_keys(decode_at(&values, parts.next().unwrap_or_default()));
+    Some(
+        keys.into_iter()
+            .zip(parts)
+            .map(|(k, part)| (k, decode_at(&values, part)))
+            .collect(),
+    )
+}
+
+/// Decompress an array root into a `Vec<T>`, one element at a time.
+///
+/// For the common array-of-rows payload, each element is decoded and
+/// deserialized into `T` on its own, so the whole array is never held as a
+/// `Value`. Fails before decoding anything if the root is not an array.
+///
+/// # Arguments
+///
+/// * `c` - The compressed representation (`values` store and `root` key)
+///
+/// # Returns
+///
+/// The deserialized rows, or an error if the root is not an array or an
+/// element does not deserialize into `T`
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{compress, decompress_rows};
+/// use serde::Deserialize;
+/// use serde_json::json;
+///
+/// #[derive(Debug, PartialEq, Deserialize)]
+/// struct User {
+///     id: u32,
+///     name: String,
+/// }
+///
+/// let data = json!([{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}]);
+/// let users: Vec<User> = decompress_rows(compress(&data)).unwrap();
+///
+/// assert_eq!(users[1], User { id: 2, name: "Bob".to_string() });
+/// ```
+///
+/// # Panics
+///
+/// Panics if the root key references an invalid index or the encoded value
+/// is malformed.
+pub fn decompress_rows<T: DeserializeOwned>(c: Compressed) -> Result<Vec<T>, serde_json::Error> {
+    let (values, root) = root_store(&c.values, c.root.as_str());
+    let entry = (!is_null_key(root)).then(|| values[decode_key(root)].as_str());
+    if let Some(entry) = entry
+        && entry.starts_with("d|")
+    {
+        return packed_elements(entry)
+            .into_iter()
+            .map(serde_json::from_value)
+            .collect();
+    }
+    let refs = entry
+        .and_then(|entry| entry.strip_prefix("a|"))
+        .ok_or_else(|| de::Error::custom("root is not an array"))?;
+    if refs.is_empty() {
+        return Ok(Vec::new());
+    }
+    refs.split('|')
+        .map(|r| serde_json::from_value(decode_at(&values, r)))
+        .collect()
+}
+
+/// Iterate over the elements of a compressed array, decoding them lazily.
+///
+/// Each element is decoded only when the iterator reaches it, so a large
+/// array can be processed row by row without holding every element as a
+/// `Value` at once.
+///
+/// # Arguments
+///
+/// * `c` - The compressed representation
+///
+/// # Returns
+///
+/// An iterator over the decoded elements, or `None` if the root is not an
+/// array
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{array_iter, compress};
+/// use serde_json::json;
+///
+/// let compressed = compress(&json!([{"id": 1}, {"id": 2}, null]));
+/// let ids: Vec<_> = array_iter(&compressed).unwrap().map(|row| row["id"].clone()).collect();
+/// assert_eq!(ids, [json!(1), json!(2), json!(null)]);
+///
+/// assert!(array_iter(&compress(&json!({"id": 1}))).is_none());
+/// ```
+///
+/// # Panics
+///
+/// Panics if the root key references an invalid index, or (while iterating)
+/// if an element is malformed.
+pub fn array_iter<'a>(c: &'a Compressed) -> Option<impl Iterator<Item = Value> + 'a> {
+    // An inline root is a scalar, never an array
+    let (Cow::Borrowed(values), root) = root_store(&c.values, c.root.as_str()) else {
+        return None;
+    };
+    if is_null_key(root) {
+        return None;
+    }
+    let entry = values[decode_key(root)].as_str();
+    let (refs, packed) = match entry.strip_prefix("a|") {
+        Some(refs) => (refs, Vec::new()),
+        // Packed integers reference nothing, so they are decoded at once
+        None if entry.starts_with("d|") => ("", packed_elements(entry)),
+        None => return None,
+    };
+    // An empty array has no refs, not one empty ref
+    let refs = (!refs.is_empty()).then(|| refs.split('|'));
+    Some(
+        refs.into_iter()
+            .flatten()
+            .map(|r| decode_at(values, r))
+            .chain(packed),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_verify_accepts_configured_conversions() {
+        let config = Config {
+            numeric_strings: true,
+            ..CONFIG
+        };
+        let data = json!({"id": "123", "big": u64::MAX, "tags": ["a"]});
+        assert_eq!(
+            verify_compressed(&data, &compress_with_config(&data, &config), &config),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_verify_rejects_broken_store() {
+        let data = json!({"name": "Alice", "age": 30});
+        let mut c = compress(&data);
+        // Simulate an encoder bug that stored the wrong number
+        let age = c.values.iter().position(|v| v == "n|30").unwrap();
+        c.values[age] = "n|31".to_string();
+        let err = verify_compressed(&data, &c, &CONFIG).unwrap_err();
+        assert_eq!(
+            err,
+            CompressError::VerifyFailed {
+                path: "/age".to_string()
+            }
+        );
+        assert!(err.to_string().contains("does not decompress to its input"));
+    }
+}
@@ -11,13 +11,13 @@
 
 /// Panic indicating an unsupported data type encountered.
 ///
-/// This function is called when an unknown or unsupported JSON value
-/// type is encountered during compression.
+/// This function is called when an unknown or unsupported value type is
+/// encountered during compression, e.g. a custom [`ExtValue`](crate::ExtValue)
+/// that no registered encoder handles.
 ///
 /// # Panics
 ///
 /// Always panics with message "unsupported data type"
-#[allow(dead_code)]
 pub fn throw_unknown_data_type() -> ! {
     panic!("unsupported data type");
 }
@@ -0,0 +1,254 @@
+//! Reusable decoder with a memo cache.
+//!
+//! This module provides the [`Decoder`] struct, which decodes keys from a
+//! borrowed values array and can remember every entry it has decoded.
+//!
+//! # When to Use
+//!
+//! A single store may hold many documents (for example when several values
+//! were added to one [`Memory`](crate::Memory)). Decoding each root with
+//! [`decode`](crate::decode) re-decodes shared subtrees every time. A
+//! [`Decoder`] keeps its cache between
+//! [`decode_cached`](Decoder::decode_cached) calls, so a subtree shared by
+//! different roots is only decoded once.
+//!
+//! # Example
+//!
+//! ```rust
+//! use compress_json_rs::{Decoder, add_value, make_memory, mem_to_values};
+//! use serde_json::json;
+//!
+//! let shared = json!({"tags": ["a", "b", "c"]});
+//!
+//! let mut mem = make_memory();
+//! let first = add_value(&mut mem, &json!({"id": 1, "meta": shared}));
+//! let second = add_value(&mut mem, &json!({"id": 2, "meta": shared}));
+//! let values = mem_to_values(&mem);
+//!
+//! let mut decoder = Decoder::new(&values);
+//! assert_eq!(decoder.decode_cached(&first)["meta"], shared);
+//! assert_eq!(decoder.decode_cached(&second)["meta"], shared);
+//! ```
+//!
+//! # Reusing Scratch Space
+//!
+//! When decompressing many small, unrelated payloads in a tight loop, the
+//! temporary buffers used to split `a|`/`o|` reference lists dominate the
+//! allocation profile. A [`DecodeScratch`] keeps that buffer between calls:
+//!
+//! ```rust
+//! use compress_json_rs::{DecodeScratch, compress};
+//! use serde_json::json;
+//!
+//! let mut scratch = DecodeScratch::new();
+//! for i in 0..3 {
+//!     let data = json!({"id": i, "tags": ["a", "b"]});
+//!     let c = compress(&data);
+//!     assert_eq!(scratch.decode(&c.values, &c.root), data);
+//! }
+//! ```
+
+use crate::core::{decode, decode_entry, is_inline_root, is_null_key, root_store, schema_keys};
+use crate::encode::decode_key;
+use crate::key::Key;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Decoder over a borrowed values array, with an optional memo cache.
+///
+/// [`decode`](Decoder::decode) decodes a key from scratch and only needs a
+/// shared reference. [`decode_cached`](Decoder::decode_cached) caches every
+/// store entry it decodes by index, so later calls (including ones for
+/// different root keys) reuse the result instead of decoding the entry again.
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{Compressed, Decoder, compress};
+/// use serde_json::json;
+///
+/// let Compressed { values, root } = compress(&json!([1, 2, 3]));
+///
+/// let mut decoder = Decoder::new(&values);
+/// assert_eq!(decoder.decode(&root), json!([1, 2, 3]));
+/// assert_eq!(decoder.decode_cached(&root), json!([1, 2, 3]));
+///
+/// // The second cached decode is served from the cache
+/// let decoded = decoder.decode_count();
+/// decoder.decode_cached(&root);
+/// assert_eq!(decoder.decode_count(), decoded);
+/// ```
+pub struct Decoder<'a> {
+    /// The values array being decoded
+    values: &'a [String],
+    /// Cache mapping store indices to their decoded values
+    memo: HashMap<usize, Value>,
+    /// Number of store entries decoded (cache misses)
+    decode_count: usize,
+}
+
+impl<'a> Decoder<'a> {
+    /// Create a decoder over a values array with an empty cache.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The values array from a compressed representation
+    pub fn new(values: &'a [String]) -> Self {
+        Decoder {
+            values,
+            memo: HashMap::new(),
+            decode_count: 0,
+        }
+    }
+
+    /// Decode a key without using or filling the cache.
+    ///
+    /// Same as [`decode`](crate::decode) on the borrowed values array.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Key of the value to decode
+    ///
+    /// # Returns
+    ///
+    /// The decoded `serde_json::Value`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key references an invalid index or the encoded value is malformed.
+    pub fn decode(&self, key: &Key) -> Value {
+        decode(self.values, key)
+    }
+
+    /// Decode a key, reusing any entries decoded by earlier cached calls.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Key of the value to decode
+    ///
+    /// # Returns
+    ///
+    /// The decoded `serde_json::Value`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key references an invalid index or the encoded value is malformed.
+    pub fn decode_cached(&mut self, key: &Key) -> Value {
+        // An inline root has no store entry to cache
+        if is_inline_root(self.values, key.as_str()) {
+            return self.decode(key);
+        }
+        self.decode_cached_at(key.as_str())
+    }
+
+    /// Decode the reference `key`, memoizing the result.
+    pub(crate) fn decode_cached_at(&mut self, key: &str) -> Value {
+        if is_null_key(key) {
+            return Value::Null;
+        }
+        let id = decode_key(key);
+        if let Some(v) = self.memo.get(&id) {
+            return v.clone();
+        }
+        let values = self.values;
+        let v = decode_entry(&values[id], &mut |k| self.decode_cached_at(k));
+        self.decode_count += 1;
+        self.memo.insert(id, v.clone());
+        v
+    }
+
+    /// Number of store entries decoded so far by cached calls.
+    ///
+    /// Cache hits and uncached [`decode`](Decoder::decode) calls do not
+    /// increase this count, which makes it useful to measure how much work
+    /// the cache saves.
+    pub fn decode_count(&self) -> usize {
+        self.decode_count
+    }
+}
+
+/// Reusable scratch space for decoding many payloads without reallocating.
+///
+/// Unlike [`Decoder`], nothing is cached between calls and the store is not
+/// borrowed, so one scratch can decode payloads from any number of
+/// unrelated values arrays. Only the segment buffer is retained; it grows to
+/// the largest nesting seen and is then reused.
+#[derive(Debug, Default)]
+pub struct DecodeScratch {
+    /// Byte ranges of reference segments, used as a stack across nesting levels
+    segments: Vec<Range<usize>>,
+}
+
+impl DecodeScratch {
+    /// Create an empty scratch buffer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decode a key from `values`, reusing this scratch's buffer.
+    ///
+    /// Produces the same result as [`decode`](crate::decode).
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The values array from a compressed representation
+    /// * `key` - Key of the value to decode
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key references an invalid index or the encoded value is malformed.
+    pub fn decode(&mut self, values: &[String], key: &Key) -> Value {
+        let (values, root) = root_store(values, key.as_str());
+        self.decode_at(&values, root)
+    }
+
+    /// Decode the reference `key` from `values`.
+    fn decode_at(&mut self, values: &[String], key: &str) -> Value {
+        if is_null_key(key) {
+            return Value::Null;
+        }
+        let v_str = &values[decode_key(key)];
+        if v_str.starts_with("a|") {
+            let base = self.push_segments(v_str);
+            let mut arr = Vec::with_capacity(self.segments.len() - base);
+            for i in base..self.segments.len() {
+                let part = &v_str[self.segments[i].clone()];
+                arr.push(self.decode_at(values, part));
+            }
+            self.segments.truncate(base);
+            Value::Array(arr)
+        } else if v_str.starts_with("o|") {
+            let base = self.push_segments(v_str);
+            if base == self.segments.len() {
+                return Value::Object(Map::new());
+            }
+            let schema = &v_str[self.segments[base].clone()];
+            let keys = schema_keys(self.decode_at(values, schema));
+            let mut map = Map::with_capacity(keys.len());
+            for (key, i) in keys.into_iter().zip(base + 1..self.segments.len()) {
+                let part = &v_str[self.segments[i].clone()];
+                map.insert(key, self.decode_at(values, part));
+            }
+            self.segments.truncate(base);
+            Value::Object(map)
+        } else {
+            decode_entry(v_str, &mut |k| self.decode_at(values, k))
+        }
+    }
+
+    /// Push the ranges of the `|`-separated references after an `a|`/`o|`
+    /// prefix, returning the stack height before the push.
+    fn push_segments(&mut self, entry: &str) -> usize {
+        let base = self.segments.len();
+        if entry.len() > 2 {
+            let mut start = 2;
+            for (i, _) in entry[2..].match_indices('|') {
+                self.segments.push(start..i + 2);
+                start = i + 3;
+            }
+            self.segments.push(start..entry.len());
+        }
+        base
+    }
+}
@@ -0,0 +1,253 @@
+//! Diagnostic views over a compression memory.
+//!
+//! This module provides read-only helpers for inspecting what a [`Memory`]
+//! holds, e.g. when debugging why a payload compresses poorly,
+//! [`diff_against`] for pinpointing where decompressed output departs from
+//! a known-good value, and [`semantically_eq`] for comparing payloads by the
+//! document they hold.
+//!
+//! # Deterministic Output
+//!
+//! The caches inside [`Memory`] are `HashMap`s, whose iteration order varies
+//! between runs. All functions here order their output by store index (the
+//! order in which values were first added), so the same input always
+//! produces the same output and can be used in golden tests.
+//!
+//! # Example
+//!
+//! ```rust
+//! use compress_json_rs::{add_value, debug_dump, make_memory};
+//! use serde_json::json;
+//!
+//! let mut mem = make_memory();
+//! add_value(&mut mem, &json!({"id": 1}));
+//!
+//! println!("{}", debug_dump(&mem));
+//! ```
+
+use crate::core::{Compressed, decode, entry_refs};
+use crate::encode::decode_key;
+use crate::key::Key;
+use crate::memory::{Memory, SchemaKey};
+use crate::number::int_to_s;
+use crate::path::escape_token;
+use serde_json::Value;
+use std::fmt::Write;
+
+/// Render the store and schema cache of a memory as text.
+///
+/// Each entry of the store is printed as `<key> = <encoded value>`, followed
+/// by each cached schema as `schema <key> = <comma-joined keys>`. Both
+/// sections are ordered by store index. Entries are listed whether or not
+/// they are in the value cache, so values evicted under
+/// [`Config::max_dictionary_len`](crate::Config::max_dictionary_len) and
+/// stores built without [`Config::dedup`](crate::Config::dedup) are shown in
+/// full.
+///
+/// # Arguments
+///
+/// * `mem` - Reference to the Memory instance
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{add_value, debug_dump, make_memory};
+/// use serde_json::json;
+///
+/// let mut mem = make_memory();
+/// add_value(&mut mem, &json!(["x", true]));
+///
+/// assert_eq!(debug_dump(&mem), "0 = x\n1 = b|T\n2 = a|0|1\n");
+/// ```
+pub fn debug_dump(mem: &Memory) -> String {
+    let mut out = String::new();
+    // Entries before `base` have been drained from the store
+    let base = mem.key_count - mem.store.len();
+    for (i, value) in mem.store.iter().enumerate() {
+        let _ = writeln!(out, "{} = {value}", int_to_s(base + i));
+    }
+    let mut schemas: Vec<(&SchemaKey, &Key)> = mem.schema_cache.iter().collect();
+    schemas.sort_by_key(|(_, key)| decode_key(key.as_str()));
+    for (schema, key) in schemas {
+        let _ = writeln!(out, "schema {key} = {}", schema.0.join(","));
+    }
+    out
+}
+
+/// Count how many times each stored value is referenced by other entries.
+///
+/// References come from array elements, object values and object schemas.
+/// A value that is only used as a root has a count of zero.
+///
+/// # Arguments
+///
+/// * `mem` - Reference to the Memory instance
+///
+/// # Returns
+///
+/// One `(key, count)` pair per stored value, ordered by store index
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{add_value, make_memory, value_histogram};
+/// use serde_json::json;
+///
+/// let mut mem = make_memory();
+/// add_value(&mut mem, &json!(["x", "x", "y"]));
+///
+/// let histogram = value_histogram(&mem);
+/// assert_eq!(histogram[0], ("0".into(), 2)); // "x"
+/// assert_eq!(histogram[1], ("1".into(), 1)); // "y"
+/// ```
+pub fn value_histogram(mem: &Memory) -> Vec<(Key, usize)> {
+    let mut counts = vec![0; mem.store.len()];
+    for entry in &mem.store {
+        for key in entry_refs(entry) {
+            if let Some(count) = counts.get_mut(decode_key(key)) {
+                *count += 1;
+            }
+        }
+    }
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(i, count)| (Key::from(int_to_s(i)), count))
+        .collect()
+}
+
+/// Describe where decompressed data differs from an expected value.
+///
+/// Decompresses `c` and compares it with `expected`, listing one line per
+/// difference with its JSON Pointer, instead of the two whole documents an
+/// `assert_eq!` prints. Useful when checking output against a golden value
+/// from another implementation.
+///
+/// # Arguments
+///
+/// * `c` - The compressed representation
+/// * `expected` - The value `c` should decompress to
+///
+/// # Returns
+///
+/// `None` if the decompressed value equals `expected`, otherwise the
+/// differences, one per line, in document order of `expected`:
+///
+/// | Line | Meaning |
+/// |------|---------|
+/// | `<path>: expected <a>, got <b>` | Different values (or types) |
+/// | `<path>: missing, expected <a>` | Key or element absent from the output |
+/// | `<path>: unexpected <b>` | Key or element absent from `expected` |
+///
+/// The root's path is shown as `(root)`.
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{compress, diff_against};
+/// use serde_json::json;
+///
+/// let c = compress(&json!({"users": [{"id": 1}, {"id": 2}]}));
+/// assert_eq!(diff_against(&c, &json!({"users": [{"id": 1}, {"id": 2}]})), None);
+/// assert_eq!(
+///     diff_against(&c, &json!({"users": [{"id": 1}, {"id": 3}]})).unwrap(),
+///     "/users/1/id: expected 3, got 2"
+/// );
+/// ```
+///
+/// # Panics
+///
+/// Panics if the compressed data is malformed, as
+/// [`decompress`](crate::decompress) does.
+pub fn diff_against(c: &Compressed, expected: &Value) -> Option<String> {
+    let actual = decode(&c.values, &c.root);
+    let mut lines = Vec::new();
+    diff_values(expected, &actual, &mut String::new(), &mut lines);
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// Whether two compressed payloads hold the same document.
+///
+/// `==` on [`Compressed`] compares the stores entry by entry, so the same
+/// document compressed with a different [`Config`](crate::Config) (e.g.
+/// `frequency_keys`, `sort_values` or `sort_key`), or by another
+/// implementation, compares unequal. This decompresses both and compares
+/// the values instead, so tests and caches can treat such payloads as
+/// equal. Identical payloads are equal without decoding.
+///
+/// # Arguments
+///
+/// * `a` - The first compressed representation
+/// * `b` - The second compressed representation
+///
+/// # Returns
+///
+/// `true` if both decompress to equal values
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{Config, compress, compress_with_config, semantically_eq};
+/// use serde_json::json;
+///
+/// let data = json!({"tags": ["a", "b", "a"], "count": 3});
+/// let plain = compress(&data);
+/// let reordered = compress_with_config(&data, &Config { frequency_keys: true, ..Config::default() });
+///
+/// assert_ne!(plain, reordered);
+/// assert!(semantically_eq(&plain, &reordered));
+/// ```
+///
+/// # Panics
+///
+/// Panics if either payload is malformed, as
+/// [`decompress`](crate::decompress) does.
+pub fn semantically_eq(a: &Compressed, b: &Compressed) -> bool {
+    a == b || decode(&a.values, &a.root) == decode(&b.values, &b.root)
+}
+
+/// Collect one line per difference between `expected` and `actual`.
+fn diff_values(expected: &Value, actual: &Value, path: &mut String, lines: &mut Vec<String>) {
+    match (expected, actual) {
+        (Value::Array(want), Value::Array(got)) => {
+            for i in 0..want.len().max(got.len()) {
+                let len = path.len();
+                let _ = write!(path, "/{i}");
+                diff_entry(want.get(i), got.get(i), path, lines);
+                path.truncate(len);
+            }
+        }
+        (Value::Object(want), Value::Object(got)) => {
+            let extra = got.keys().filter(|k| !want.contains_key(*k));
+            for k in want.keys().chain(extra) {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&escape_token(k));
+                diff_entry(want.get(k), got.get(k), path, lines);
+                path.truncate(len);
+            }
+        }
+        _ if expected == actual => {}
+        _ => lines.push(format!("{}: expected {expected}, got {actual}", show(path))),
+    }
+}
+
+/// Compare a member that may be missing on either side.
+fn diff_entry(
+    want: Option<&Value>,
+    got: Option<&Value>,
+    path: &mut String,
+    lines: &mut Vec<String>,
+) {
+    match (want, got) {
+        (Some(want), Some(got)) => diff_values(want, got, path, lines),
+        (Some(want), None) => lines.push(format!("{}: missing, expected {want}", show(path))),
+        (None, Some(got)) => lines.push(format!("{}: unexpected {got}", show(path))),
+        (None, None) => {}
+    }
+}
+
+/// A JSON Pointer for display, naming the root explicitly.
+fn show(path: &str) -> &str {
+    if path.is_empty() { "(root)" } else { path }
+}
@@ -0,0 +1,90 @@
+//! Diff compression between two versions of a document.
+//!
+//! This module provides [`compress_diff`] and [`apply_diff`] for sending only
+//! what changed between a `base` document both sides already have and a
+//! `next` version of it.
+//!
+//! # How It Works
+//!
+//! Compression is deterministic, so compressing `base` yields the same store
+//! on both ends. [`compress_diff`] compresses `next` into a memory that has
+//! been pre-seeded with `base`: unchanged subtrees are found in the value
+//! cache and referenced by their existing keys, and only new entries are
+//! appended. The diff carries just those appended entries plus the new root.
+//!
+//! [`apply_diff`] rebuilds the seeded store from `base`, appends the diff
+//! entries and decodes the new root.
+//!
+//! # Example
+//!
+//! ```rust
+//! use compress_json_rs::{apply_diff, compress_diff};
+//! use serde_json::json;
+//!
+//! let base = json!({"name": "Alice", "tags": ["a", "b", "c"], "age": 30});
+//! let next = json!({"name": "Alice", "tags": ["a", "b", "c"], "age": 31});
+//!
+//! let diff = compress_diff(&base, &next);
+//! assert_eq!(apply_diff(&base, diff), next);
+//! ```
+
+use crate::core::{Compressed, compress, decode};
+use crate::memory::{add_value, make_memory};
+use serde_json::Value;
+
+/// Compress `next` relative to `base`, keeping only new store entries.
+///
+/// Both documents must be compressed with the default configuration on
+/// both ends, since the receiver rebuilds the base store with [`compress`].
+///
+/// # Arguments
+///
+/// * `base` - The document the receiver already has
+/// * `next` - The new version of the document
+///
+/// # Returns
+///
+/// A [`Compressed`] whose values are only the entries `next` adds on top of
+/// `base`, and whose root points into the combined store
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::compress_diff;
+/// use serde_json::json;
+///
+/// let base = json!({"a": "unchanged", "b": 1});
+/// let diff = compress_diff(&base, &base);
+///
+/// // Nothing changed, nothing to send
+/// assert!(diff.values.is_empty());
+/// ```
+pub fn compress_diff(base: &Value, next: &Value) -> Compressed {
+    let mut mem = make_memory();
+    add_value(&mut mem, base);
+    let base_len = mem.store.len();
+    let root = add_value(&mut mem, next);
+    let values = mem.store.split_off(base_len);
+    Compressed { values, root }
+}
+
+/// Reconstruct the new document from `base` and a diff.
+///
+/// # Arguments
+///
+/// * `base` - The same base document passed to [`compress_diff`]
+/// * `diff` - The output of [`compress_diff`]
+///
+/// # Returns
+///
+/// The `next` document the diff was produced from
+///
+/// # Panics
+///
+/// Panics if the diff was produced against a different base, since its
+/// references would then point at missing or unrelated entries.
+pub fn apply_diff(base: &Value, diff: Compressed) -> Value {
+    let mut values = compress(base).values;
+    values.extend(diff.values);
+    decode(&values, &diff.root)
+}
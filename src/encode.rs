@@ -1,7 +1,8 @@
 //! Encoding and decoding functions for compressed values.
 //!
-//! This module provides internal functions for encoding JSON values into
-//! their compressed string representations and decoding them back.
+//! This module provides the functions for encoding JSON values into their
+//! compressed string representations and decoding them back. The public
+//! ones are re-exported from [`codec`](crate::codec).
 //!
 //! # Encoding Format
 //!
@@ -13,13 +14,17 @@
 //! | Infinity | `N\|+` | `"N\|+"` (when preserved) |
 //! | -Infinity | `N\|-` | `"N\|-"` (when preserved) |
 //! | NaN | `N\|0` | `"N\|0"` (when preserved) |
+//! | Chunked string | `c\|` | `"c\|0\|1"` (with `chunk_strings`) |
+//! | Packed integers | `d\|` | `"d\|G8\|1\|4"` (with `pack_int_arrays`) |
+//! | Binary blob | `x\|` | `"x\|AAEC"` (via [`ExtValue::Bytes`](crate::ExtValue::Bytes)) |
 //! | Escaped string | `s\|` | `"s\|n\|foo"` |
 //! | Plain string | _(none)_ | `"hello"` |
 //!
 //! # String Escaping
 //!
-//! Strings that start with reserved prefixes (`b|`, `n|`, `N|`, `o|`, `a|`, `s|`)
-//! are escaped with `s|` to prevent ambiguity during decoding.
+//! Strings that start with a reserved prefix (listed once in
+//! [`RESERVED_PREFIXES`]) are escaped with `s|` to prevent ambiguity during
+//! decoding.
 //!
 //! # Special Values (v3.4.0+)
 //!
@@ -32,6 +37,29 @@
 //! This ensures compatibility with JavaScript and Python implementations v3.4.0+.
 
 use crate::number::s_to_int;
+use std::borrow::Cow;
+
+/// Prefixes that mark an entry as something other than a plain string.
+///
+/// This is the single list consulted when escaping strings: any string that
+/// starts with one of these is stored with the `s|` escape prefix. A new
+/// entry type must add its prefix here, whatever its length.
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::codec::{RESERVED_PREFIXES, decode_str, encode_str};
+///
+/// for prefix in RESERVED_PREFIXES {
+///     let s = format!("{prefix}data");
+///     assert!(encode_str(&s).starts_with("s|"));
+///     assert_eq!(decode_str(&encode_str(&s)), s);
+/// }
+/// ```
+pub const RESERVED_PREFIXES: &[&str] = &["b|", "n|", "N|", "o|", "a|", "s|", "x|", "c|", "d|"];
+
+/// Prefix prepended to strings that start with a reserved prefix.
+const ESCAPE_PREFIX: &str = "s|";
 
 /// Encode a regular number to compressed string with 'n|' prefix.
 ///
@@ -45,11 +73,19 @@ use crate::number::s_to_int;
 ///
 /// # Returns
 ///
-/// String in format `"n|<number>"`
+/// String in format `"n|<number>"`. The number is written as plain decimal
+/// digits, never in exponent form (`1e21` becomes `n|1000000000000000000000`),
+/// and always parses back to the same `f64`.
+///
+/// The digits are the shortest that round-trip, as computed by `ryu`, so the
+/// output does not depend on how the toolchain's `Display` for `f64` happens
+/// to format; `ryu`'s exponent is expanded and a `.0` fraction dropped.
 ///
 /// # Example
 ///
-/// ```ignore
+/// ```rust
+/// use compress_json_rs::codec::encode_num;
+///
 /// assert_eq!(encode_num(42.5), "n|42.5");
 /// assert_eq!(encode_num(-3.14), "n|-3.14");
 /// assert_eq!(encode_num(0.0), "n|0");
@@ -61,7 +97,45 @@ use crate::number::s_to_int;
 /// - `preserve_nan`/`preserve_infinite`: encoded as `N|0`, `N|+`, `N|-`
 /// - Otherwise: converted to null (empty string)
 pub fn encode_num(num: f64) -> String {
-    format!("n|{num}")
+    if !num.is_finite() {
+        // Not a valid input; keep the `Display` rendering rather than garbage
+        return format!("n|{num}");
+    }
+    let mut buffer = ryu::Buffer::new();
+    let mut out = String::from("n|");
+    push_plain_decimal(&mut out, buffer.format_finite(num));
+    out
+}
+
+/// Append a number printed by `ryu` (`42.0`, `-1.5e-7`, `1e21`) as plain
+/// decimal digits (`42`, `-0.00000015`, `1000000000000000000000`).
+fn push_plain_decimal(out: &mut String, text: &str) {
+    let (mantissa, exp) = match text.split_once('e') {
+        Some((m, e)) => (m, e.parse::<i32>().expect("ryu exponent")),
+        None => (text, 0),
+    };
+    if let Some(rest) = mantissa.strip_prefix('-') {
+        out.push('-');
+        return push_plain_decimal(out, &format!("{rest}e{exp}"));
+    }
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let frac = if frac == "0" { "" } else { frac };
+    let digits = format!("{int}{frac}");
+    // Number of digits before the decimal point
+    let point = int.len() as i32 + exp;
+    if point <= 0 {
+        out.push_str("0.");
+        out.extend(std::iter::repeat_n('0', (-point) as usize));
+        out.push_str(&digits);
+    } else if point as usize >= digits.len() {
+        out.push_str(&digits);
+        out.extend(std::iter::repeat_n('0', point as usize - digits.len()));
+    } else {
+        let (whole, fraction) = digits.split_at(point as usize);
+        out.push_str(whole);
+        out.push('.');
+        out.push_str(fraction);
+    }
 }
 
 /// Check if an encoded string represents a special value (Infinity/NaN).
@@ -111,6 +185,19 @@ pub fn decode_special(s: &str) -> f64 {
 ///
 /// The decoded f64 value
 ///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::codec::decode_num;
+///
+/// assert_eq!(decode_num("n|42.5"), 42.5);
+/// assert_eq!(decode_num("n|-1e3"), -1000.0);
+/// ```
+///
+/// A leading `+` is accepted here. Checked decoding is stricter: it rejects
+/// `+42` and digit separators such as `1_000` unless
+/// [`Config::lenient`](crate::Config::lenient) is set.
+///
 /// # Panics
 ///
 /// Panics if the string after the prefix is not a valid number.
@@ -119,6 +206,58 @@ pub fn decode_num(s: &str) -> f64 {
     s2.parse::<f64>().expect("invalid number")
 }
 
+/// Normalize the payload of an `n|` entry written by a less strict encoder.
+///
+/// Strips a leading `+` and `_` digit separators between digits, so `+42`
+/// and `1_000` read as `42` and `1000`. Other text is returned unchanged;
+/// whether the result is a valid number is left to the caller.
+///
+/// # Example
+///
+/// ```ignore
+/// assert_eq!(lenient_num("+1_000.5"), "1000.5");
+/// assert_eq!(lenient_num("1e+5"), "1e+5");
+/// ```
+pub(crate) fn lenient_num(s: &str) -> Cow<'_, str> {
+    let unsigned = s.strip_prefix('+').unwrap_or(s);
+    if !unsigned.contains('_') {
+        return Cow::Borrowed(unsigned);
+    }
+    let bytes = unsigned.as_bytes();
+    let kept = unsigned.char_indices().filter(|&(i, c)| {
+        let separator = c == '_'
+            && i > 0
+            && bytes[i - 1].is_ascii_digit()
+            && bytes.get(i + 1).is_some_and(u8::is_ascii_digit);
+        !separator
+    });
+    Cow::Owned(kept.map(|(_, c)| c).collect())
+}
+
+/// Check whether converting a number's text through `f64` would change it.
+///
+/// True when the text overflows an `f64` or has significant digits that the
+/// nearest `f64` does not reproduce. Used with `arbitrary_precision` to keep
+/// such numbers as their original text.
+#[cfg(feature = "arbitrary_precision")]
+pub(crate) fn f64_loses_precision(text: &str) -> bool {
+    /// Significant digits of a decimal, without leading/trailing zeros
+    fn digits(s: &str) -> String {
+        let mantissa = s.split(['e', 'E']).next().unwrap_or(s);
+        let digits: String = mantissa
+            .trim_start_matches(['-', '0', '.'])
+            .chars()
+            .filter(char::is_ascii_digit)
+            .collect();
+        digits.trim_end_matches('0').to_string()
+    }
+
+    match text.parse::<f64>() {
+        Ok(f) if f.is_finite() => digits(text) != digits(&format!("{f:e}")),
+        _ => true,
+    }
+}
+
 /// Decode a key string (base-62) to an index.
 ///
 /// Converts a base-62 encoded key back to its numeric index
@@ -131,6 +270,15 @@ pub fn decode_num(s: &str) -> f64 {
 /// # Returns
 ///
 /// The numeric index as usize
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::codec::decode_key;
+///
+/// assert_eq!(decode_key("A"), 10);
+/// assert_eq!(decode_key("10"), 62);
+/// ```
 pub fn decode_key(key: &str) -> usize {
     s_to_int(key)
 }
@@ -161,6 +309,15 @@ pub fn encode_bool(b: bool) -> String {
 /// # Returns
 ///
 /// `true` for "b|T", `false` for "b|F" or empty string
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::codec::{decode_bool, encode_bool};
+///
+/// assert!(decode_bool(&encode_bool(true)));
+/// assert!(!decode_bool("b|F"));
+/// ```
 pub fn decode_bool(s: &str) -> bool {
     match s {
         "b|T" => true,
@@ -171,8 +328,8 @@ pub fn decode_bool(s: &str) -> bool {
 
 /// Encode a string, escaping reserved prefixes with 's|' if needed.
 ///
-/// If the string starts with a reserved prefix (`b|`, `o|`, `n|`, `N|`, `a|`, `s|`),
-/// it's escaped by prepending `s|` to prevent decoding ambiguity.
+/// If the string starts with one of the [`RESERVED_PREFIXES`], it's escaped
+/// by prepending `s|` to prevent decoding ambiguity.
 ///
 /// # Arguments
 ///
@@ -180,28 +337,38 @@ pub fn decode_bool(s: &str) -> bool {
 ///
 /// # Returns
 ///
-/// The original string, or escaped with `s|` prefix if needed
+/// The original string (borrowed, no allocation), or an owned copy escaped
+/// with the `s|` prefix if needed
 ///
 /// # Example
 ///
-/// ```ignore
+/// ```rust
+/// use compress_json_rs::codec::encode_str;
+///
 /// assert_eq!(encode_str("hello"), "hello");
 /// assert_eq!(encode_str("n|123"), "s|n|123"); // Escaped
 /// assert_eq!(encode_str("N|+"), "s|N|+");     // Escaped (v3.2.0)
 /// ```
-pub fn encode_str(s: &str) -> String {
-    // Check for reserved prefixes using starts_with (UTF-8 safe)
-    // Note: N| added in v3.2.0 for special values
-    if s.starts_with("b|")
-        || s.starts_with("o|")
-        || s.starts_with("n|")
-        || s.starts_with("N|")
-        || s.starts_with("a|")
-        || s.starts_with("s|")
-    {
-        return format!("s|{s}");
-    }
-    s.to_string()
+pub fn encode_str(s: &str) -> Cow<'_, str> {
+    if needs_escape(s) {
+        return Cow::Owned(format!("{ESCAPE_PREFIX}{s}"));
+    }
+    Cow::Borrowed(s)
+}
+
+/// Encode an owned string, reusing its allocation when no escaping is needed.
+///
+/// Produces the same output as [`encode_str`].
+pub fn encode_string(s: String) -> String {
+    if needs_escape(&s) {
+        return format!("{ESCAPE_PREFIX}{s}");
+    }
+    s
+}
+
+/// Check whether a string starts with a reserved prefix and must be escaped.
+pub(crate) fn needs_escape(s: &str) -> bool {
+    RESERVED_PREFIXES.iter().any(|prefix| s.starts_with(prefix))
 }
 
 /// Decode a compressed string, unescaping 's|' prefix if present.
@@ -213,9 +380,18 @@ pub fn encode_str(s: &str) -> String {
 /// # Returns
 ///
 /// The original string with `s|` prefix removed if present
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::codec::decode_str;
+///
+/// assert_eq!(decode_str("hello"), "hello");
+/// assert_eq!(decode_str("s|n|123"), "n|123");
+/// ```
 pub fn decode_str(s: &str) -> String {
     // Use strip_prefix for safe UTF-8 handling
-    if let Some(stripped) = s.strip_prefix("s|") {
+    if let Some(stripped) = s.strip_prefix(ESCAPE_PREFIX) {
         stripped.to_string()
     } else {
         s.to_string()
@@ -240,10 +416,54 @@ mod tests {
         assert_eq!(encode_num(0.0), "n|0");
     }
 
+    #[test]
+    fn test_encode_num_is_shortest_plain_decimal() {
+        // Shortest round-trip digits without exponent, like `Display`; the two
+        // may only pick different last digits when two candidates tie
+        let mut bits: u64 = 0x9E37_79B9_7F4A_7C15;
+        for _ in 0..100_000 {
+            bits ^= bits << 13;
+            bits ^= bits >> 7;
+            bits ^= bits << 17;
+            let x = f64::from_bits(bits);
+            if x.is_finite() {
+                let encoded = encode_num(x);
+                assert_eq!(encoded[2..].parse::<f64>(), Ok(x), "{bits:#x}");
+                assert_eq!(encoded.len(), format!("n|{x}").len(), "{bits:#x}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_lenient_num() {
+        assert_eq!(lenient_num("+42"), "42");
+        assert_eq!(lenient_num("1_000_000"), "1000000");
+        assert_eq!(lenient_num("-1_000.2_5e+1_0"), "-1000.25e+10");
+        // Only separators between digits are dropped
+        assert_eq!(lenient_num("_1"), "_1");
+        assert_eq!(lenient_num("1__0"), "1__0");
+        assert_eq!(lenient_num("1e+5"), "1e+5");
+    }
+
     #[test]
     fn test_escape_special_prefix() {
         assert_eq!(encode_str("N|+"), "s|N|+");
         assert_eq!(encode_str("N|-"), "s|N|-");
         assert_eq!(encode_str("N|0"), "s|N|0");
+        assert_eq!(encode_str("x|AA=="), "s|x|AA==");
+        assert_eq!(encode_str("c|0|1"), "s|c|0|1");
+        assert_eq!(encode_str("d|G8|1"), "s|d|G8|1");
+    }
+
+    #[test]
+    fn test_escape_prefix_is_reserved() {
+        // Otherwise an escaped string could not be told apart from a plain one
+        assert!(RESERVED_PREFIXES.contains(&ESCAPE_PREFIX));
+    }
+
+    #[test]
+    fn test_encode_str_borrows_unescaped() {
+        assert!(matches!(encode_str("hello"), Cow::Borrowed("hello")));
+        assert!(matches!(encode_str("n|1"), Cow::Owned(_)));
     }
 }
@@ -0,0 +1,240 @@
+//! Error types for fallible operations.
+//!
+//! The regular [`decompress`](crate::decompress) trusts its input and panics
+//! on malformed data. The `try_*` functions report problems as a
+//! [`DecompressError`] instead, so untrusted payloads cannot crash a service.
+//! Path updates with [`set_path`](crate::set_path) report a [`PathError`].
+//! [`try_compress_with_config`](crate::try_compress_with_config) (and, with
+//! the `simd-json` feature, `try_compress_simd`) reports a [`CompressError`]
+//! naming where a rejected value sits, and
+//! [`compress_with_report`](crate::compress_with_report) lists each lossy
+//! change it made as a [`Warning`].
+
+use std::fmt;
+
+/// Reason a compressed payload could not be decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecompressError {
+    /// A reference is not a valid base-62 key
+    InvalidKey {
+        /// The offending reference
+        key: String,
+    },
+    /// A reference points past the end of the values array
+    IndexOutOfBounds {
+        /// The referenced index
+        index: usize,
+        /// Length of the values array
+        len: usize,
+    },
+    /// An entry references itself, directly or through its descendants
+    Cycle {
+        /// Index of the entry that was reached again
+        index: usize,
+    },
+    /// An entry could not be decoded (e.g. an unparsable number)
+    InvalidEntry {
+        /// Index of the malformed entry
+        index: usize,
+    },
+    /// An object's schema is not a string or an array of strings
+    InvalidSchema {
+        /// Index of the schema entry
+        index: usize,
+    },
+    /// The output nests deeper than [`Config::max_depth`](crate::Config::max_depth)
+    MaxDepthExceeded {
+        /// The configured limit
+        limit: usize,
+    },
+    /// The output has more values than [`Config::max_nodes`](crate::Config::max_nodes)
+    MaxNodesExceeded {
+        /// The configured limit
+        limit: usize,
+    },
+    /// An array has more elements than
+    /// [`Config::max_array_len`](crate::Config::max_array_len)
+    MaxArrayLenExceeded {
+        /// Index of the array entry
+        index: usize,
+        /// The configured limit
+        limit: usize,
+    },
+    /// An object has more keys than
+    /// [`Config::max_object_keys`](crate::Config::max_object_keys)
+    MaxObjectKeysExceeded {
+        /// Index of the object entry
+        index: usize,
+        /// The configured limit
+        limit: usize,
+    },
+    /// The input is not a serialized `[values, root]` pair
+    Parse {
+        /// The parser's description of the problem
+        message: String,
+    },
+}
+
+impl fmt::Display for DecompressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecompressError::InvalidKey { key } => write!(f, "invalid reference key {key:?}"),
+            DecompressError::IndexOutOfBounds { index, len } => {
+                write!(f, "reference to index {index} is out of bounds (len {len})")
+            }
+            DecompressError::Cycle { index } => {
+                write!(f, "entry {index} references itself")
+            }
+            DecompressError::InvalidEntry { index } => write!(f, "entry {index} is malformed"),
+            DecompressError::InvalidSchema { index } => {
+                write!(f, "entry {index} is not a valid object schema")
+            }
+            DecompressError::MaxDepthExceeded { limit } => {
+                write!(f, "nesting depth exceeds the limit of {limit}")
+            }
+            DecompressError::MaxNodesExceeded { limit } => {
+                write!(f, "output exceeds the limit of {limit} values")
+            }
+            DecompressError::MaxArrayLenExceeded { index, limit } => {
+                write!(
+                    f,
+                    "array at entry {index} exceeds the limit of {limit} elements"
+                )
+            }
+            DecompressError::MaxObjectKeysExceeded { index, limit } => {
+                write!(
+                    f,
+                    "object at entry {index} exceeds the limit of {limit} keys"
+                )
+            }
+            DecompressError::Parse { message } => {
+                write!(f, "payload is not a compressed value: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecompressError {}
+
+/// Reason a JSON Pointer could not be applied to a compressed value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathError {
+    /// The pointer is neither empty nor starts with `/`
+    InvalidPointer {
+        /// The rejected pointer
+        pointer: String,
+    },
+    /// The pointer does not lead to an existing value (or, for the last
+    /// token, to an object that can take a new key)
+    NotFound {
+        /// The prefix of the pointer that could not be resolved
+        pointer: String,
+    },
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathError::InvalidPointer { pointer } => {
+                write!(f, "invalid JSON pointer {pointer:?}")
+            }
+            PathError::NotFound { pointer } => write!(f, "no value at {pointer:?}"),
+        }
+    }
+}
+
+impl std::error::Error for PathError {}
+
+/// Reason a value could not be compressed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompressError {
+    /// A number that the configuration rejects (NaN, infinite, or an
+    /// integer `f64` would round)
+    UnsupportedValue {
+        /// JSON Pointer to the number, e.g. `/metrics/3/value`
+        path: String,
+        /// The number as JavaScript prints it (`NaN`, `Infinity`,
+        /// `-Infinity`), or the integer's digits
+        value: String,
+    },
+    /// A string or object key longer than
+    /// [`Config::max_value_len`](crate::Config::max_value_len)
+    ValueTooLong {
+        /// JSON Pointer to the string, or to the member whose key it is
+        path: String,
+        /// Length of the string's store entry in bytes
+        len: usize,
+        /// The configured limit
+        limit: usize,
+    },
+    /// With [`Config::verify_on_compress`](crate::Config::verify_on_compress),
+    /// the output did not decompress to the input
+    VerifyFailed {
+        /// JSON Pointer to the first value that came back different
+        path: String,
+    },
+}
+
+impl fmt::Display for CompressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompressError::UnsupportedValue { path, value } => {
+                write!(f, "unsupported value {value} at {path:?}")
+            }
+            CompressError::ValueTooLong { path, len, limit } => {
+                write!(
+                    f,
+                    "{len}-byte value at {path:?} exceeds the limit of {limit}"
+                )
+            }
+            CompressError::VerifyFailed { path } => {
+                write!(
+                    f,
+                    "compressed data does not decompress to its input at {path:?}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for CompressError {}
+
+/// A lossy change made to a value during compression.
+///
+/// Collected by [`compress_with_report`](crate::compress_with_report) for
+/// values the configuration neither preserves nor rejects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    /// A NaN or infinite number stored as `null`
+    ReplacedWithNull {
+        /// JSON Pointer to the number, e.g. `/a/0`
+        path: String,
+        /// The number as JavaScript prints it (`NaN`, `Infinity`,
+        /// `-Infinity`)
+        value: String,
+    },
+    /// An integer beyond `f64` precision stored as the nearest `f64`
+    Rounded {
+        /// JSON Pointer to the integer
+        path: String,
+        /// The integer's digits
+        value: String,
+        /// The integer that was stored instead
+        stored: String,
+    },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::ReplacedWithNull { path, value } => {
+                write!(f, "{value} at {path} replaced with null")
+            }
+            Warning::Rounded {
+                path,
+                value,
+                stored,
+            } => write!(f, "{value} at {path} rounded to {stored}"),
+        }
+    }
+}
@@ -0,0 +1,293 @@
+//! Extended values with custom encode/decode hooks.
+//!
+//! JSON has no native representation for data such as dates. This module
+//! provides [`ExtValue`], a JSON-like tree that can also hold custom values
+//! of a user type `T`, together with the [`ExtEncoder`] / [`ExtDecoder`]
+//! trait pair for storing them as custom prefixed store entries.
+//!
+//! # Custom Entries
+//!
+//! An encoder declares a tag (e.g. `D`) and turns a custom value into a
+//! payload string; the value is stored as `<tag>|<payload>` (e.g.
+//! `D|2024-01-15T10:30:00Z`). When decoding, entries are dispatched on their
+//! tag to the decoder registered for it.
+//!
+//! # Binary Data
+//!
+//! [`ExtValue::Bytes`] is built in and needs no hooks: bytes are stored as
+//! `x|<base64>` entries (standard alphabet, padded) and restored as bytes by
+//! [`decompress_ext`]. Plain [`decompress`](crate::decompress) has no bytes
+//! type and yields the base64 text as a string instead.
+//!
+//! # Escaping
+//!
+//! Plain strings that happen to start with a registered `<tag>|` are escaped
+//! with `s|`, just like strings starting with the built-in prefixes, so they
+//! are never mistaken for custom values.
+//!
+//! # Example
+//!
+//! ```rust
+//! use compress_json_rs::{ExtDecoder, ExtEncoder, ExtValue, compress_ext, decompress_ext};
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct Date(String);
+//!
+//! struct DateCodec;
+//!
+//! impl ExtEncoder<Date> for DateCodec {
+//!     fn tag(&self) -> &str {
+//!         "D"
+//!     }
+//!     fn encode(&self, value: &Date) -> Option<String> {
+//!         Some(value.0.clone())
+//!     }
+//! }
+//!
+//! impl ExtDecoder<Date> for DateCodec {
+//!     fn tag(&self) -> &str {
+//!         "D"
+//!     }
+//!     fn decode(&self, payload: &str) -> Option<Date> {
+//!         Some(Date(payload.to_string()))
+//!     }
+//! }
+//!
+//! let value = ExtValue::Array(vec![
+//!     ExtValue::Custom(Date("2024-01-15".to_string())),
+//!     ExtValue::String("D|not a date".to_string()),
+//! ]);
+//!
+//! let compressed = compress_ext(&value, &[&DateCodec]);
+//! assert_eq!(decompress_ext(compressed, &[&DateCodec]), value);
+//! ```
+
+use crate::blob64;
+use crate::core::{Compressed, decode_at, decode_entry, is_null_key, root_store, schema_keys};
+use crate::debug::{throw_unknown_data_type, throw_unsupported_data};
+use crate::encode::{decode_key, encode_str, needs_escape};
+use crate::key::Key;
+use crate::memory::{
+    Memory, add_value, get_schema, get_value_key, get_value_key_owned, make_memory, mem_to_values,
+};
+use serde_json::{Number, Value};
+
+/// A JSON-like value tree that can also hold custom values.
+///
+/// Objects are stored as ordered `(key, value)` pairs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExtValue<T> {
+    /// JSON `null`
+    Null,
+    /// JSON boolean
+    Bool(bool),
+    /// JSON number
+    Number(Number),
+    /// JSON string
+    String(String),
+    /// JSON array
+    Array(Vec<ExtValue<T>>),
+    /// JSON object as ordered key/value pairs
+    Object(Vec<(String, ExtValue<T>)>),
+    /// Binary data, stored as an `x|<base64>` entry
+    Bytes(Vec<u8>),
+    /// A custom value, stored through an [`ExtEncoder`]
+    Custom(T),
+}
+
+impl<T> From<Value> for ExtValue<T> {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Null => ExtValue::Null,
+            Value::Bool(b) => ExtValue::Bool(b),
+            Value::Number(n) => ExtValue::Number(n),
+            Value::String(s) => ExtValue::String(s),
+            Value::Array(arr) => ExtValue::Array(arr.into_iter().map(ExtValue::from).collect()),
+            Value::Object(map) => {
+                ExtValue::Object(map.into_iter().map(|(k, v)| (k, v.into())).collect())
+            }
+        }
+    }
+}
+
+/// Encoding hook for custom values.
+pub trait ExtEncoder<T> {
+    /// Tag written before the `|` of entries produced by this encoder.
+    ///
+    /// Must be non-empty, must not contain `|`, and must not be one of the
+    /// built-in prefixes (`b`, `n`, `N`, `s`, `a`, `o`, `x`, `c`, `d`).
+    fn tag(&self) -> &str;
+
+    /// Encode a custom value into its payload.
+    ///
+    /// Returns `None` if this encoder does not handle `value`, so the next
+    /// registered encoder is tried.
+    fn encode(&self, value: &T) -> Option<String>;
+}
+
+/// Decoding hook for custom values.
+pub trait ExtDecoder<T> {
+    /// Tag of the entries this decoder handles (see [`ExtEncoder::tag`]).
+    fn tag(&self) -> &str;
+
+    /// Decode the payload of an entry carrying this decoder's tag.
+    ///
+    /// Returns `None` if the payload is invalid.
+    fn decode(&self, payload: &str) -> Option<T>;
+}
+
+/// Compress an [`ExtValue`], storing custom values through `encoders`.
+///
+/// # Arguments
+///
+/// * `o` - The value tree to compress
+/// * `encoders` - Hooks tried in order for each custom value
+///
+/// # Panics
+///
+/// Panics if an encoder uses an invalid tag, or if no encoder handles a
+/// custom value.
+pub fn compress_ext<T>(o: &ExtValue<T>, encoders: &[&dyn ExtEncoder<T>]) -> Compressed {
+    for encoder in encoders {
+        check_tag(encoder.tag());
+    }
+    let tags: Vec<&str> = encoders.iter().map(|e| e.tag()).collect();
+    let mut mem = make_memory();
+    let root = add_ext_value(&mut mem, o, encoders, &tags);
+    let values = mem_to_values(&mem);
+    Compressed { values, root }
+}
+
+/// Decompress into an [`ExtValue`], restoring custom values through `decoders`.
+///
+/// Entries whose tag has no registered decoder are decoded as regular JSON.
+///
+/// # Arguments
+///
+/// * `c` - The compressed representation
+/// * `decoders` - Hooks dispatched on each entry's tag
+///
+/// # Panics
+///
+/// Panics if a key references an invalid index, an entry is malformed, or a
+/// decoder rejects its payload.
+pub fn decompress_ext<T>(c: Compressed, decoders: &[&dyn ExtDecoder<T>]) -> ExtValue<T> {
+    let (values, root) = root_store(&c.values, c.root.as_str());
+    decode_ext(&values, root, decoders)
+}
+
+/// Panic if `tag` cannot be used for custom entries.
+fn check_tag(tag: &str) {
+    if tag.is_empty() || tag.contains('|') || needs_escape(&format!("{tag}|")) {
+        throw_unsupported_data(&format!("[extension tag {tag:?}]"));
+    }
+}
+
+/// Recursively add an extended value to memory, returning its key.
+fn add_ext_value<T>(
+    mem: &mut Memory,
+    o: &ExtValue<T>,
+    encoders: &[&dyn ExtEncoder<T>],
+    tags: &[&str],
+) -> Key {
+    match o {
+        ExtValue::Null => Key::default(),
+        ExtValue::Bool(b) => add_value(mem, &Value::Bool(*b)),
+        ExtValue::Number(n) => add_value(mem, &Value::Number(n.clone())),
+        ExtValue::String(s) => {
+            let custom_prefix = tags
+                .iter()
+                .any(|t| s.strip_prefix(t).is_some_and(|r| r.starts_with('|')));
+            if custom_prefix {
+                get_value_key_owned(mem, format!("s|{s}"))
+            } else {
+                get_value_key(mem, &encode_str(s))
+            }
+        }
+        ExtValue::Array(arr) => {
+            if arr.is_empty() {
+                return get_value_key(mem, "a|");
+            }
+            let mut acc = String::from("a");
+            for v in arr {
+                let key = match v {
+                    ExtValue::Null => Key::from("_"),
+                    v => add_ext_value(mem, v, encoders, tags),
+                };
+                acc.push('|');
+                acc.push_str(key.as_str());
+            }
+            get_value_key_owned(mem, acc)
+        }
+        ExtValue::Object(entries) => {
+            if entries.is_empty() {
+                return get_value_key(mem, "o|");
+            }
+            let keys: Vec<String> = entries.iter().map(|(k, _)| k.clone()).collect();
+            let key_id = get_schema(mem, &keys);
+            let mut acc = String::from("o|");
+            acc.push_str(key_id.as_str());
+            for (_, v) in entries {
+                let val_key = add_ext_value(mem, v, encoders, tags);
+                acc.push('|');
+                acc.push_str(val_key.as_str());
+            }
+            get_value_key_owned(mem, acc)
+        }
+        ExtValue::Bytes(bytes) => get_value_key_owned(mem, format!("x|{}", blob64::encode(bytes))),
+        ExtValue::Custom(c) => {
+            for encoder in encoders {
+                if let Some(payload) = encoder.encode(c) {
+                    return get_value_key_owned(mem, format!("{}|{payload}", encoder.tag()));
+                }
+            }
+            throw_unknown_data_type()
+        }
+    }
+}
+
+/// Decode a key into an extended value.
+fn decode_ext<T>(values: &[String], key: &str, decoders: &[&dyn ExtDecoder<T>]) -> ExtValue<T> {
+    if is_null_key(key) {
+        return ExtValue::Null;
+    }
+    let v_str = &values[decode_key(key)];
+    if let Some(refs) = v_str.strip_prefix("a|") {
+        if refs.is_empty() {
+            return ExtValue::Array(Vec::new());
+        }
+        return ExtValue::Array(
+            refs.split('|')
+                .map(|k| decode_ext(values, k, decoders))
+                .collect(),
+        );
+    }
+    if v_str.starts_with("o|") {
+        if v_str == "o|" {
+            return ExtValue::Object(Vec::new());
+        }
+        let parts: Vec<&str> = v_str.split('|').collect();
+        let keys = schema_keys(decode_at(values, parts[1]));
+        return ExtValue::Object(
+            keys.into_iter()
+                .zip(&parts[2..])
+                .map(|(k, r)| (k, decode_ext(values, r, decoders)))
+                .collect(),
+        );
+    }
+    if let Some(encoded) = v_str.strip_prefix("x|") {
+        return match blob64::decode(encoded) {
+            Some(bytes) => ExtValue::Bytes(bytes),
+            None => panic!("Invalid base64 in binary entry: {encoded:?}"),
+        };
+    }
+    if let Some((tag, payload)) = v_str.split_once('|')
+        && let Some(decoder) = decoders.iter().find(|d| d.tag() == tag)
+    {
+        return match decoder.decode(payload) {
+            Some(c) => ExtValue::Custom(c),
+            None => panic!("Invalid payload for extension tag {tag:?}: {payload:?}"),
+        };
+    }
+    decode_entry(v_str, &mut |k| decode_at(values, k)).into()
+}
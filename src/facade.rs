@@ -0,0 +1,105 @@
+//! Configured compressor object.
+//!
+//! [`CompressJson`] holds a [`Config`] so it can be set up once and passed
+//! around, instead of threading the configuration through each call to the
+//! `*_with_config` functions. The free functions such as
+//! [`compress`](crate::compress) remain the shortcut for the defaults.
+//!
+//! # Example
+//!
+//! ```rust
+//! use compress_json_rs::{CompressJson, Config};
+//! use serde_json::json;
+//!
+//! let codec = CompressJson::new(Config { sort_key: true, ..Config::default() });
+//!
+//! let data = json!({"b": 1, "a": [true, null]});
+//! let compressed = codec.compress(&data);
+//! assert_eq!(codec.decompress(compressed), data);
+//! ```
+
+use crate::config::{CONFIG, Config};
+use crate::core::{
+    Compressed, compress_str_with_config, compress_with_config, decompress,
+    try_compress_with_config,
+};
+use crate::error::{CompressError, DecompressError};
+use crate::safe::try_decompress_with_config;
+use serde_json::Value;
+
+/// Compresses and decompresses with one [`Config`].
+///
+/// Each method is the `*_with_config` function of the same name called with
+/// the held configuration. The configuration is `Copy`, so a `CompressJson`
+/// is cheap to clone into each thread or request handler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressJson {
+    /// Configuration used by every method
+    config: Config,
+}
+
+impl CompressJson {
+    /// Create a compressor with the given configuration.
+    pub const fn new(config: Config) -> Self {
+        CompressJson { config }
+    }
+
+    /// The configuration this compressor uses.
+    pub const fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Compress a JSON value, as [`compress_with_config`] does.
+    ///
+    /// # Panics
+    ///
+    /// Panics on values the configuration rejects; see
+    /// [`try_compress`](Self::try_compress).
+    pub fn compress(&self, o: &Value) -> Compressed {
+        compress_with_config(o, &self.config)
+    }
+
+    /// Compress a JSON value, reporting a rejected value instead of
+    /// panicking, as [`try_compress_with_config`] does.
+    pub fn try_compress(&self, o: &Value) -> Result<Compressed, CompressError> {
+        try_compress_with_config(o, &self.config)
+    }
+
+    /// Parse JSON text and compress it, as [`compress_str_with_config`]
+    /// does.
+    pub fn compress_str(&self, json: &str) -> Result<Compressed, serde_json::Error> {
+        compress_str_with_config(json, &self.config)
+    }
+
+    /// Restore the original JSON from its compressed form.
+    ///
+    /// Decoding needs no configuration, so this is the same as
+    /// [`decompress`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the compressed data is malformed; see
+    /// [`try_decompress`](Self::try_decompress).
+    pub fn decompress(&self, c: Compressed) -> Value {
+        decompress(c)
+    }
+
+    /// Decompress untrusted data with the configuration's decode limits, as
+    /// [`try_decompress_with_config`] does.
+    pub fn try_decompress(&self, c: &Compressed) -> Result<Value, DecompressError> {
+        try_decompress_with_config(c, &self.config)
+    }
+}
+
+impl Default for CompressJson {
+    /// A compressor using [`CONFIG`].
+    fn default() -> Self {
+        CompressJson::new(CONFIG)
+    }
+}
+
+impl From<Config> for CompressJson {
+    fn from(config: Config) -> Self {
+        CompressJson::new(config)
+    }
+}
@@ -0,0 +1,86 @@
+//! Frequency-ordered key assignment.
+//!
+//! Keys are normally assigned in first-seen order. With
+//! [`Config::frequency_keys`](crate::Config::frequency_keys) the store is
+//! renumbered after compression so that the most referenced entries sit at
+//! the lowest indices, where base-62 keys are shortest.
+
+use crate::core::{Compressed, entry_refs, is_null_key};
+use crate::encode::decode_key;
+use crate::key::Key;
+use crate::number::int_to_s;
+
+/// The entry order that gives the most referenced entries the shortest keys.
+///
+/// References are counted across all `a|`, `o|` (including the schema) and
+/// `c|` entries plus the root. Entries are then ordered by descending count,
+/// keeping first-seen order among equal counts.
+pub(crate) fn frequency_order(c: &Compressed) -> Vec<usize> {
+    let mut counts = vec![0usize; c.values.len()];
+    if !c.root.is_null() {
+        counts[decode_key(c.root.as_str())] += 1;
+    }
+    for value in &c.values {
+        for r in entry_refs(value) {
+            counts[decode_key(r)] += 1;
+        }
+    }
+
+    let mut order: Vec<usize> = (0..c.values.len()).collect();
+    // Stable, so ties keep their first-seen order
+    order.sort_by(|a, b| counts[*b].cmp(&counts[*a]));
+    order
+}
+
+/// Rebuild a store from the entries at `order`, in that order.
+///
+/// The entry at `order[i]` gets key `i` and every reference (including the
+/// root) is rewritten to the new keys. Entries missing from `order` are
+/// dropped, so they must not be referenced by any kept entry.
+pub(crate) fn renumber(c: Compressed, order: &[usize]) -> Compressed {
+    let Compressed { values, root } = c;
+    let mut new_keys = vec![String::new(); values.len()];
+    for (new_index, &old_index) in order.iter().enumerate() {
+        new_keys[old_index] = int_to_s(new_index);
+    }
+
+    let remap = |r: &str| -> String {
+        if is_null_key(r) {
+            r.to_string()
+        } else {
+            new_keys[decode_key(r)].clone()
+        }
+    };
+    let mut values: Vec<Option<String>> = values.into_iter().map(Some).collect();
+    let sorted = order
+        .iter()
+        .map(|&old_index| {
+            let value = values[old_index].take().unwrap_or_default();
+            if !is_container(&value) {
+                return value;
+            }
+            // Keep the prefix letter, rewrite every reference after it
+            let mut parts = value.split('|');
+            let mut out = parts.next().unwrap_or_default().to_string();
+            for r in parts {
+                out.push('|');
+                out.push_str(&remap(r));
+            }
+            out
+        })
+        .collect();
+    let root = if root.is_null() {
+        root
+    } else {
+        Key::from(remap(root.as_str()))
+    };
+    Compressed {
+        values: sorted,
+        root,
+    }
+}
+
+/// Whether an entry holds references (a non-empty `a|`, `o|` or `c|` entry).
+pub(crate) fn is_container(value: &str) -> bool {
+    value.len() > 2 && matches!(value.get(..2), Some("a|" | "o|" | "c|"))
+}
@@ -0,0 +1,65 @@
+//! Removal of unreferenced store entries.
+//!
+//! Editing a compressed value in place (see [`set_path`](crate::set_path))
+//! or merging stores leaves entries that nothing refers to anymore.
+//! [`garbage_collect`] drops them and renumbers the remaining entries so the
+//! keys stay compact.
+
+use crate::core::{Compressed, entry_refs, is_inline_root};
+use crate::encode::decode_key;
+use crate::frequency::renumber;
+
+/// Drop the store entries that are not reachable from the root.
+///
+/// Entries are reachable if the root refers to them directly or through
+/// `a|`, `o|` (including the schema) and `c|` references. The remaining
+/// entries keep their relative order and are renumbered from `0`, with every
+/// reference rewritten to match. The decoded value is unchanged.
+///
+/// # Arguments
+///
+/// * `c` - The compressed representation to clean up
+///
+/// # Returns
+///
+/// The compressed representation without unreferenced entries
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{compress, decompress, garbage_collect, set_path};
+/// use serde_json::json;
+///
+/// let mut c = compress(&json!({"name": "Alice"}));
+/// set_path(&mut c, "/name", &json!("Bob")).unwrap();
+/// // "Alice" and the old object entry are still stored
+/// assert!(c.values.contains(&"Alice".to_string()));
+///
+/// let c = garbage_collect(c);
+/// assert!(!c.values.contains(&"Alice".to_string()));
+/// assert_eq!(decompress(c), json!({"name": "Bob"}));
+/// ```
+///
+/// # Panics
+///
+/// Panics if a reference is not a valid key into the store.
+pub fn garbage_collect(c: Compressed) -> Compressed {
+    // An inline root references no store entries
+    if is_inline_root(&c.values, c.root.as_str()) {
+        return c;
+    }
+    let mut reachable = vec![false; c.values.len()];
+    let mut pending = Vec::new();
+    if !c.root.is_null() {
+        pending.push(decode_key(c.root.as_str()));
+    }
+    while let Some(index) = pending.pop() {
+        if reachable[index] {
+            continue;
+        }
+        reachable[index] = true;
+        pending.extend(entry_refs(&c.values[index]).into_iter().map(decode_key));
+    }
+    let order: Vec<usize> = (0..c.values.len()).filter(|&i| reachable[i]).collect();
+    renumber(c, &order)
+}
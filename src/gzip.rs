@@ -0,0 +1,121 @@
+//! Gzip integration.
+//!
+//! Available with the `flate2` feature.
+//!
+//! - [`compress_gzip`] / [`decompress_gzip`] apply gzip on top of the
+//!   serialized `[values, root]` form for maximum reduction in cold storage.
+//! - [`size_report`] measures a document as plain JSON, as compressed JSON,
+//!   and as gzip-compressed plain JSON, to help decide whether this library,
+//!   gzip, or both suit a payload.
+//!
+//! # Expected Reduction
+//!
+//! Deduplication removes repeated values and schemas, but the remaining
+//! store still has redundancy (similar strings, recurring reference
+//! patterns) that gzip removes. On repetitive documents such as arrays of
+//! similar records, gzip typically shrinks the compressed form by a further
+//! 50-80%.
+
+use crate::core::{Compressed, compress, decompress};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde_json::Value;
+use std::io::Write;
+
+/// Serialized sizes of a document in different forms, in bytes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SizeReport {
+    /// Size of the document serialized as JSON
+    pub raw_bytes: usize,
+    /// Size of the compressed `[values, root]` form serialized as JSON
+    pub compressed_bytes: usize,
+    /// Size of the JSON serialization after gzip (default level)
+    pub gzip_bytes: usize,
+}
+
+impl SizeReport {
+    /// Compressed size as a fraction of the raw size.
+    pub fn compressed_ratio(&self) -> f64 {
+        self.compressed_bytes as f64 / self.raw_bytes as f64
+    }
+
+    /// Gzip size as a fraction of the raw size.
+    pub fn gzip_ratio(&self) -> f64 {
+        self.gzip_bytes as f64 / self.raw_bytes as f64
+    }
+}
+
+/// Measure a document as raw JSON, compressed JSON, and gzipped JSON.
+///
+/// # Arguments
+///
+/// * `o` - The document to measure
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::size_report;
+/// use serde_json::json;
+///
+/// let data = json!([{"name": "Alice"}, {"name": "Alice"}, {"name": "Alice"}]);
+/// let report = size_report(&data);
+///
+/// assert_eq!(report.raw_bytes, data.to_string().len());
+/// assert!(report.compressed_bytes < report.raw_bytes);
+/// ```
+pub fn size_report(o: &Value) -> SizeReport {
+    let raw = o.to_string();
+    let compressed = serde_json::to_string(&compress(o)).expect("serialize compressed");
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(raw.as_bytes()).expect("write to Vec");
+    let gzip = encoder.finish().expect("write to Vec");
+
+    SizeReport {
+        raw_bytes: raw.len(),
+        compressed_bytes: compressed.len(),
+        gzip_bytes: gzip.len(),
+    }
+}
+
+/// Compress a value and gzip its serialized `[values, root]` form.
+///
+/// # Arguments
+///
+/// * `o` - The value to compress
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{compress_gzip, decompress_gzip};
+/// use serde_json::json;
+///
+/// let data = json!({"name": "Alice", "tags": ["a", "b"]});
+/// let bytes = compress_gzip(&data);
+/// assert_eq!(decompress_gzip(&bytes).unwrap(), data);
+/// ```
+pub fn compress_gzip(o: &Value) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    serde_json::to_writer(&mut encoder, &compress(o)).expect("write to Vec");
+    encoder.finish().expect("write to Vec")
+}
+
+/// Restore a value from the output of [`compress_gzip`].
+///
+/// # Arguments
+///
+/// * `bytes` - Gzipped `[values, root]` JSON
+///
+/// # Errors
+///
+/// Returns an error if `bytes` is not valid gzip or does not contain a
+/// `[values, root]` pair.
+///
+/// # Panics
+///
+/// Panics if the decoded store is malformed, like [`decompress`].
+pub fn decompress_gzip(bytes: &[u8]) -> Result<Value, serde_json::Error> {
+    let compressed: Compressed = serde_json::from_reader(GzDecoder::new(bytes))?;
+    Ok(decompress(compressed))
+}
@@ -0,0 +1,84 @@
+//! Content hashing of compressed payloads.
+//!
+//! [`content_hash`] gives a stable 64-bit hash that other implementations
+//! can reproduce, so caches shared between languages can key entries by
+//! content.
+
+use crate::core::Compressed;
+use std::io::{self, Write};
+
+/// FNV-1a 64-bit offset basis
+pub(crate) const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+/// FNV-1a 64-bit prime
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Hash a compressed payload with 64-bit FNV-1a over its JSON serialization.
+///
+/// # Algorithm
+///
+/// The hash is 64-bit FNV-1a over the UTF-8 bytes of the payload's compact
+/// JSON serialization, `[[values...],"root"]`, with no whitespace and
+/// non-ASCII characters written as-is. This is the output of
+/// `JSON.stringify(payload)` in JavaScript and of
+/// `json.dumps(payload, separators=(",", ":"), ensure_ascii=False)` in
+/// Python.
+///
+/// FNV-1a starts from the offset basis `0xcbf29ce484222325` and, for each
+/// byte, XORs the byte into the hash and multiplies by the prime
+/// `0x100000001b3` (wrapping at 64 bits).
+///
+/// # Canonical Form
+///
+/// The hash covers the compressed form, not the document: the same
+/// document compressed with a different key order or store layout hashes
+/// differently. Compress with `sort_key` and otherwise default options on
+/// every side to get equal hashes for equal documents.
+///
+/// # Arguments
+///
+/// * `c` - The compressed representation
+///
+/// # Returns
+///
+/// The 64-bit hash
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{Config, compress_with_config, content_hash};
+/// use serde_json::json;
+///
+/// let config = Config { sort_key: true, ..Config::default() };
+/// let c = compress_with_config(&json!({"a": 1, "b": 2}), &config);
+///
+/// // `[["a","b","a|0|1","n|1","n|2","o|2|3|4"],"5"]`
+/// assert_eq!(content_hash(&c), 0x216c_1bcb_f65e_8d46);
+/// ```
+pub fn content_hash(c: &Compressed) -> u64 {
+    let mut hasher = Fnv1a(FNV_OFFSET);
+    serde_json::to_writer(&mut hasher, c).expect("hashing never fails");
+    hasher.0
+}
+
+/// Writer that folds every byte into an FNV-1a hash.
+struct Fnv1a(u64);
+
+impl Write for Fnv1a {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 = fnv(self.0, buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Fold `bytes` into an FNV-1a hash.
+pub(crate) fn fnv(mut hash: u64, bytes: &[u8]) -> u64 {
+    for &b in bytes {
+        hash ^= u64::from(b);
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
@@ -6,6 +6,7 @@
 //! # Functions
 //!
 //! - [`trim_undefined`] - Remove null values from an object (shallow)
+//! - [`trim_undefined_with`] - Remove values matching a predicate from an object (shallow)
 //! - [`trim_undefined_recursively`] - Remove null values from nested objects
 //!
 //! # Use Cases
@@ -19,7 +20,9 @@
 //!
 //! The functions are named `trim_undefined` for compatibility with the
 //! JavaScript version, where `undefined` and `null` have different semantics.
-//! In Rust/JSON, these functions operate on `null` values.
+//! In Rust/JSON, these functions operate on `null` values. Data that marks
+//! "undefined" some other way (e.g. a sentinel string) and needs to keep
+//! real `null`s can use [`trim_undefined_with`].
 
 use serde_json::{Map, Value};
 
@@ -61,7 +64,41 @@ use serde_json::{Map, Value};
 /// Arrays within the object are not modified. If an object contains an array
 /// with null elements, those null elements will remain.
 pub fn trim_undefined(object: &mut Map<String, Value>) {
-    object.retain(|_, v| !v.is_null());
+    trim_undefined_with(object, Value::is_null);
+}
+
+/// Remove keys whose values the caller counts as undefined (shallow).
+///
+/// Like [`trim_undefined`], but `is_undefined` decides which values are
+/// removed, so `null` can be kept when "undefined" is represented some
+/// other way.
+///
+/// # Arguments
+///
+/// * `object` - Mutable reference to a JSON object map
+/// * `is_undefined` - Returns `true` for values whose keys should be removed
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::trim_undefined_with;
+/// use serde_json::{json, Map, Value};
+///
+/// let mut data: Map<String, Value> = serde_json::from_value(json!({
+///     "name": "Alice",
+///     "email": null,
+///     "phone": "$undefined"
+/// })).unwrap();
+///
+/// trim_undefined_with(&mut data, |v| v == "$undefined");
+///
+/// assert_eq!(Value::Object(data), json!({"name": "Alice", "email": null}));
+/// ```
+pub fn trim_undefined_with<F: Fn(&Value) -> bool>(
+    object: &mut Map<String, Value>,
+    is_undefined: F,
+) {
+    object.retain(|_, v| !is_undefined(v));
 }
 
 /// Recursively remove keys with null values in nested JSON objects.
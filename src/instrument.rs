@@ -0,0 +1,177 @@
+//! Compression and decompression metrics.
+//!
+//! An [`Instrumentation`] receives callbacks as values are compressed and
+//! decompressed, so dashboards can gather custom metrics (entries stored,
+//! schema reuse, nesting depth) without patching the crate. Every callback
+//! has an empty default, and a memory without instrumentation skips them
+//! entirely, so uninstrumented compression pays nothing.
+//!
+//! # Example
+//!
+//! ```rust
+//! use compress_json_rs::{CONFIG, Instrumentation, compress_instrumented};
+//! use serde_json::json;
+//! use std::sync::Arc;
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//!
+//! #[derive(Default)]
+//! struct MaxDepth(AtomicUsize);
+//!
+//! impl Instrumentation for MaxDepth {
+//!     fn on_depth(&self, depth: usize) {
+//!         self.0.fetch_max(depth, Ordering::Relaxed);
+//!     }
+//! }
+//!
+//! let metrics = Arc::new(MaxDepth::default());
+//! compress_instrumented(&json!({"a": [[1]]}), &CONFIG, metrics.clone());
+//! assert_eq!(metrics.0.load(Ordering::Relaxed), 3);
+//! ```
+
+use crate::config::Config;
+use crate::core::{
+    Compressed, compress_in, decode, decode_at, decode_entry, is_inline_root, is_null_key,
+    schema_keys,
+};
+use crate::encode::decode_key;
+use crate::memory::{instrument, make_memory_with_config};
+use serde_json::{Map, Value};
+use std::sync::Arc;
+
+/// Receiver of compression and decompression events.
+///
+/// # Events
+///
+/// | Callback | Compression | Decompression |
+/// |----------|-------------|---------------|
+/// | `on_value_stored` | Each new store entry, including schemas and chunks | — |
+/// | `on_schema_reused` | Each object whose key list is already stored | — |
+/// | `on_depth` | Each value added, with its nesting depth | Each value decoded, with its nesting depth |
+///
+/// The root has depth 0 and the elements and fields of a container are one
+/// level deeper than it. `null` array elements are written as a marker
+/// without being added, so compression does not report their depth.
+///
+/// Callbacks take `&self`, so an implementation can be shared through an
+/// [`Arc`] with the code reading its metrics; use atomics or locks for the
+/// counters.
+pub trait Instrumentation: Send + Sync {
+    /// Called when a new entry is added to the store, with its encoded form.
+    ///
+    /// Over a whole compression, the number of calls equals the length of
+    /// the resulting store.
+    fn on_value_stored(&self, _value: &str) {}
+
+    /// Called when an object's key list is found among the stored schemas,
+    /// with the key of the shared schema entry.
+    fn on_schema_reused(&self, _schema: &str) {}
+
+    /// Called for each value compressed or decompressed, with its nesting
+    /// depth.
+    fn on_depth(&self, _depth: usize) {}
+}
+
+/// Compress a JSON value, reporting events to `instrumentation`.
+///
+/// Produces the same output as [`compress_with_config`](crate::compress_with_config).
+///
+/// # Arguments
+///
+/// * `o` - A reference to the JSON value to compress
+/// * `config` - The configuration to compress with
+/// * `instrumentation` - Receiver of the compression events
+///
+/// # Panics
+///
+/// Panics where [`compress_with_config`](crate::compress_with_config) does.
+pub fn compress_instrumented(
+    o: &Value,
+    config: &Config,
+    instrumentation: Arc<dyn Instrumentation>,
+) -> Compressed {
+    let mut mem = make_memory_with_config(config);
+    instrument(&mut mem, instrumentation);
+    compress_in(mem, o).unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// Decompress a compressed representation, reporting the depth of each
+/// value to `instrumentation`.
+///
+/// Produces the same value as [`decompress`](crate::decompress).
+///
+/// # Arguments
+///
+/// * `c` - The compressed representation
+/// * `instrumentation` - Receiver of the decompression events
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{Instrumentation, compress, decompress_instrumented};
+/// use serde_json::json;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// #[derive(Default)]
+/// struct Nodes(AtomicUsize);
+///
+/// impl Instrumentation for Nodes {
+///     fn on_depth(&self, _depth: usize) {
+///         self.0.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let data = json!({"tags": ["a", "a", null]});
+/// let nodes = Nodes::default();
+/// assert_eq!(decompress_instrumented(&compress(&data), &nodes), data);
+/// assert_eq!(nodes.0.load(Ordering::Relaxed), 5);
+/// ```
+///
+/// # Panics
+///
+/// Panics if a key references an invalid index or an entry is malformed.
+pub fn decompress_instrumented(c: &Compressed, instrumentation: &dyn Instrumentation) -> Value {
+    if is_inline_root(&c.values, c.root.as_str()) {
+        instrumentation.on_depth(0);
+        return decode(&c.values, &c.root);
+    }
+    decode_node(&c.values, c.root.as_str(), 0, instrumentation)
+}
+
+/// Decode the value referenced by `key`, reporting it and its descendants.
+fn decode_node(
+    values: &[String],
+    key: &str,
+    depth: usize,
+    instrumentation: &dyn Instrumentation,
+) -> Value {
+    instrumentation.on_depth(depth);
+    if is_null_key(key) {
+        return Value::Null;
+    }
+    let v_str = values[decode_key(key)].as_str();
+    if let Some(rest) = v_str.strip_prefix("o|") {
+        if rest.is_empty() {
+            return Value::Object(Map::new());
+        }
+        let mut parts = rest.split('|');
+        let keys = schema_keys(decode_at(values, parts.next().unwrap_or_default()));
+        let mut map = Map::with_capacity(keys.len());
+        for (k, r) in keys.into_iter().zip(parts) {
+            map.insert(k, decode_node(values, r, depth + 1, instrumentation));
+        }
+        return Value::Object(map);
+    }
+    if v_str.starts_with("a|") {
+        return decode_entry(v_str, &mut |r| {
+            decode_node(values, r, depth + 1, instrumentation)
+        });
+    }
+    let value = decode_entry(v_str, &mut |r| decode_at(values, r));
+    // Packed (`d|`) integer arrays hold their elements inline
+    if let Value::Array(items) = &value {
+        for _ in items {
+            instrumentation.on_depth(depth + 1);
+        }
+    }
+    value
+}
@@ -0,0 +1,124 @@
+//! Strongly-typed references into the values array.
+
+use crate::number::ITO_S;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Key type for compressed references.
+///
+/// Keys are base-62 encoded strings that reference positions in the values array.
+/// The base-62 encoding uses characters `0-9`, `A-Z`, and `a-z`. An empty key
+/// (or the `_` array marker) stands for `null`.
+///
+/// Keys are a distinct type from value strings so that an encoded value can
+/// not be passed where a reference is expected by mistake. Conversions from
+/// and to `String` are free; parsing with [`FromStr`] also checks the key's
+/// characters. Keys serialize as plain strings.
+///
+/// # Examples
+///
+/// - `"0"` - First value (index 0)
+/// - `"A"` - Eleventh value (index 10)
+/// - `"10"` - Sixty-third value (index 62)
+///
+/// ```rust
+/// use compress_json_rs::Key;
+///
+/// let key: Key = "10".parse().unwrap();
+/// assert_eq!(key.as_str(), "10");
+/// assert_eq!(key.to_string(), "10");
+/// assert!("a|0".parse::<Key>().is_err());
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Key(String);
+
+impl Key {
+    /// The key's text.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Consume the key, returning its text.
+    pub fn into_string(self) -> String {
+        self.0
+    }
+
+    /// Whether this key stands for `null` (empty or the `_` marker).
+    pub fn is_null(&self) -> bool {
+        self.0.is_empty() || self.0 == "_"
+    }
+}
+
+impl From<String> for Key {
+    fn from(s: String) -> Self {
+        Key(s)
+    }
+}
+
+impl From<&str> for Key {
+    fn from(s: &str) -> Self {
+        Key(s.to_string())
+    }
+}
+
+impl From<Key> for String {
+    fn from(key: Key) -> Self {
+        key.0
+    }
+}
+
+impl AsRef<str> for Key {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for Key {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Key {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Error returned when parsing a [`Key`] from text that is not a key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseKeyError {
+    /// The rejected text
+    text: String,
+}
+
+impl fmt::Display for ParseKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid key {:?}", self.text)
+    }
+}
+
+impl std::error::Error for ParseKeyError {}
+
+impl FromStr for Key {
+    type Err = ParseKeyError;
+
+    /// Parse a key, accepting base-62 text, the empty key and `_`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "_" || s.chars().all(|c| ITO_S.contains(c)) {
+            Ok(Key(s.to_string()))
+        } else {
+            Err(ParseKeyError {
+                text: s.to_string(),
+            })
+        }
+    }
+}
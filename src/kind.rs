@@ -0,0 +1,69 @@
+//! Cheap inspection of a compressed value's type.
+//!
+//! [`root_kind`] reads the prefix of the root store entry to tell what kind
+//! of value a payload holds, without decoding the tree. This is useful for
+//! routing payloads (e.g. "is this an array or an object?").
+
+use crate::core::{Compressed, is_null_key, root_store};
+use crate::encode::decode_key;
+
+/// The JSON type of a compressed value.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ValueKind {
+    /// `null`
+    Null,
+    /// `true` or `false`
+    Bool,
+    /// Any number, including the special `N|` values
+    Number,
+    /// Any string, including chunked (`c|`) and binary (`x|`) entries
+    String,
+    /// An array, including packed (`d|`) integer arrays
+    Array,
+    /// An object
+    Object,
+}
+
+/// Determine the kind of the root value from its store entry prefix.
+///
+/// Only the root entry is inspected; nothing is decoded. An empty (or `_`)
+/// root key denotes `null`.
+///
+/// Special numbers (`N|+`, `N|-`, `N|0`) are reported as
+/// [`ValueKind::Number`], even though [`decompress`](crate::decompress)
+/// turns them into `null` since JSON cannot represent them.
+///
+/// # Arguments
+///
+/// * `c` - The compressed representation to inspect
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{ValueKind, compress, root_kind};
+/// use serde_json::json;
+///
+/// assert_eq!(root_kind(&compress(&json!([1, 2]))), ValueKind::Array);
+/// assert_eq!(root_kind(&compress(&json!({"a": 1}))), ValueKind::Object);
+/// assert_eq!(root_kind(&compress(&json!(null))), ValueKind::Null);
+/// ```
+///
+/// # Panics
+///
+/// Panics if the root key references an invalid index.
+pub fn root_kind(c: &Compressed) -> ValueKind {
+    let (values, root) = root_store(&c.values, c.root.as_str());
+    if is_null_key(root) {
+        return ValueKind::Null;
+    }
+    let v_str = &values[decode_key(root)];
+    match v_str.split_once('|').map(|(prefix, _)| prefix) {
+        Some("b") => ValueKind::Bool,
+        Some("n" | "N") => ValueKind::Number,
+        // Plain and packed (`d|`) arrays
+        Some("a" | "d") => ValueKind::Array,
+        Some("o") => ValueKind::Object,
+        // Plain, escaped (`s|`), chunked (`c|`) and binary (`x|`) strings
+        _ => ValueKind::String,
+    }
+}
@@ -19,7 +19,7 @@
 //! | **Schema Deduplication** | Objects with identical keys share schemas |
 //! | **Compact Keys** | Base-62 encoding for minimal key size |
 //! | **UTF-8 Safe** | Full Unicode support for all string values |
-//! | **Zero Dependencies** | Only requires `serde_json` |
+//! | **Few Dependencies** | Only requires `serde`, `serde_json` and `ryu` |
 //!
 //! # Quick Start
 //!
@@ -31,6 +31,16 @@
 //! serde_json = "1.0"
 //! ```
 //!
+//! Optional Cargo features:
+//!
+//! | Feature | Description |
+//! |---------|-------------|
+//! | `arbitrary_precision` | Preserve numbers beyond `f64` precision exactly |
+//! | `flate2` | Gzip the compressed form (`compress_gzip`) and compare sizes (`size_report`) |
+//! | `serde_transcode` | Compress JSON text from a reader straight into a writer (`transcode`) |
+//! | `base64` | Compress into a base64url token for URLs (`compress_to_urlsafe`, `decompress_from_urlsafe`) |
+//! | `simd-json` | Compress and decompress `simd_json::OwnedValue` directly (`compress_simd`, `decompress_simd`, `try_compress_simd`, `compress_simd_with_report`) |
+//!
 //! Basic usage:
 //!
 //! ```rust
@@ -44,7 +54,7 @@
 //!     "roles": ["admin", "user"]
 //! });
 //!
-//! // Compress into (values, root_key)
+//! // Compress into a values array and root key
 //! let compressed = compress(&data);
 //!
 //! // Decompress back to original
@@ -60,22 +70,65 @@
 //! | Function | Description |
 //! |----------|-------------|
 //! | [`compress`] | Compress a JSON value into [`Compressed`] format |
+//! | [`compress_owned`] | Compress an owned value, moving its strings |
+//! | [`compress_with_config`] | Compress with a custom [`Config`] |
+//! | [`try_compress_with_config`] | Compress, returning a [`CompressError`] for rejected values |
+//! | [`compress_with_report`] | Compress, listing each lossy change as a [`Warning`] |
+//! | [`compress_str`] | Parse JSON text and compress it |
+//! | [`compress_str_with_spans`] | Parse and compress JSON text, recording each entry's source byte range |
+//! | [`compress_to_lines`] | Compress into text with one store entry per line |
+//! | [`compress_to_writer_string`] | Compress, appending the `[values, root]` JSON to a reused `String` |
+//! | [`compress_batch`] | Compress several documents into one shared store |
+//! | [`compress_shared`] | Compress an array of `Rc` subtrees, storing each allocation once |
 //! | [`decompress`] | Restore original JSON from [`Compressed`] format |
 //! | [`decode`] | Decode a single key from the values array |
+//! | [`decompress_from_lines`] | Decompress the text form of [`compress_to_lines`] |
+//! | [`decompress_to_raw`] | Restore JSON as a serialized `RawValue` |
+//! | [`decompress_into_value`] | Decompress into an existing `Value`, reusing its allocations |
+//! | [`decompress_ordered`] | Restore an object root as key/value pairs in stored order |
+//! | [`decompress_rows`] | Deserialize each element of an array root into a `Vec<T>` |
+//! | [`array_iter`] | Iterate over a compressed array's elements, decoding lazily |
+//! | [`try_decompress`] | Decompress untrusted data, returning a [`DecompressError`] |
+//! | [`try_decompress_with_config`] | Fallible decompression with decode limits |
+//! | [`decompress_safe`] | Decompress any input without panicking, with every limit on |
+//! | [`validate`] | Check that a payload decodes, without decoding it |
+//! | [`validate_reader`] | Parse a payload from a stream and [`validate`] it |
+//! | [`array_get`] | Decode one element of an array root without its siblings |
+//! | [`compress_diff`] | Compress only what changed relative to a base document |
+//! | [`apply_diff`] | Rebuild a document from its base and a diff |
+//! | [`get_path`] | Read the value at a JSON Pointer |
+//! | [`get_paths`] | Read the values at several JSON Pointers in one pass |
+//! | [`set_path`] | Replace the value at a JSON Pointer without recompressing |
+//! | [`splice_path`] | Place an already compressed value at a JSON Pointer by reference |
+//! | [`garbage_collect`] | Drop store entries no longer reachable from the root |
+//! | [`decompress_visit`] | Walk compressed data with a [`DecodeVisitor`] |
+//! | [`compress_ext`] | Compress an [`ExtValue`] with custom value hooks |
+//! | [`decompress_ext`] | Decompress into an [`ExtValue`] with custom value hooks |
 //!
 //! ## Types
 //!
 //! | Type | Description |
 //! |------|-------------|
-//! | [`Compressed`] | Tuple type `(Vec<String>, String)` for compressed data |
-//! | [`Key`] | String type alias for base-62 encoded references |
+//! | [`Compressed`] | Values array and root key of compressed data |
+//! | [`Key`] | Base-62 encoded reference into the values array |
+//! | [`ParseKeyError`] | Error parsing a [`Key`] from text |
+//! | [`ValueKind`] | JSON type of a compressed value, see [`root_kind`] |
 //! | [`CONFIG`] | Global configuration constants |
+//! | [`CompressJson`] | Compressor holding a [`Config`] for repeated use |
+//! | [`NullObjectPolicy`] | How `null` object values are stored, see [`Config::null_object_policy`] |
+//! | [`CompressError`] | Reason a value was rejected by [`try_compress_with_config`] |
+//! | [`DecompressError`] | Reason untrusted data could not be decompressed |
+//! | [`PathError`] | Reason [`set_path`] or [`splice_path`] could not apply a pointer |
+//! | [`SourceSpans`] | Source byte range of each entry, see [`compress_str_with_spans`] |
+//! | [`Warning`] | A lossy change reported by [`compress_with_report`] |
+//! | [`Instrumentation`] | Receiver of compression and decompression events |
 //!
 //! ## Helper Functions
 //!
 //! | Function | Description |
 //! |----------|-------------|
 //! | [`trim_undefined`] | Remove null values from object (shallow) |
+//! | [`trim_undefined_with`] | Remove values matching a predicate from object (shallow) |
 //! | [`trim_undefined_recursively`] | Remove null values from nested objects |
 //!
 //! ## Low-Level API
@@ -83,14 +136,45 @@
 //! | Function | Description |
 //! |----------|-------------|
 //! | [`make_memory`] | Create a new compression memory store |
+//! | [`make_memory_with_config`] | Create a memory store with a custom [`Config`] |
 //! | [`add_value`] | Add a value to memory, get its key |
+//! | [`add_compressed`] | Copy an already compressed value into memory, get its key |
+//! | [`instrument`] | Report a memory's compression events to an [`Instrumentation`] |
 //! | [`mem_to_values`] | Extract values array from memory |
+//! | [`dictionary_len`] | Count the values a memory keeps for deduplication |
+//! | [`snapshot`] | Checkpoint a memory as a serializable [`MemorySnapshot`] |
+//! | [`restore`] | Rebuild a memory from a [`MemorySnapshot`] |
+//! | [`Decoder`] | Decode keys from a borrowed store, optionally memoized across calls |
+//! | [`DecodeScratch`] | Decode many payloads through one reused buffer |
+//! | [`ArrayCompressor`] | Compress an array one element at a time |
+//! | [`ArrayWriter`] | Compress an array one element at a time into a writer |
+//!
+//! ## Diagnostics
+//!
+//! | Function | Description |
+//! |----------|-------------|
+//! | [`debug_dump`] | Render a memory's store and schemas as text |
+//! | [`value_histogram`] | Count references to each stored value |
+//! | [`expanded_node_count`] | Count the nodes of the decompressed tree |
+//! | [`root_kind`] | Get the root value's [`ValueKind`] without decoding |
+//! | [`content_hash`] | Stable cross-language hash of a compressed payload |
+//! | [`compress_with_stats`] | Compress and report sizes and store entries by type ([`CompressStats`]) |
+//! | [`diff_against`] | List where decompressed data differs from an expected value |
+//! | [`semantically_eq`] | Compare two payloads by the document they hold, ignoring store order |
+//! | [`compress_instrumented`] | Compress, reporting events to an [`Instrumentation`] |
+//! | [`decompress_instrumented`] | Decompress, reporting the depth of each value |
+//!
+//! ## Codec
+//!
+//! The [`codec`] module exposes the per-type encoders and decoders of
+//! individual store entries (`decode_bool`, `decode_num`, `decode_str`, ...)
+//! for building custom decoders.
 //!
 //! # Compression Format
 //!
-//! The [`Compressed`] type is a tuple `(Vec<String>, String)`:
-//! - First element: Deduplicated value store
-//! - Second element: Base-62 key pointing to root value
+//! The [`Compressed`] struct holds two fields, serialized as `[values, root]`:
+//! - `values`: Deduplicated value store
+//! - `root`: Base-62 key pointing to root value
 //!
 //! ## Value Encoding
 //!
@@ -104,8 +188,11 @@
 //! | `N\|+` | Infinity | `N\|+` | `Infinity` |
 //! | `N\|-` | -Infinity | `N\|-` | `-Infinity` |
 //! | `N\|0` | NaN | `N\|0` | `NaN` |
+//! | `c\|` | Chunked string | `c\|0\|1` | `"chunk0chunk1"` (with `chunk_strings`) |
+//! | `x\|` | Binary blob | `x\|AAEC` | `[0, 1, 2]` as [`ExtValue::Bytes`] |
 //! | `s\|` | Escaped string | `s\|n\|foo` | `"n\|foo"` |
 //! | `a\|` | Array | `a\|0\|1\|2` | `[val0, val1, val2]` |
+//! | `d\|` | Packed integer array | `d\|G8\|1\|4` | `[1000, 1001, 1005]` (with `pack_int_arrays`) |
 //! | `o\|` | Object | `o\|0\|1\|2` | `{schema0: val1, ...}` |
 //! | _(none)_ | String | `hello` | `"hello"` |
 //! | `""` / `_` | Null | | `null` |
@@ -119,6 +206,9 @@
 //! Key:   "0" "1" "2"   "9" "A" "B"   "Z" "a" "b"   "z" "10" "11"
 //! ```
 //!
+//! The alphabet and base are exposed as [`KEY_ALPHABET`] and [`KEY_BASE`],
+//! and [`key_for_index`] returns the key for a given store index.
+//!
 //! # Examples
 //!
 //! ## Serialize for Storage
@@ -147,7 +237,7 @@
 //! Compression is most effective for repetitive data:
 //!
 //! ```rust
-//! use compress_json_rs::{compress, decompress};
+//! use compress_json_rs::{Compressed, compress, decompress};
 //! use serde_json::json;
 //!
 //! // Data with repeated schema and values
@@ -157,13 +247,13 @@
 //!     { "id": 3, "name": "Charlie", "role": "admin" },
 //! ]);
 //!
-//! let (values, root) = compress(&users);
+//! let Compressed { values, root } = compress(&users);
 //!
 //! // Schema ["id", "name", "role"] stored once
 //! // Value "user" stored once, referenced twice
 //! println!("Compressed to {} unique values", values.len());
 //!
-//! let restored = decompress((values, root));
+//! let restored = decompress(Compressed { values, root });
 //! assert_eq!(users, restored);
 //! ```
 //!
@@ -273,22 +363,100 @@
 #![warn(rustdoc::missing_crate_level_docs)]
 
 // Module declarations
+mod blob64;
 mod boolean;
+pub mod codec;
 mod config;
 mod core;
 mod debug;
+mod decoder;
+mod diagnostics;
+mod diff;
 mod encode;
+mod error;
+mod ext;
+mod facade;
+mod frequency;
+mod gc;
+#[cfg(feature = "flate2")]
+mod gzip;
+mod hash;
 mod helpers;
+mod instrument;
+mod key;
+mod kind;
+mod lines;
 mod memory;
 mod number;
+mod packed;
+mod parse;
+mod path;
+mod safe;
+mod sections;
+#[cfg(feature = "simd-json")]
+mod simd;
+mod sorted;
+mod spans;
+mod stats;
+mod stream;
+#[cfg(feature = "serde_transcode")]
+mod transcode;
+#[cfg(feature = "base64")]
+mod url;
+mod visit;
+mod wire;
 
 // Re-export core functionality
-pub use core::{Compressed, compress, decode, decompress};
+pub use core::{
+    Compressed, array_iter, compress, compress_batch, compress_owned, compress_shared,
+    compress_str, compress_str_with_config, compress_with_config, compress_with_report, decode,
+    decompress, decompress_into_value, decompress_ordered, decompress_rows, decompress_to_raw,
+    try_compress_with_config,
+};
 
 // Expose lower-level APIs
-pub use config::{CONFIG, Config};
-pub use helpers::{trim_undefined, trim_undefined_recursively};
-pub use memory::{Key, Memory, add_value, make_memory, mem_to_values};
+pub use config::{CONFIG, Config, NullObjectPolicy};
+pub use decoder::{DecodeScratch, Decoder};
+pub use diagnostics::{debug_dump, diff_against, semantically_eq, value_histogram};
+pub use diff::{apply_diff, compress_diff};
+pub use error::{CompressError, DecompressError, PathError, Warning};
+pub use ext::{ExtDecoder, ExtEncoder, ExtValue, compress_ext, decompress_ext};
+pub use facade::CompressJson;
+pub use gc::garbage_collect;
+#[cfg(feature = "flate2")]
+pub use gzip::{SizeReport, compress_gzip, decompress_gzip, size_report};
+pub use hash::content_hash;
+pub use helpers::{trim_undefined, trim_undefined_recursively, trim_undefined_with};
+pub use instrument::{Instrumentation, compress_instrumented, decompress_instrumented};
+pub use key::{Key, ParseKeyError};
+pub use kind::{ValueKind, root_kind};
+pub use lines::{compress_to_lines, decompress_from_lines};
+pub use memory::{
+    Memory, MemorySnapshot, add_compressed, add_value, dictionary_len, instrument, make_memory,
+    make_memory_with_config, mem_to_values, restore, snapshot,
+};
+pub use path::{get_path, get_paths, set_path, splice_path};
+pub use safe::{
+    array_get, decompress_safe, try_decompress, try_decompress_with_config, validate,
+    validate_reader,
+};
+#[cfg(feature = "simd-json")]
+pub use simd::{
+    compress_simd, compress_simd_with_config, compress_simd_with_report, decompress_simd,
+    try_compress_simd,
+};
+pub use spans::{SourceSpans, compress_str_with_spans};
+pub use stats::{CompressStats, compress_with_stats};
+pub use stream::{ArrayCompressor, ArrayWriter};
+#[cfg(feature = "serde_transcode")]
+pub use transcode::{transcode, transcode_with_config};
+#[cfg(feature = "base64")]
+pub use url::{compress_to_urlsafe, decompress_from_urlsafe};
+pub use visit::{DecodeVisitor, decompress_visit, expanded_node_count};
+pub use wire::compress_to_writer_string;
+
+// Expose the base-62 key alphabet
+pub use number::{ITO_S as KEY_ALPHABET, N as KEY_BASE, key_for_index};
 
 // Expose encoding functions for special values (v3.2.0+)
 pub use encode::{decode_num, decode_special, encode_num, is_special_value};
@@ -0,0 +1,116 @@
+//! Line-oriented text form of compressed data.
+//!
+//! Instead of the JSON array `[[...values...], root]`, the compressed form
+//! is written as plain text with one store entry per line, followed by the
+//! root key on the last line. Each entry sits on its own line, so the
+//! output greps and diffs well.
+//!
+//! # Escaping
+//!
+//! Entries may contain any character, so three are escaped with a
+//! backslash:
+//!
+//! | Character | Written as |
+//! |-----------|------------|
+//! | `\` | `\\` |
+//! | line feed | `\n` |
+//! | carriage return | `\r` |
+//!
+//! A text with `n` entries therefore always has `n + 1` lines, separated by
+//! single line feeds, with no trailing line feed. When reading, a backslash
+//! followed by any other character is kept as it is.
+//!
+//! # Example
+//!
+//! ```rust
+//! use compress_json_rs::{compress_to_lines, decompress_from_lines};
+//! use serde_json::json;
+//!
+//! let data = json!(["first\nsecond", "first\nsecond"]);
+//! let text = compress_to_lines(&data);
+//!
+//! assert_eq!(text, "first\\nsecond\na|0|0\n1");
+//! assert_eq!(decompress_from_lines(&text), data);
+//! ```
+
+use crate::core::{Compressed, compress, decompress};
+use serde_json::Value;
+
+/// Compress a JSON value into the line-oriented text form.
+///
+/// # Arguments
+///
+/// * `o` - The value to compress
+///
+/// # Returns
+///
+/// The escaped store entries and the root key, one per line
+pub fn compress_to_lines(o: &Value) -> String {
+    let c = compress(o);
+    let mut out = String::new();
+    for value in &c.values {
+        push_escaped(&mut out, value);
+        out.push('\n');
+    }
+    out.push_str(c.root.as_str());
+    out
+}
+
+/// Decompress the line-oriented text form produced by [`compress_to_lines`].
+///
+/// # Arguments
+///
+/// * `text` - The escaped store entries and the root key, one per line
+///
+/// # Returns
+///
+/// The decompressed value
+///
+/// # Panics
+///
+/// Panics if the root key references an invalid index or an entry is
+/// malformed, as [`decompress`] does.
+pub fn decompress_from_lines(text: &str) -> Value {
+    let mut lines: Vec<String> = text.split('\n').map(unescape).collect();
+    // `split` always yields at least one line, the root
+    let root = lines.pop().unwrap_or_default();
+    decompress(Compressed::new(lines, root))
+}
+
+/// Append `value` to `out` with the line separators and backslashes escaped.
+fn push_escaped(out: &mut String, value: &str) {
+    for ch in value.chars() {
+        match ch {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            _ => out.push(ch),
+        }
+    }
+}
+
+/// Undo [`push_escaped`] for one line.
+fn unescape(line: &str) -> String {
+    if !line.contains('\\') {
+        return line.to_string();
+    }
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            out.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('\\') => out.push('\\'),
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
+            }
+            None => out.push('\\'),
+        }
+    }
+    out
+}
@@ -19,7 +19,9 @@
 //! 3. If not found, a new key is generated and the value is stored
 //!
 //! This ensures that identical values (like repeated strings or numbers)
-//! are only stored once.
+//! are only stored once. Containers are deduplicated the same way: children
+//! are added first, and since an equal child always gets the same key, equal
+//! arrays and objects encode to the same `a|`/`o|` string and share one entry.
 //!
 //! # Example
 //!
@@ -39,24 +41,24 @@
 //! assert_eq!(values.len(), 1);
 //! ```
 
-use crate::config::CONFIG;
+use crate::config::{CONFIG, Config, NullObjectPolicy};
+use crate::core::{Compressed, is_composite, is_inline_root, is_null_key};
 use crate::debug::throw_unsupported_data;
-use crate::encode::{encode_bool, encode_num, encode_str};
+#[cfg(feature = "arbitrary_precision")]
+use crate::encode::f64_loses_precision;
+use crate::encode::{decode_key, encode_bool, encode_num, encode_str, encode_string, needs_escape};
+use crate::error::DecompressError;
+use crate::instrument::Instrumentation;
+use crate::key::Key;
 use crate::number::int_to_s;
-use serde_json::Value;
-use std::collections::HashMap;
-
-/// Key type for compressed references.
-///
-/// Keys are base-62 encoded strings that reference positions in the values array.
-/// The base-62 encoding uses characters `0-9`, `A-Z`, and `a-z`.
-///
-/// # Examples
-///
-/// - `"0"` - First value (index 0)
-/// - `"A"` - Eleventh value (index 10)
-/// - `"10"` - Sixty-third value (index 62)
-pub type Key = String;
+use crate::packed::pack_ints;
+use crate::safe::object_schemas;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+use std::borrow::{Borrow, Cow};
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Arc;
 
 /// In-memory structure holding store and caches for compression.
 ///
@@ -70,7 +72,12 @@ pub type Key = String;
 /// | `store` | `Vec<String>` | Encoded string values |
 /// | `value_cache` | `HashMap` | Maps values to keys |
 /// | `schema_cache` | `HashMap` | Maps schemas to keys |
+/// | `fingerprints` | `HashMap` | Maps long values' fingerprints to key indices |
+/// | `recency` | `Recency` | Use order of cached values, with [`Config::max_dictionary_len`] |
 /// | `key_count` | `usize` | Key counter |
+/// | `config` | `Config` | Options applied while adding values |
+/// | `instrumentation` | `Option<Arc<dyn Instrumentation>>` | Receiver of compression events |
+/// | `depth` | `usize` | Nesting depth of the value being added |
 ///
 /// # Usage
 ///
@@ -99,11 +106,22 @@ pub struct Memory {
     /// The actual stored values (encoded strings)
     pub(crate) store: Vec<String>,
     /// Cache mapping encoded values to their keys
-    pub(crate) value_cache: HashMap<String, String>,
-    /// Cache mapping object schemas to their keys
-    pub(crate) schema_cache: HashMap<String, String>,
+    pub(crate) value_cache: HashMap<Arc<str>, Key>,
+    /// Cache mapping object schemas (key lists) to their keys
+    pub(crate) schema_cache: HashMap<SchemaKey, Key>,
+    /// Fingerprints of long values, mapped to the index of the first
+    /// entry stored with that fingerprint
+    pub(crate) fingerprints: HashMap<u64, usize>,
+    /// Use order of the cached values, for evicting the least recently used
+    pub(crate) recency: Recency,
     /// Counter for generating sequential keys
     pub(crate) key_count: usize,
+    /// Configuration applied while adding values
+    pub(crate) config: Config,
+    /// Receiver of compression events, set with [`instrument`]
+    pub(crate) instrumentation: Option<Arc<dyn Instrumentation>>,
+    /// Nesting depth of the value being added
+    pub(crate) depth: usize,
 }
 
 /// Convert internal store to values array.
@@ -134,6 +152,30 @@ pub fn mem_to_values(mem: &Memory) -> Vec<String> {
     mem.store.clone()
 }
 
+/// Number of values in a memory's deduplication cache.
+///
+/// This is the part of a memory that stays resident when its store is
+/// written out as it grows; [`Config::max_dictionary_len`] bounds it.
+///
+/// # Arguments
+///
+/// * `mem` - Reference to the Memory instance
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{Config, add_value, dictionary_len, make_memory_with_config};
+/// use serde_json::json;
+///
+/// let config = Config { max_dictionary_len: Some(2), ..Config::default() };
+/// let mut mem = make_memory_with_config(&config);
+/// add_value(&mut mem, &json!(["a", "b", "c"]));
+/// assert_eq!(dictionary_len(&mem), 2);
+/// ```
+pub fn dictionary_len(mem: &Memory) -> usize {
+    mem.value_cache.len()
+}
+
 /// Create a new in-memory Memory instance.
 ///
 /// Initializes an empty `Memory` struct ready to accept values.
@@ -151,28 +193,347 @@ pub fn mem_to_values(mem: &Memory) -> Vec<String> {
 /// // Ready to use with add_value()
 /// ```
 pub fn make_memory() -> Memory {
+    make_memory_with_config(&CONFIG)
+}
+
+/// Create a new Memory instance that compresses with a custom configuration.
+///
+/// # Arguments
+///
+/// * `config` - The configuration applied to every value added
+///
+/// # Returns
+///
+/// A new, empty Memory instance
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{Config, make_memory_with_config};
+///
+/// let config = Config { sort_key: true, ..Config::default() };
+/// let mem = make_memory_with_config(&config);
+/// // Ready to use with add_value()
+/// ```
+pub fn make_memory_with_config(config: &Config) -> Memory {
     Memory {
         store: Vec::new(),
         value_cache: HashMap::new(),
         schema_cache: HashMap::new(),
+        fingerprints: HashMap::new(),
+        recency: Recency::default(),
         key_count: 0,
+        config: *config,
+        instrumentation: None,
+        depth: 0,
     }
 }
 
+/// Report compression events of a memory to `instrumentation`.
+///
+/// Every value added to `mem` from then on reports the entries it stores,
+/// the schemas it reuses and the depth of each node (see
+/// [`Instrumentation`]). Memories are not instrumented by default, and
+/// skip the callbacks entirely.
+///
+/// # Arguments
+///
+/// * `mem` - The memory to instrument
+/// * `instrumentation` - Receiver of the events, replacing any previous one
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{Instrumentation, add_value, instrument, make_memory};
+/// use serde_json::json;
+/// use std::sync::Arc;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// #[derive(Default)]
+/// struct Stored(AtomicUsize);
+///
+/// impl Instrumentation for Stored {
+///     fn on_value_stored(&self, _value: &str) {
+///         self.0.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let stored = Arc::new(Stored::default());
+/// let mut mem = make_memory();
+/// instrument(&mut mem, stored.clone());
+/// add_value(&mut mem, &json!(["a", "b", "a"]));
+/// assert_eq!(stored.0.load(Ordering::Relaxed), 3);
+/// ```
+pub fn instrument(mem: &mut Memory, instrumentation: Arc<dyn Instrumentation>) {
+    mem.instrumentation = Some(instrumentation);
+}
+
+/// Create a Memory whose store starts out as an existing values array.
+///
+/// Every entry is registered in the value cache and every object schema in
+/// the schema cache, so values added afterwards reuse the existing entries
+/// and keep their keys. Used to extend an already compressed store.
+///
+/// Schemas are resolved as fallible decoding does, so a malformed store is
+/// reported instead of panicking.
+pub(crate) fn memory_from_values(
+    values: &[String],
+    config: &Config,
+) -> Result<Memory, DecompressError> {
+    let mut mem = make_memory_with_config(config);
+    for value in values {
+        if !mem.value_cache.contains_key(value.as_str()) {
+            let id = mem.key_count;
+            cache_value(&mut mem, value, id);
+        }
+        mem.key_count += 1;
+    }
+    mem.store = values.to_vec();
+    for (schema, keys) in object_schemas(values)? {
+        mem.schema_cache
+            .entry(SchemaKey(keys))
+            .or_insert_with(|| Key::from(schema));
+    }
+    Ok(mem)
+}
+
+/// Serializable checkpoint of a [`Memory`].
+///
+/// Holds the store and configuration; the caches are rebuilt from the store
+/// by [`restore`]. Created with [`snapshot`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemorySnapshot {
+    /// Encoded values, in key order
+    values: Vec<String>,
+    /// Configuration the memory compresses with
+    config: Config,
+}
+
+/// Capture the state of a memory so it can be restored later.
+///
+/// The memory is append-only, so a snapshot is a copy of its store: keys
+/// handed out before the snapshot stay valid in the restored memory, and
+/// values added afterwards to either memory do not affect the other. This
+/// lets a long-running process persist a shared dictionary and resume from
+/// it, or fork one for a speculative compression and throw the fork away.
+///
+/// # Arguments
+///
+/// * `mem` - Reference to the Memory instance
+///
+/// # Returns
+///
+/// A [`MemorySnapshot`], which can be serialized with serde
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{add_value, make_memory, mem_to_values, restore, snapshot};
+/// use serde_json::json;
+///
+/// let mut mem = make_memory();
+/// let key = add_value(&mut mem, &json!({"status": "active"}));
+/// let saved = serde_json::to_string(&snapshot(&mem)).unwrap();
+///
+/// add_value(&mut mem, &json!("speculative"));
+///
+/// let mut resumed = restore(serde_json::from_str(&saved).unwrap()).unwrap();
+/// assert_eq!(mem_to_values(&resumed).len(), 4);
+/// assert_eq!(add_value(&mut resumed, &json!({"status": "active"})), key);
+/// ```
+pub fn snapshot(mem: &Memory) -> MemorySnapshot {
+    MemorySnapshot {
+        values: mem.store.clone(),
+        config: mem.config,
+    }
+}
+
+/// Rebuild a memory from a [`snapshot`].
+///
+/// The value and schema caches are reconstructed from the stored entries,
+/// so values added to the restored memory are deduplicated against
+/// everything stored before the snapshot.
+///
+/// # Arguments
+///
+/// * `snapshot` - A snapshot taken with [`snapshot`]
+///
+/// # Returns
+///
+/// A Memory holding the snapshot's store and configuration, or the
+/// [`DecompressError`] [`try_decompress`](crate::try_decompress) would
+/// report for a malformed object schema in a corrupted or untrusted
+/// snapshot
+pub fn restore(snapshot: MemorySnapshot) -> Result<Memory, DecompressError> {
+    memory_from_values(&snapshot.values, &snapshot.config)
+}
+
 /// Get or insert a value in the store, returning its key.
 ///
 /// This is the core deduplication function. It checks if the encoded value
 /// already exists in the cache, returning the existing key if so. Otherwise,
-/// it generates a new key, stores the value, and caches the mapping.
-fn get_value_key(mem: &mut Memory, value: &str) -> String {
-    if let Some(key) = mem.value_cache.get(value) {
-        return key.clone();
+/// it generates a new key, stores the value, and caches the mapping. With
+/// [`Config::dedup`] off, the cache is bypassed and every call stores a new
+/// entry.
+pub(crate) fn get_value_key(mem: &mut Memory, value: &str) -> Key {
+    if mem.config.dedup
+        && let Some(key) = cached_key(mem, value)
+    {
+        return key;
+    }
+    insert_value(mem, value.to_string())
+}
+
+/// Get or insert an owned value in the store, returning its key.
+///
+/// Same as [`get_value_key`], but takes ownership of the encoded value so
+/// that a cache miss does not need to copy it again.
+pub(crate) fn get_value_key_owned(mem: &mut Memory, value: String) -> Key {
+    if mem.config.dedup
+        && let Some(key) = cached_key(mem, &value)
+    {
+        return key;
+    }
+    insert_value(mem, value)
+}
+
+/// Find the key of a value already in the value cache.
+///
+/// Long values are first matched by [`fingerprint`] against the entry
+/// stored with the same fingerprint, so a string repeated thousands of
+/// times is compared, not hashed in full, on each occurrence. A different
+/// value with the same fingerprint, or an entry no longer in the store
+/// (drained by a writer), falls back to the full lookup.
+fn cached_key(mem: &mut Memory, value: &str) -> Option<Key> {
+    let mut found = None;
+    if value.len() >= FINGERPRINT_MIN_LEN
+        && let Some(&id) = mem.fingerprints.get(&fingerprint(value))
+    {
+        // Entries before `base` have been drained from the store
+        let base = mem.key_count - mem.store.len();
+        if id >= base && mem.store[id - base] == value {
+            found = Some(Key::from(int_to_s(id)));
+        }
+    }
+    let key = found.or_else(|| mem.value_cache.get(value).cloned())?;
+    if mem.config.max_dictionary_len.is_some() {
+        mem.recency.touch(decode_key(key.as_str()));
     }
+    Some(key)
+}
+
+/// Use order of the values in a memory's value cache.
+///
+/// Only kept when [`Config::max_dictionary_len`] bounds the cache. Values
+/// are tracked by key index; the map from last use to value lets the least
+/// recently used value be found and removed from the cache. It shares the
+/// cache's allocation of each value rather than holding a copy.
+#[derive(Default)]
+pub(crate) struct Recency {
+    /// Use counter, advanced on every insert and cache hit
+    clock: u64,
+    /// Last use of each cached value, by key index
+    last_used: HashMap<usize, u64>,
+    /// Cached values by last use, least recent first
+    by_use: BTreeMap<u64, Arc<str>>,
+}
+
+impl Recency {
+    /// Record a use of the cached value with key index `id`.
+    fn touch(&mut self, id: usize) {
+        let Some(last) = self.last_used.get_mut(&id) else {
+            return;
+        };
+        self.clock += 1;
+        if let Some(value) = self.by_use.remove(last) {
+            self.by_use.insert(self.clock, value);
+        }
+        *last = self.clock;
+    }
+
+    /// Start tracking a newly cached value.
+    fn insert(&mut self, id: usize, value: Arc<str>) {
+        self.clock += 1;
+        self.last_used.insert(id, self.clock);
+        self.by_use.insert(self.clock, value);
+    }
+
+    /// Stop tracking the least recently used value and return it.
+    fn pop_oldest(&mut self) -> Option<Arc<str>> {
+        let (_, value) = self.by_use.pop_first()?;
+        Some(value)
+    }
+}
+
+/// Add a stored value to the value cache under key index `id`.
+///
+/// With [`Config::max_dictionary_len`], the least recently used values are
+/// then evicted until the cache is within the limit. Evicted entries stay
+/// in the store, so keys already handed out remain valid; the value is just
+/// stored again if it is seen again.
+fn cache_value(mem: &mut Memory, value: &str, id: usize) {
+    if value.len() >= FINGERPRINT_MIN_LEN {
+        mem.fingerprints.entry(fingerprint(value)).or_insert(id);
+    }
+    let value = Arc::<str>::from(value);
+    let Some(limit) = mem.config.max_dictionary_len else {
+        mem.value_cache.insert(value, Key::from(int_to_s(id)));
+        return;
+    };
+    mem.recency.insert(id, Arc::clone(&value));
+    mem.value_cache.insert(value, Key::from(int_to_s(id)));
+    while mem.value_cache.len() > limit {
+        let Some(oldest) = mem.recency.pop_oldest() else {
+            break;
+        };
+        let Some(key) = mem.value_cache.remove(&oldest) else {
+            continue;
+        };
+        let id = decode_key(key.as_str());
+        mem.recency.last_used.remove(&id);
+        if oldest.len() >= FINGERPRINT_MIN_LEN {
+            let print = fingerprint(&oldest);
+            if mem.fingerprints.get(&print) == Some(&id) {
+                mem.fingerprints.remove(&print);
+            }
+        }
+    }
+}
+
+/// Values at least this long are looked up by fingerprint first.
+const FINGERPRINT_MIN_LEN: usize = 256;
+
+/// Length of each window of bytes a fingerprint samples.
+const FINGERPRINT_WINDOW: usize = 32;
+
+/// Cheap hash of a long value: its length and four windows of its bytes
+/// (the start, the end and two in between).
+///
+/// Values that differ only outside the windows share a fingerprint, so a
+/// match must still be confirmed by comparing the values.
+fn fingerprint(value: &str) -> u64 {
+    let bytes = value.as_bytes();
+    let mut hasher = DefaultHasher::new();
+    hasher.write_usize(bytes.len());
+    let last = bytes.len() - FINGERPRINT_WINDOW;
+    for at in [0, last / 3, last / 3 * 2, last] {
+        hasher.write(&bytes[at..at + FINGERPRINT_WINDOW]);
+    }
+    hasher.finish()
+}
+
+/// Store a new value under the next sequential key and cache it.
+fn insert_value(mem: &mut Memory, value: String) -> Key {
     let id = mem.key_count;
-    let key = int_to_s(id);
+    let key = Key::from(int_to_s(id));
     mem.key_count += 1;
-    mem.store.push(value.to_string());
-    mem.value_cache.insert(value.to_string(), key.clone());
+    if mem.config.dedup {
+        cache_value(mem, &value, id);
+    }
+    if let Some(instrumentation) = &mem.instrumentation {
+        instrumentation.on_value_stored(&value);
+    }
+    mem.store.push(value);
     key
 }
 
@@ -180,27 +541,357 @@ fn get_value_key(mem: &mut Memory, value: &str) -> String {
 ///
 /// Schemas are stored as arrays of key strings. Objects with identical
 /// keys share the same schema, reducing storage for arrays of similar objects.
-fn get_schema(mem: &mut Memory, keys: &[String]) -> String {
-    let mut schema_keys = keys.to_vec();
-    if CONFIG.sort_key {
-        schema_keys.sort();
+/// The key array also shares the value cache: a data array equal to a key
+/// list (e.g. `["id", "name"]`) and the schema of `{"id": .., "name": ..}`
+/// are a single store entry.
+///
+/// The cache is keyed by the key list itself rather than a joined string, so
+/// keys containing separators (e.g. `{"a,b": 1}` vs `{"a": 1, "b": 2}`)
+/// never collide.
+///
+/// With [`Config::share_schemas`] off, the cache is bypassed: a single key
+/// is referenced as its string entry and longer key lists get a new entry
+/// per object.
+pub(crate) fn get_schema<K: AsRef<str>>(mem: &mut Memory, keys: &[K]) -> Key {
+    for key in keys {
+        check_value_len(mem, key.as_ref());
+    }
+    if mem.config.sort_key && !keys.is_sorted_by(|a, b| a.as_ref() <= b.as_ref()) {
+        let mut sorted: Vec<&str> = keys.iter().map(AsRef::as_ref).collect();
+        sorted.sort_unstable();
+        return sorted_schema(mem, &sorted);
     }
-    let schema = schema_keys.join(",");
-    if let Some(key) = mem.schema_cache.get(&schema) {
+    sorted_schema(mem, keys)
+}
+
+/// Get or insert a schema whose keys are already in their final order.
+///
+/// The cache is searched with the borrowed keys; they are only copied when
+/// the schema is new.
+fn sorted_schema<K: AsRef<str>>(mem: &mut Memory, keys: &[K]) -> Key {
+    if !mem.config.share_schemas {
+        if let [key] = keys {
+            return get_value_key(mem, &encode_str(key.as_ref()));
+        }
+        let list = key_list(mem, keys);
+        return insert_value(mem, list);
+    }
+    if let Some(key) = mem.schema_cache.get(&Keys(keys) as &dyn KeyList) {
+        if let Some(instrumentation) = &mem.instrumentation {
+            instrumentation.on_schema_reused(key.as_str());
+        }
         return key.clone();
     }
-    // Represent schema as an array of strings
-    let arr = Value::Array(
-        schema_keys
-            .iter()
-            .map(|k| Value::String(k.clone()))
-            .collect(),
-    );
-    let key_id = add_value(mem, &arr);
-    mem.schema_cache.insert(schema, key_id.clone());
+    let list = match extended_schema(mem, keys) {
+        Some(list) => list,
+        None => key_list(mem, keys),
+    };
+    let key_id = get_value_key_owned(mem, list);
+    let owned = keys.iter().map(|k| k.as_ref().to_string()).collect();
+    mem.schema_cache.insert(SchemaKey(owned), key_id.clone());
     key_id
 }
 
+/// Build a schema entry that extends the longest stored schema it starts
+/// with, if [`Config::extend_schemas`] is set and one of at least two keys
+/// exists.
+///
+/// The entry references the base schema first, then the extra keys:
+/// `a|<base>|<key>...`.
+fn extended_schema<K: AsRef<str>>(mem: &mut Memory, keys: &[K]) -> Option<String> {
+    if !mem.config.extend_schemas {
+        return None;
+    }
+    let (len, base) = (2..keys.len()).rev().find_map(|len| {
+        let prefix = Keys(&keys[..len]);
+        Some((len, mem.schema_cache.get(&prefix as &dyn KeyList)?.clone()))
+    })?;
+    let mut acc = key_list(mem, &keys[len..]);
+    acc.insert_str(1, &format!("|{base}"));
+    Some(acc)
+}
+
+/// Store each key as a plain string and build the `a|` entry listing them.
+///
+/// Keys bypass [`add_value`]'s string options, so `numeric_strings` or
+/// `chunk_strings` never turn a key into something other than a string.
+fn key_list<K: AsRef<str>>(mem: &mut Memory, keys: &[K]) -> String {
+    let mut acc = String::from("a");
+    for key in keys {
+        let key_id = get_value_key(mem, &encode_str(key.as_ref()));
+        acc.push('|');
+        acc.push_str(key_id.as_str());
+    }
+    acc
+}
+
+/// An object's key list, hashed and compared key by key.
+///
+/// The schema cache owns its keys as [`SchemaKey`]s but is searched through
+/// this trait, so a lookup can use borrowed keys ([`Keys`]) without copying
+/// them.
+pub(crate) trait KeyList {
+    /// Number of keys
+    fn key_count(&self) -> usize;
+    /// The key at position `i`
+    fn key(&self, i: usize) -> &str;
+}
+
+impl Hash for dyn KeyList + '_ {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.key_count());
+        for i in 0..self.key_count() {
+            self.key(i).hash(state);
+        }
+    }
+}
+
+impl PartialEq for dyn KeyList + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        self.key_count() == other.key_count()
+            && (0..self.key_count()).all(|i| self.key(i) == other.key(i))
+    }
+}
+
+impl Eq for dyn KeyList + '_ {}
+
+/// Borrowed keys, for looking up the schema cache.
+struct Keys<'a, K>(&'a [K]);
+
+impl<K: AsRef<str>> KeyList for Keys<'_, K> {
+    fn key_count(&self) -> usize {
+        self.0.len()
+    }
+
+    fn key(&self, i: usize) -> &str {
+        self.0[i].as_ref()
+    }
+}
+
+/// Owned key list stored in the schema cache.
+#[derive(Debug, Clone)]
+pub(crate) struct SchemaKey(pub(crate) Vec<String>);
+
+impl KeyList for SchemaKey {
+    fn key_count(&self) -> usize {
+        self.0.len()
+    }
+
+    fn key(&self, i: usize) -> &str {
+        &self.0[i]
+    }
+}
+
+impl Hash for SchemaKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self as &dyn KeyList).hash(state);
+    }
+}
+
+impl PartialEq for SchemaKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for SchemaKey {}
+
+impl<'a> Borrow<dyn KeyList + 'a> for SchemaKey {
+    fn borrow(&self) -> &(dyn KeyList + 'a) {
+        self
+    }
+}
+
+/// Check whether a string is a canonical integer that decodes back exactly.
+///
+/// Accepts an optional leading `-` followed by digits without leading zeros,
+/// and only when the value fits in an `i64` or `u64`.
+fn is_integer_string(s: &str) -> bool {
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return false;
+    }
+    if digits.len() > 1 && digits.starts_with('0') {
+        return false;
+    }
+    if s == "-0" {
+        return false;
+    }
+    s.parse::<i64>().is_ok() || s.parse::<u64>().is_ok()
+}
+
+/// Store a long string as a `c|` list of chunks of at most `chunk_len` bytes.
+///
+/// Chunks end on character boundaries; a chunk is only longer than
+/// `chunk_len` if a single character is.
+fn add_chunked_str(mem: &mut Memory, s: &str, chunk_len: usize) -> Key {
+    let mut acc = String::from("c");
+    let mut start = 0;
+    while start < s.len() {
+        let mut end = (start + chunk_len.max(1)).min(s.len());
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        if end == start {
+            end = start + s[start..].chars().next().map_or(1, char::len_utf8);
+        }
+        let key = get_value_key(mem, &encode_str(&s[start..end]));
+        acc.push('|');
+        acc.push_str(key.as_str());
+        start = end;
+    }
+    get_value_key_owned(mem, acc)
+}
+
+/// Add an integer to memory, returning its key.
+///
+/// The integer is stored through `f64` (rejected first if it would round and
+/// [`Config::error_on_precision_loss`] is set) and written as the exact
+/// integer that `f64` holds, without a fraction, so it decodes as that
+/// integer `Number`. Shortest float digits would not do: `2^63` prints as
+/// `9223372036854776000`, a different integer.
+pub(crate) fn add_int(mem: &mut Memory, i: i128) -> Key {
+    check_integer_precision(mem, i);
+    get_value_key_owned(mem, format!("n|{}", stored_int(i)))
+}
+
+/// The integer [`add_int`] stores for `i`.
+///
+/// This is the `f64` rounding of `i`, except that integers near `u64::MAX`
+/// round up to `2^64`, which would decode as a float; they are kept at
+/// `u64::MAX` instead.
+pub(crate) fn stored_int(i: i128) -> i128 {
+    ((i as f64) as i128).min(i128::from(u64::MAX))
+}
+
+/// Add an array of integers to memory as one packed `d|` entry, returning
+/// its key.
+///
+/// Returns `None`, storing nothing, unless [`Config::pack_int_arrays`] is
+/// set and every element is an integer (`Some` in `ints`). Elements are kept
+/// exactly, without the `f64` rounding of [`add_int`].
+pub(crate) fn add_packed_ints<I: IntoIterator<Item = Option<i128>>>(
+    mem: &mut Memory,
+    ints: I,
+) -> Option<Key> {
+    if !mem.config.pack_int_arrays {
+        return None;
+    }
+    pack_ints(ints).map(|packed| get_value_key_owned(mem, packed))
+}
+
+/// Whether `arr` is stored as a packed `d|` entry under `config`.
+pub(crate) fn packs_ints<'a, N: JsonNode<'a>>(config: &Config, arr: &N::Array) -> bool {
+    let mut ints = N::array_ints(arr).peekable();
+    config.pack_int_arrays && ints.peek().is_some() && ints.all(|i| i.is_some())
+}
+
+/// The integer a JSON value holds, if it is an integer `Number`.
+fn json_int(v: &Value) -> Option<i128> {
+    v.as_i64().map(i128::from).or(v.as_u64().map(i128::from))
+}
+
+/// Add a floating-point number to memory, returning its key.
+///
+/// NaN and infinities are stored, rejected or turned into `null` according
+/// to the memory's configuration, as documented on [`add_value`]. Integral
+/// values get a `.0` fraction (`n|1.0`), so they decode as a float `Number`
+/// rather than an integer.
+pub(crate) fn add_f64(mem: &mut Memory, f: f64) -> Key {
+    // Handle NaN (v3.4.0 logic)
+    if f.is_nan() {
+        if mem.config.preserve_nan {
+            return get_value_key(mem, "N|0");
+        }
+        if mem.config.error_on_nan {
+            throw_unsupported_data("[number NaN]");
+        }
+        // Convert to null like JSON.stringify
+        return Key::default();
+    }
+
+    // Handle Infinity (v3.4.0 logic)
+    if f.is_infinite() {
+        if mem.config.preserve_infinite {
+            if f.is_sign_positive() {
+                return get_value_key(mem, "N|+");
+            } else {
+                return get_value_key(mem, "N|-");
+            }
+        }
+        if mem.config.error_on_infinite {
+            if f.is_sign_positive() {
+                throw_unsupported_data("[number Infinity]");
+            } else {
+                throw_unsupported_data("[number -Infinity]");
+            }
+        }
+        // Convert to null like JSON.stringify
+        return Key::default();
+    }
+
+    // Regular number, marked as a float if it has no fraction digits
+    let mut encoded = encode_num(f);
+    if !encoded.contains('.') {
+        encoded.push_str(".0");
+    }
+    get_value_key_owned(mem, encoded)
+}
+
+/// Length of the entry a string is stored as, if it exceeds
+/// [`Config::max_value_len`].
+///
+/// Returns the encoded length (the string plus any `s|` escape) and the
+/// limit, or `None` if the string fits or no limit is set.
+pub(crate) fn exceeds_max_value_len(config: &Config, s: &str) -> Option<(usize, usize)> {
+    let limit = config.max_value_len?;
+    let len = s.len() + if needs_escape(s) { 2 } else { 0 };
+    (len > limit).then_some((len, limit))
+}
+
+/// Reject a string longer than [`Config::max_value_len`], if so configured.
+fn check_value_len(mem: &Memory, s: &str) {
+    if let Some((len, limit)) = exceeds_max_value_len(&mem.config, s) {
+        throw_unsupported_data(&format!(
+            "[string of {len} bytes exceeds max_value_len {limit}]"
+        ));
+    }
+}
+
+/// Reject an integer that `f64` cannot hold exactly, if so configured.
+///
+/// Used with [`Config::error_on_precision_loss`] before an integer is
+/// stored through `f64`.
+pub(crate) fn check_integer_precision(mem: &Memory, i: i128) {
+    if mem.config.error_on_precision_loss && stored_int(i) != i {
+        throw_unsupported_data(&format!("[integer {i} beyond f64 precision]"));
+    }
+}
+
+/// Add a string to memory, returning its key.
+///
+/// An owned string is moved into the store when it becomes a new entry as
+/// it is; a borrowed one is only copied then.
+pub(crate) fn add_str<'s>(mem: &mut Memory, s: impl Into<Cow<'s, str>>) -> Key {
+    let s = s.into();
+    if s.is_empty() && mem.config.treat_empty_string_as_null {
+        return Key::default();
+    }
+    check_value_len(mem, &s);
+    if mem.config.numeric_strings && is_integer_string(&s) {
+        return get_value_key(mem, &format!("n|{s}"));
+    }
+    if let Some(chunk_len) = mem.config.chunk_strings
+        && s.len() > chunk_len
+    {
+        return add_chunked_str(mem, &s, chunk_len);
+    }
+    match s {
+        Cow::Borrowed(s) => get_value_key(mem, &encode_str(s)),
+        Cow::Owned(s) => get_value_key_owned(mem, encode_string(s)),
+    }
+}
+
 /// Recursively add a JSON value to memory, returning its key.
 ///
 /// This function handles all JSON value types and recursively processes
@@ -224,6 +915,8 @@ fn get_schema(mem: &mut Memory, keys: &[String]) -> String {
 /// | Bool | `b\|T` or `b\|F` | `"b\|T"` |
 /// | Number | `n\|<value>` | `"n\|42.5"` |
 /// | String | Plain or `s\|<escaped>` | `"hello"` or `"s\|n\|123"` |
+/// | Numeric string | `n\|<digits>` (with `numeric_strings`) | `"n\|123"` |
+/// | Long string | `c\|<refs>` (with `chunk_strings`) | `"c\|0\|1"` |
 /// | Array | `a\|<refs>` | `"a\|0\|1\|2"` |
 /// | Object | `o\|<schema>\|<refs>` | `"o\|0\|1\|2"` |
 ///
@@ -257,88 +950,391 @@ fn get_schema(mem: &mut Memory, keys: &[String]) -> String {
 /// | Infinity | Encoded as `N\|+` | Panic | Returns `""` (null) |
 /// | -Infinity | Encoded as `N\|-` | Panic | Returns `""` (null) |
 ///
+/// Integers beyond `2^53` that `f64` would round panic when
+/// [`Config::error_on_precision_loss`] is set.
+///
+/// - **Number kind**: Integers are stored as `n|1` and floats as `n|1.0`
+///   (a `.0` is added to integral floats), so `1` and `1.0` decode with
+///   their original `is_i64` / `is_f64` kind
+///
 /// - **Null in arrays**: Encoded as `_` to distinguish from empty references
 pub fn add_value(mem: &mut Memory, o: &Value) -> Key {
-    match o {
-        Value::Null => "".to_string(),
-        Value::Bool(b) => get_value_key(mem, &encode_bool(*b)),
-        Value::Number(n) => {
-            // Convert number to f64
-            let f = n.as_f64().unwrap_or_else(|| {
-                // integer fallback
-                n.as_i64()
-                    .map(|i| i as f64)
-                    .or_else(|| n.as_u64().map(|u| u as f64))
-                    .unwrap_or(0.0)
-            });
-
-            // Handle NaN (v3.4.0 logic)
-            if f.is_nan() {
-                if CONFIG.preserve_nan {
-                    return get_value_key(mem, "N|0");
-                }
-                if CONFIG.error_on_nan {
-                    throw_unsupported_data("[number NaN]");
-                }
-                // Convert to null like JSON.stringify
-                return "".to_string();
-            }
+    add_value_visited(mem, o, &mut |_, _| {})
+}
 
-            // Handle Infinity (v3.4.0 logic)
-            if f.is_infinite() {
-                if CONFIG.preserve_infinite {
-                    if f.is_sign_positive() {
-                        return get_value_key(mem, "N|+");
-                    } else {
-                        return get_value_key(mem, "N|-");
-                    }
-                }
-                if CONFIG.error_on_infinite {
-                    if f.is_sign_positive() {
-                        throw_unsupported_data("[number Infinity]");
-                    } else {
-                        throw_unsupported_data("[number -Infinity]");
-                    }
-                }
-                // Convert to null like JSON.stringify
-                return "".to_string();
-            }
+/// Add an already compressed value to memory, returning its key.
+///
+/// The entries reachable from `c.root` are copied into the memory as they
+/// are encoded, with their references remapped to the memory's keys; no
+/// value is decoded and re-encoded. Entries equal to ones already stored
+/// are shared as usual. The returned key can be placed in a larger document
+/// by reference, as [`splice_path`](crate::splice_path) does, to build a
+/// composite document from pre-compressed parts.
+///
+/// The copied entries keep the encoding they were compressed with; the
+/// memory's [`Config`] is not applied to them.
+///
+/// # Arguments
+///
+/// * `mem` - The memory to add the entries to
+/// * `c` - The compressed value to copy
+///
+/// # Returns
+///
+/// The key of the copied root in `mem`
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{add_compressed, add_value, compress, decode, make_memory, mem_to_values};
+/// use serde_json::json;
+///
+/// let part = compress(&json!({"name": "Alice", "tags": ["a", "b"]}));
+///
+/// let mut mem = make_memory();
+/// add_value(&mut mem, &json!(["b", "c"]));
+/// let key = add_compressed(&mut mem, &part);
+///
+/// let values = mem_to_values(&mem);
+/// assert_eq!(decode(&values, &key), json!({"name": "Alice", "tags": ["a", "b"]}));
+/// ```
+///
+/// # Panics
+///
+/// Panics if `c` references an invalid index.
+pub fn add_compressed(mem: &mut Memory, c: &Compressed) -> Key {
+    let root = c.root.as_str();
+    if is_null_key(root) {
+        return Key::default();
+    }
+    if is_inline_root(&c.values, root) {
+        return get_value_key(mem, root);
+    }
+    let mut remapped = vec![None; c.values.len()];
+    copy_entry(mem, &c.values, decode_key(root), &mut remapped)
+}
 
-            // Regular number
-            get_value_key(mem, &encode_num(f))
-        }
-        Value::String(s) => get_value_key(mem, &encode_str(s)),
-        Value::Array(arr) => {
-            let mut acc = String::from("a");
-            for v in arr.iter() {
-                let key = if v.is_null() {
-                    "_".to_string()
-                } else {
-                    add_value(mem, v)
-                };
+/// Copy the entry at `index` of `values` and the entries it references into
+/// memory, memoizing the new keys in `remapped`.
+fn copy_entry(
+    mem: &mut Memory,
+    values: &[String],
+    index: usize,
+    remapped: &mut [Option<Key>],
+) -> Key {
+    if let Some(key) = &remapped[index] {
+        return key.clone();
+    }
+    let entry = values[index].as_str();
+    let key = if is_composite(entry) {
+        let (prefix, refs) = entry.split_at(2);
+        let mut acc = String::from(prefix);
+        // An empty container has no refs, not one empty ref
+        for (i, r) in refs.split('|').filter(|_| !refs.is_empty()).enumerate() {
+            if i > 0 {
                 acc.push('|');
-                acc.push_str(&key);
             }
-            if acc == "a" {
-                acc = "a|".to_string();
+            if is_null_key(r) {
+                acc.push_str(r);
+            } else {
+                let child = copy_entry(mem, values, decode_key(r), remapped);
+                acc.push_str(child.as_str());
             }
-            get_value_key(mem, &acc)
         }
-        Value::Object(map) => {
-            let keys: Vec<String> = map.keys().cloned().collect();
-            if keys.is_empty() {
-                return get_value_key(mem, "o|");
-            }
-            let key_id = get_schema(mem, &keys);
-            let mut acc = String::from("o|");
-            acc.push_str(&key_id);
-            for key in keys.iter() {
-                let v = &map[key];
-                let val_key = add_value(mem, v);
-                acc.push('|');
-                acc.push_str(&val_key);
+        get_value_key_owned(mem, acc)
+    } else {
+        get_value_key(mem, entry)
+    };
+    remapped[index] = Some(key.clone());
+    key
+}
+
+/// Add a value to memory like [`add_value`], calling `visit` with each
+/// non-null node and its key once the node is stored.
+///
+/// Children are visited before their parent, objects in map order. `null`
+/// array elements and fields are not visited.
+pub(crate) fn add_value_visited<F: FnMut(&Value, &Key)>(
+    mem: &mut Memory,
+    o: &Value,
+    visit: &mut F,
+) -> Key {
+    add_child(mem, o, visit)
+}
+
+/// Recursively add an owned JSON value to memory, returning its key.
+///
+/// Produces exactly the same keys and store as [`add_value`], but moves
+/// strings out of the input instead of cloning them.
+pub(crate) fn add_value_owned(mem: &mut Memory, o: Value) -> Key {
+    add_child(mem, o, &mut |(), _| {})
+}
+
+/// A JSON value as [`add_child`] stores it.
+///
+/// Borrowed and owned `serde_json` values go through the same walk, so they
+/// follow the configuration alike; only how a value is taken apart differs.
+pub(crate) trait JsonNode<'a>: Sized {
+    /// Elements of an array
+    type Array: IntoIterator<Item = Self>;
+    /// Fields of an object, with their names
+    type Object: IntoIterator<Item = (Self::Name, Self)>;
+    /// Name of an object field
+    type Name: AsRef<str>;
+    /// What the visitor of [`add_child`] is passed for the value
+    type Handle: Copy;
+
+    /// Split the value into its kind and contents.
+    fn into_kind(self) -> NodeKind<'a, Self>;
+
+    /// The value as passed to the visitor of [`add_child`].
+    fn handle(&self) -> Self::Handle;
+
+    /// Whether the value is `null`.
+    fn is_null(&self) -> bool;
+
+    /// The value's contents, if it is a string.
+    fn as_str(&self) -> Option<&str>;
+
+    /// The integers of an array, `None` for each element that is not one.
+    fn array_ints(array: &Self::Array) -> impl Iterator<Item = Option<i128>>;
+
+    /// Whether the value is stored as `null` under `config`: `null` itself,
+    /// or an empty string with [`Config::treat_empty_string_as_null`].
+    fn stores_null(&self, config: &Config) -> bool {
+        self.is_null() || (config.treat_empty_string_as_null && self.as_str() == Some(""))
+    }
+}
+
+/// The kind of a [`JsonNode`], with its contents.
+pub(crate) enum NodeKind<'a, N: JsonNode<'a>> {
+    Null,
+    Bool(bool),
+    Int(i128),
+    Float(f64),
+    /// A number stored as its own text: one `f64` cannot hold, or whose
+    /// digits it would lose
+    Text(String),
+    Str(Cow<'a, str>),
+    Array(N::Array),
+    Object(N::Object),
+}
+
+/// The kind of a `serde_json` number.
+fn number_kind<'a, N: JsonNode<'a>>(n: &Number) -> NodeKind<'a, N> {
+    // Keep digits that would be lost converting through f64
+    #[cfg(feature = "arbitrary_precision")]
+    if f64_loses_precision(n.as_str()) {
+        return NodeKind::Text(n.to_string());
+    }
+    // Integers and floats keep their kind through decoding
+    if let Some(i) = n.as_i64().map(i128::from).or(n.as_u64().map(i128::from)) {
+        return NodeKind::Int(i);
+    }
+    match n.as_f64() {
+        Some(f) => NodeKind::Float(f),
+        // Not a finite f64: keep the number's own text rather than
+        // substituting a value for it
+        None => NodeKind::Text(n.to_string()),
+    }
+}
+
+impl<'a> JsonNode<'a> for &'a Value {
+    type Array = &'a Vec<Value>;
+    type Object = &'a Map<String, Value>;
+    type Name = &'a String;
+    type Handle = &'a Value;
+
+    fn into_kind(self) -> NodeKind<'a, Self> {
+        match self {
+            Value::Null => NodeKind::Null,
+            Value::Bool(b) => NodeKind::Bool(*b),
+            Value::Number(n) => number_kind(n),
+            Value::String(s) => NodeKind::Str(Cow::Borrowed(s)),
+            Value::Array(arr) => NodeKind::Array(arr),
+            Value::Object(map) => NodeKind::Object(map),
+        }
+    }
+
+    fn handle(&self) -> &'a Value {
+        self
+    }
+
+    fn is_null(&self) -> bool {
+        Value::is_null(self)
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        Value::as_str(self)
+    }
+
+    fn array_ints(array: &&'a Vec<Value>) -> impl Iterator<Item = Option<i128>> {
+        array.iter().map(json_int)
+    }
+}
+
+impl JsonNode<'static> for Value {
+    type Array = Vec<Value>;
+    type Object = Map<String, Value>;
+    type Name = String;
+    type Handle = ();
+
+    fn into_kind(self) -> NodeKind<'static, Self> {
+        match self {
+            Value::String(s) => NodeKind::Str(Cow::Owned(s)),
+            Value::Array(arr) => NodeKind::Array(arr),
+            Value::Object(map) => NodeKind::Object(map),
+            Value::Null => NodeKind::Null,
+            Value::Bool(b) => NodeKind::Bool(b),
+            Value::Number(n) => number_kind(&n),
+        }
+    }
+
+    fn handle(&self) {}
+
+    fn is_null(&self) -> bool {
+        Value::is_null(self)
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        Value::as_str(self)
+    }
+
+    fn array_ints(array: &Vec<Value>) -> impl Iterator<Item = Option<i128>> {
+        array.iter().map(json_int)
+    }
+}
+
+/// Add a value one level below the node being added, calling `visit` with
+/// it and its key once it is stored, unless it is stored as `null`.
+pub(crate) fn add_child<'a, N: JsonNode<'a>, F: FnMut(N::Handle, &Key)>(
+    mem: &mut Memory,
+    o: N,
+    visit: &mut F,
+) -> Key {
+    let handle = o.handle();
+    let key = nested(mem, |mem| add_node(mem, o, visit));
+    if !key.is_null() {
+        visit(handle, &key);
+    }
+    key
+}
+
+/// Store one node for [`add_child`], visiting its children.
+fn add_node<'a, N: JsonNode<'a>, F: FnMut(N::Handle, &Key)>(
+    mem: &mut Memory,
+    o: N,
+    visit: &mut F,
+) -> Key {
+    match o.into_kind() {
+        NodeKind::Null => Key::default(),
+        NodeKind::Bool(b) => get_value_key(mem, &encode_bool(b)),
+        NodeKind::Int(i) => add_int(mem, i),
+        NodeKind::Float(f) => add_f64(mem, f),
+        NodeKind::Text(text) => get_value_key_owned(mem, format!("n|{text}")),
+        NodeKind::Str(s) => add_str(mem, s),
+        NodeKind::Array(arr) => {
+            if let Some(key) = add_packed_ints(mem, N::array_ints(&arr)) {
+                return key;
             }
-            get_value_key(mem, &acc)
+            add_array(mem, arr, N::stores_null, |mem, v| add_child(mem, v, visit))
         }
+        NodeKind::Object(map) => {
+            add_object(mem, map, N::stores_null, |mem, v| add_child(mem, v, visit))
+        }
+    }
+}
+
+/// Store an array entry, adding each element with `add` unless
+/// `stores_null` says it is stored as `null` (written as `_`).
+pub(crate) fn add_array<T>(
+    mem: &mut Memory,
+    elements: impl IntoIterator<Item = T>,
+    stores_null: impl Fn(&T, &Config) -> bool,
+    mut add: impl FnMut(&mut Memory, T) -> Key,
+) -> Key {
+    let mut acc = String::from("a");
+    for v in elements {
+        let key = if stores_null(&v, &mem.config) {
+            Key::from("_")
+        } else {
+            add(mem, v)
+        };
+        acc.push('|');
+        acc.push_str(key.as_str());
+    }
+    if acc.len() == 1 {
+        acc.push('|');
+    }
+    get_value_key_owned(mem, acc)
+}
+
+/// Store an object entry from its `(name, child)` fields, adding each kept
+/// child with `add` once the schema is stored.
+///
+/// Fields are filtered by [`kept_fields`] and, with [`Config::sort_key`],
+/// put in key order, so every kind of input lays out its objects alike.
+pub(crate) fn add_object<K: AsRef<str>, T>(
+    mem: &mut Memory,
+    fields: impl IntoIterator<Item = (K, T)>,
+    stores_null: impl Fn(&T, &Config) -> bool,
+    mut add: impl FnMut(&mut Memory, T) -> Key,
+) -> Key {
+    let mut fields: Vec<(K, T)> = kept_fields(&mem.config, fields, stores_null).collect();
+    if fields.is_empty() {
+        return get_value_key(mem, "o|");
+    }
+    if mem.config.sort_key && !fields.is_sorted_by(|a, b| a.0.as_ref() <= b.0.as_ref()) {
+        fields.sort_by(|a, b| a.0.as_ref().cmp(b.0.as_ref()));
+    }
+    // Borrowed, so a cached schema costs no key copies
+    let names: Vec<&str> = fields.iter().map(|(k, _)| k.as_ref()).collect();
+    let key_id = get_schema(mem, &names);
+    let mut acc = String::from("o|");
+    acc.push_str(key_id.as_str());
+    for (_, v) in fields {
+        let val_key = add(mem, v);
+        acc.push('|');
+        acc.push_str(field_ref(mem, &val_key));
+    }
+    get_value_key_owned(mem, acc)
+}
+
+/// The fields of an object that are stored: all of them, or with
+/// [`NullObjectPolicy::Drop`] those whose child `stores_null` says is not
+/// stored as `null`.
+pub(crate) fn kept_fields<K, T>(
+    config: &Config,
+    fields: impl IntoIterator<Item = (K, T)>,
+    stores_null: impl Fn(&T, &Config) -> bool,
+) -> impl Iterator<Item = (K, T)> {
+    let drop_nulls = config.null_object_policy == NullObjectPolicy::Drop;
+    fields
+        .into_iter()
+        .filter(move |(_, v)| !(drop_nulls && stores_null(v, config)))
+}
+
+/// Report the depth of the node about to be added, then add it one level
+/// deeper than its parent.
+pub(crate) fn nested(mem: &mut Memory, add: impl FnOnce(&mut Memory) -> Key) -> Key {
+    if let Some(instrumentation) = &mem.instrumentation {
+        instrumentation.on_depth(mem.depth);
+    }
+    mem.depth += 1;
+    let key = add(mem);
+    mem.depth -= 1;
+    key
+}
+
+/// Reference to write for an object field, following
+/// [`Config::null_object_policy`].
+///
+/// `null` values have an empty key; with [`NullObjectPolicy::Marker`] they
+/// are written as `_` instead, like `null` array elements.
+pub(crate) fn field_ref<'a>(mem: &Memory, key: &'a Key) -> &'a str {
+    if key.is_null() && mem.config.null_object_policy == NullObjectPolicy::Marker {
+        "_"
+    } else {
+        key.as_str()
     }
 }
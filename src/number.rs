@@ -26,10 +26,16 @@
 //! | 3844 | `"100"` |
 
 /// Character set for base-62 encoding: 0-9, A-Z, a-z
-const ITO_S: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+///
+/// Re-exported as [`KEY_ALPHABET`](crate::KEY_ALPHABET). The character at
+/// position `i` is the digit with value `i`.
+pub const ITO_S: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
 
 /// Base of the encoding (62 characters)
-const N: usize = ITO_S.len();
+///
+/// Re-exported as [`KEY_BASE`](crate::KEY_BASE). Keys have one character
+/// for the first `N` values, two for the first `N * N`, and so on.
+pub const N: usize = ITO_S.len();
 
 /// Convert base-62 string to integer index.
 ///
@@ -55,14 +61,24 @@ const N: usize = ITO_S.len();
 /// assert_eq!(s_to_int("10"), 62);
 /// ```
 pub fn s_to_int(s: &str) -> usize {
-    let mut acc = 0;
-    let mut pow = 1;
-    for c in s.chars().rev() {
+    s.chars().fold(0, |acc, c| {
         let idx = ITO_S.find(c).expect("invalid character in s_to_int");
-        acc += idx * pow;
-        pow *= N;
+        acc * N + idx
+    })
+}
+
+/// Convert base-62 string to integer index without panicking.
+///
+/// Returns `None` if the string is empty, contains characters outside the
+/// base-62 character set, or encodes an index that overflows `usize`.
+pub(crate) fn checked_s_to_int(s: &str) -> Option<usize> {
+    if s.is_empty() {
+        return None;
     }
-    acc
+    s.chars().try_fold(0usize, |acc, c| {
+        let idx = ITO_S.find(c)?;
+        acc.checked_mul(N)?.checked_add(idx)
+    })
 }
 
 /// Convert integer to base-62 string.
@@ -86,18 +102,39 @@ pub fn s_to_int(s: &str) -> usize {
 /// ```
 #[allow(dead_code)]
 pub fn int_to_s(value: usize) -> String {
-    if value == 0 {
-        return ITO_S.chars().next().unwrap().to_string();
-    }
+    // Enough base-62 digits for any 64-bit value
+    const MAX_DIGITS: usize = 11;
+    let digits = ITO_S.as_bytes();
+    let mut buf = [0u8; MAX_DIGITS];
+    let mut start = MAX_DIGITS;
     let mut val = value;
-    let mut acc = Vec::new();
-    while val != 0 {
-        let i = val % N;
-        let c = ITO_S.chars().nth(i).unwrap();
-        acc.push(c);
+    loop {
+        start -= 1;
+        buf[start] = digits[val % N];
         val /= N;
+        if val == 0 {
+            break;
+        }
     }
-    acc.iter().rev().collect()
+    // Every byte comes from the ASCII alphabet
+    String::from(std::str::from_utf8(&buf[start..]).expect("base-62 digits are ASCII"))
+}
+
+/// Get the key assigned to the value stored at `index`.
+///
+/// This is the public name for `int_to_s`, useful for estimating how many
+/// values fit before keys grow by a character.
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{KEY_BASE, key_for_index};
+///
+/// assert_eq!(key_for_index(KEY_BASE - 1), "z");
+/// assert_eq!(key_for_index(KEY_BASE), "10");
+/// ```
+pub fn key_for_index(index: usize) -> String {
+    int_to_s(index)
 }
 
 /// Reverse a string.
@@ -119,6 +156,37 @@ mod tests {
         }
     }
 
+    /// The original digit-by-digit encoder, kept to pin the output.
+    fn reference_int_to_s(value: usize) -> String {
+        if value == 0 {
+            return "0".to_string();
+        }
+        let mut val = value;
+        let mut acc = Vec::new();
+        while val != 0 {
+            acc.push(ITO_S.chars().nth(val % N).unwrap());
+            val /= N;
+        }
+        acc.iter().rev().collect()
+    }
+
+    #[test]
+    fn test_int_to_s_matches_reference_over_large_range() {
+        let boundaries = (1..=10u32).flat_map(|p| {
+            let power = N.pow(p);
+            [power - 1, power, power + 1]
+        });
+        let samples = (0..1_000_000)
+            .chain((0..64).map(|shift| 1usize << shift))
+            .chain(boundaries)
+            .chain([usize::MAX - 1, usize::MAX]);
+        for i in samples {
+            let encoded = int_to_s(i);
+            assert_eq!(encoded, reference_int_to_s(i), "{i}");
+            assert_eq!(s_to_int(&encoded), i, "{i}");
+        }
+    }
+
     #[test]
     fn test_known_values() {
         assert_eq!(int_to_s(0), "0");
@@ -129,4 +197,13 @@ mod tests {
         assert_eq!(int_to_s(61), "z");
         assert_eq!(int_to_s(62), "10");
     }
+
+    #[test]
+    fn test_checked_s_to_int() {
+        assert_eq!(checked_s_to_int("10"), Some(62));
+        assert_eq!(checked_s_to_int(&int_to_s(usize::MAX)), Some(usize::MAX));
+        assert_eq!(checked_s_to_int(""), None);
+        assert_eq!(checked_s_to_int("a-b"), None);
+        assert_eq!(checked_s_to_int("zzzzzzzzzzzzzzzzzzzz"), None);
+    }
 }
@@ -0,0 +1,159 @@
+//! Packed integer arrays.
+//!
+//! An array of integers normally costs a reference per element plus one
+//! `n|` entry per distinct value, which adds up for the large IDs common in
+//! database exports. With
+//! [`Config::pack_int_arrays`](crate::Config::pack_int_arrays) such an
+//! array is stored as a single `d|` entry instead: the first element
+//! followed by the difference of each element from the one before, all in
+//! base 62 with a leading `-` for negative values. Sequential or clustered
+//! IDs then take a character or two per element.
+//!
+//! ```text
+//! [1000, 1001, 1005, 998]  =>  d|G8|1|4|-7
+//! ```
+//!
+//! Elements are stored exactly, including 64-bit integers beyond `2^53`.
+//! The `d|` entry is an extension of this crate and is not understood by
+//! other compress-json implementations.
+
+use crate::number::{ITO_S, N};
+use serde_json::{Number, Value};
+
+/// Encode the integers of an array as a `d|` entry.
+///
+/// Returns `None` if the array is empty or an element is not an integer
+/// (`None` in `ints`).
+pub(crate) fn pack_ints<I: IntoIterator<Item = Option<i128>>>(ints: I) -> Option<String> {
+    let mut acc = String::from("d");
+    let mut prev = 0;
+    for i in ints {
+        let i = i?;
+        acc.push('|');
+        push_signed(&mut acc, i - prev);
+        prev = i;
+    }
+    (acc.len() > 1).then_some(acc)
+}
+
+/// Decode the payload of a `d|` entry (after the prefix) into its integers.
+///
+/// Returns `None` if a part is not a signed base-62 number or an element
+/// falls outside the `i64` and `u64` range.
+pub(crate) fn unpack_ints(payload: &str) -> Option<Vec<i128>> {
+    let mut out = Vec::new();
+    let mut prev: i128 = 0;
+    for part in payload.split('|') {
+        prev = prev.checked_add(parse_signed(part)?)?;
+        if prev < i128::from(i64::MIN) || prev > i128::from(u64::MAX) {
+            return None;
+        }
+        out.push(prev);
+    }
+    Some(out)
+}
+
+/// Decode a `d|` entry into an array of integers.
+///
+/// # Panics
+///
+/// Panics if the entry is malformed.
+pub(crate) fn decode_packed(v_str: &str) -> Value {
+    Value::Array(packed_elements(v_str))
+}
+
+/// Decode the elements of a `d|` entry.
+///
+/// # Panics
+///
+/// Panics if the entry is malformed.
+pub(crate) fn packed_elements(v_str: &str) -> Vec<Value> {
+    let ints = v_str
+        .strip_prefix("d|")
+        .and_then(unpack_ints)
+        .unwrap_or_else(|| panic!("Invalid packed integer array: {v_str}"));
+    ints.into_iter().map(int_value).collect()
+}
+
+/// The `Number` for an unpacked element, which is in `i64` or `u64` range.
+pub(crate) fn int_value(i: i128) -> Value {
+    match i64::try_from(i) {
+        Ok(i) => Value::Number(Number::from(i)),
+        Err(_) => Value::Number(Number::from(i as u64)),
+    }
+}
+
+/// Append `value` in base 62, with a leading `-` if negative.
+fn push_signed(out: &mut String, value: i128) {
+    if value < 0 {
+        out.push('-');
+    }
+    // Enough base-62 digits for any 128-bit magnitude
+    const MAX_DIGITS: usize = 22;
+    let digits = ITO_S.as_bytes();
+    let mut buf = [0u8; MAX_DIGITS];
+    let mut start = MAX_DIGITS;
+    let mut val = value.unsigned_abs();
+    loop {
+        start -= 1;
+        buf[start] = digits[(val % N as u128) as usize];
+        val /= N as u128;
+        if val == 0 {
+            break;
+        }
+    }
+    out.extend(buf[start..].iter().map(|&b| char::from(b)));
+}
+
+/// Parse a signed base-62 number written by [`push_signed`].
+fn parse_signed(s: &str) -> Option<i128> {
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    if digits.is_empty() {
+        return None;
+    }
+    let magnitude = digits.chars().try_fold(0i128, |acc, c| {
+        let idx = ITO_S.find(c)?;
+        acc.checked_mul(N as i128)?.checked_add(idx as i128)
+    })?;
+    Some(if negative { -magnitude } else { magnitude })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_example() {
+        let ints = [1000, 1001, 1005, 998].map(Some);
+        assert_eq!(pack_ints(ints).as_deref(), Some("d|G8|1|4|-7"));
+        assert_eq!(unpack_ints("G8|1|4|-7"), Some(vec![1000, 1001, 1005, 998]));
+    }
+
+    #[test]
+    fn test_pack_extremes() {
+        let ints = [i128::from(i64::MIN), i128::from(u64::MAX), 0];
+        let packed = pack_ints(ints.map(Some)).unwrap();
+        assert_eq!(unpack_ints(&packed[2..]), Some(ints.to_vec()));
+    }
+
+    #[test]
+    fn test_pack_rejects_non_integers_and_empty() {
+        assert_eq!(pack_ints([Some(1), None]), None);
+        assert_eq!(pack_ints([]), None);
+    }
+
+    #[test]
+    fn test_unpack_rejects_malformed() {
+        assert_eq!(unpack_ints(""), None);
+        assert_eq!(unpack_ints("1||2"), None);
+        assert_eq!(unpack_ints("-"), None);
+        assert_eq!(unpack_ints("1|!"), None);
+        // Past u64::MAX
+        let max = pack_ints([Some(i128::from(u64::MAX))]).unwrap();
+        assert_eq!(unpack_ints(&format!("{}|1", &max[2..])), None);
+        assert_eq!(unpack_ints("zzzzzzzzzzzzzzzzzzzzzzzzzzzzz"), None);
+    }
+}
@@ -0,0 +1,100 @@
+//! JSON text parsing for the string-based compression entry points.
+//!
+//! `serde_json` silently keeps the last value when an object repeats a key.
+//! When [`Config::strict_duplicate_keys`] is enabled, parsing goes through
+//! [`StrictValue`] instead, which builds the same `Value` but rejects any
+//! duplicate key with an error.
+
+use crate::config::Config;
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::{Map, Number, Value};
+use std::fmt;
+
+/// Parse JSON text into a `Value`, honoring the duplicate-key setting.
+pub(crate) fn parse_json(json: &str, config: &Config) -> Result<Value, serde_json::Error> {
+    if config.strict_duplicate_keys {
+        serde_json::from_str::<StrictValue>(json).map(|v| v.0)
+    } else {
+        serde_json::from_str(json)
+    }
+}
+
+/// Map key serde_json uses to pass numbers through with `arbitrary_precision`
+#[cfg(feature = "arbitrary_precision")]
+pub(crate) const NUMBER_TOKEN: &str = "$serde_json::private::Number";
+
+/// A `Value` wrapper whose deserialization errors on duplicate object keys.
+struct StrictValue(Value);
+
+impl<'de> Deserialize<'de> for StrictValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(StrictVisitor).map(StrictValue)
+    }
+}
+
+/// Visitor building a `Value` while checking for duplicate keys.
+struct StrictVisitor;
+
+impl<'de> Visitor<'de> for StrictVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any valid JSON value")
+    }
+
+    fn visit_bool<E>(self, b: bool) -> Result<Value, E> {
+        Ok(Value::Bool(b))
+    }
+
+    fn visit_i64<E>(self, i: i64) -> Result<Value, E> {
+        Ok(Value::Number(i.into()))
+    }
+
+    fn visit_u64<E>(self, u: u64) -> Result<Value, E> {
+        Ok(Value::Number(u.into()))
+    }
+
+    fn visit_f64<E>(self, f: f64) -> Result<Value, E> {
+        Ok(Number::from_f64(f).map_or(Value::Null, Value::Number))
+    }
+
+    fn visit_str<E>(self, s: &str) -> Result<Value, E> {
+        Ok(Value::String(s.to_string()))
+    }
+
+    fn visit_string<E>(self, s: String) -> Result<Value, E> {
+        Ok(Value::String(s))
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut arr = Vec::new();
+        while let Some(StrictValue(v)) = seq.next_element()? {
+            arr.push(v);
+        }
+        Ok(Value::Array(arr))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Value, A::Error> {
+        let mut map = Map::new();
+        while let Some(key) = access.next_key::<String>()? {
+            // With `arbitrary_precision`, serde_json hands numbers over as a
+            // single-entry map holding the number's text
+            #[cfg(feature = "arbitrary_precision")]
+            if map.is_empty() && key == NUMBER_TOKEN {
+                let text: String = access.next_value()?;
+                let n = text.parse::<Number>().map_err(de::Error::custom)?;
+                return Ok(Value::Number(n));
+            }
+            if map.contains_key(&key) {
+                return Err(de::Error::custom(format!("duplicate key `{key}`")));
+            }
+            let StrictValue(v) = access.next_value()?;
+            map.insert(key, v);
+        }
+        Ok(Value::Object(map))
+    }
+}
@@ -0,0 +1,425 @@
+//! Reading and updating single paths of a compressed value.
+//!
+//! Paths are [JSON Pointers](https://www.rfc-editor.org/rfc/rfc6901) such as
+//! `/users/0/name`; the empty pointer `""` is the whole document.
+//!
+//! [`get_path`] decodes only the value at the path, not its siblings;
+//! [`get_paths`] resolves several paths, sharing the work for common
+//! ancestors.
+//! [`set_path`] stores a new value and rewrites just the chain of entries
+//! from the root down to its parent, so every untouched subtree keeps its
+//! existing store entry. Large documents can be edited without a full
+//! decompress/compress cycle. [`splice_path`] does the same with a value
+//! that is already compressed, copying its entries instead of re-encoding it.
+//!
+//! # Example
+//!
+//! ```rust
+//! use compress_json_rs::{compress, decompress, get_path, set_path};
+//! use serde_json::json;
+//!
+//! let mut c = compress(&json!({"user": {"name": "Alice", "age": 30}}));
+//! assert_eq!(get_path(&c, "/user/age"), Some(json!(30)));
+//!
+//! set_path(&mut c, "/user/age", &json!(31)).unwrap();
+//! assert_eq!(decompress(c), json!({"user": {"name": "Alice", "age": 31}}));
+//! ```
+
+use crate::config::CONFIG;
+use crate::core::{Compressed, decode_at, is_null_key, root_store, schema_keys};
+use crate::decoder::Decoder;
+use crate::encode::decode_key;
+use crate::error::PathError;
+use crate::key::Key;
+use crate::memory::{
+    Memory, add_compressed, add_value, get_schema, get_value_key_owned, memory_from_values,
+};
+use crate::packed::packed_elements;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// One container on the way from the root to the updated value.
+struct Link {
+    /// Stored entry of the container
+    entry: String,
+    /// Where the child goes within the container
+    slot: Slot,
+}
+
+/// Position of a child within its container's entry.
+enum Slot {
+    /// Replace the reference at this position of an array
+    Element(usize),
+    /// Replace the reference of this existing object key
+    Field(usize),
+    /// Add this key to an object
+    NewField(String),
+}
+
+/// Read the value at a JSON Pointer.
+///
+/// Only the entries along the path and the subtree at its end are decoded.
+///
+/// # Arguments
+///
+/// * `c` - The compressed representation
+/// * `pointer` - A JSON Pointer, e.g. `/items/0`
+///
+/// # Returns
+///
+/// The value at `pointer`, or `None` if the pointer is malformed or does not
+/// lead to a value
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{compress, get_path};
+/// use serde_json::json;
+///
+/// let c = compress(&json!({"items": [{"id": 7}]}));
+/// assert_eq!(get_path(&c, "/items/0/id"), Some(json!(7)));
+/// assert_eq!(get_path(&c, "/items/1"), None);
+/// ```
+///
+/// # Panics
+///
+/// Panics if the compressed data is malformed.
+pub fn get_path(c: &Compressed, pointer: &str) -> Option<Value> {
+    let (values, root) = root_store(&c.values, c.root.as_str());
+    let values = &values[..];
+    let mut key = root.to_string();
+    let tokens = parse_pointer(pointer).ok()?;
+    for (i, token) in tokens.iter().enumerate() {
+        if let Some(found) = packed_get(values, &key, &tokens[i..]) {
+            return found;
+        }
+        key = child(values, &key, token, &mut |k| decode_at(values, k))?.1?;
+    }
+    Some(decode_at(values, &key))
+}
+
+/// Read the values at several JSON Pointers in one pass.
+///
+/// Equivalent to calling [`get_path`] for each pointer, but work is shared
+/// between them: a common ancestor (e.g. `/user` for `/user/name` and
+/// `/user/age`) is looked up once, and every decoded entry, including object
+/// schemas, is cached for the remaining pointers. Fetching a few fields of a
+/// large record this way avoids both repeated lookups and a full
+/// decompression.
+///
+/// # Arguments
+///
+/// * `c` - The compressed representation
+/// * `pointers` - JSON Pointers to resolve
+///
+/// # Returns
+///
+/// One result per pointer, in the same order, as [`get_path`] would return
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{compress, get_paths};
+/// use serde_json::json;
+///
+/// let c = compress(&json!({"user": {"name": "Alice", "age": 30}}));
+/// assert_eq!(
+///     get_paths(&c, &["/user/name", "/user/age", "/user/email"]),
+///     [Some(json!("Alice")), Some(json!(30)), None]
+/// );
+/// ```
+///
+/// # Panics
+///
+/// Panics if the compressed data is malformed.
+pub fn get_paths(c: &Compressed, pointers: &[&str]) -> Vec<Option<Value>> {
+    let (values, root) = root_store(&c.values, c.root.as_str());
+    let values = &values[..];
+    let mut decoder = Decoder::new(values);
+    // Key reached by each pointer prefix seen so far (`None` if missing)
+    let mut resolved: HashMap<&str, Option<String>> = HashMap::new();
+    pointers
+        .iter()
+        .map(|pointer| {
+            let tokens = parse_pointer(pointer).ok()?;
+            let mut key = root.to_string();
+            let mut end = 0;
+            for (i, token) in tokens.iter().enumerate() {
+                if let Some(found) = packed_get(values, &key, &tokens[i..]) {
+                    return found;
+                }
+                // Ends at the next `/`, or the end of the pointer
+                end = pointer[end + 1..]
+                    .find('/')
+                    .map_or(pointer.len(), |i| end + 1 + i);
+                let prefix = &pointer[..end];
+                let next = match resolved.get(prefix) {
+                    Some(next) => next.clone(),
+                    None => {
+                        let next = child(values, &key, token, &mut |k| decoder.decode_cached_at(k))
+                            .and_then(|(_, next)| next);
+                        resolved.insert(prefix, next.clone());
+                        next
+                    }
+                };
+                key = next?;
+            }
+            Some(decoder.decode_cached_at(&key))
+        })
+        .collect()
+}
+
+/// Replace (or add) the value at a JSON Pointer.
+///
+/// `new_value` is added to the store and every container on the path is
+/// re-encoded to reference its new child; all other entries are left as
+/// they are and stay shared. If the last token names a key that does not
+/// exist in an object, the key is added. Array elements can only be
+/// replaced, not appended.
+///
+/// Entries that are no longer referenced remain in the store; pass the
+/// result to [`garbage_collect`](crate::garbage_collect) to drop them.
+///
+/// # Arguments
+///
+/// * `c` - The compressed representation to update in place
+/// * `pointer` - A JSON Pointer, e.g. `/items/0`; `""` replaces the root
+/// * `new_value` - The value to store at `pointer`
+///
+/// # Returns
+///
+/// `Ok(())`, or a [`PathError`] if the pointer is malformed or does not lead
+/// to a value. On error `c` is unchanged.
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{PathError, compress, decompress, set_path};
+/// use serde_json::json;
+///
+/// let mut c = compress(&json!({"tags": ["a", "b"]}));
+/// set_path(&mut c, "/tags/1", &json!("c")).unwrap();
+/// set_path(&mut c, "/count", &json!(2)).unwrap();
+/// assert_eq!(decompress(c.clone()), json!({"tags": ["a", "c"], "count": 2}));
+///
+/// assert!(matches!(
+///     set_path(&mut c, "/tags/5", &json!("x")),
+///     Err(PathError::NotFound { .. })
+/// ));
+/// ```
+///
+/// # Panics
+///
+/// Panics if the compressed data is malformed.
+pub fn set_path(c: &mut Compressed, pointer: &str, new_value: &Value) -> Result<(), PathError> {
+    replace_path(c, pointer, |mem| add_value(mem, new_value))
+}
+
+/// Place an already compressed value at a JSON Pointer, by reference.
+///
+/// Works like [`set_path`], but the new value is `part`, whose entries are
+/// copied into the store with their keys remapped (see [`add_compressed`])
+/// rather than decoded and re-encoded. Entries that `c` already holds are
+/// shared. This builds a composite document from pre-compressed parts, e.g.
+/// a cached sub-document embedded in a larger response.
+///
+/// # Arguments
+///
+/// * `c` - The compressed representation to update in place
+/// * `pointer` - A JSON Pointer, e.g. `/user`; `""` replaces the root
+/// * `part` - The compressed value to store at `pointer`
+///
+/// # Returns
+///
+/// `Ok(())`, or a [`PathError`] if the pointer is malformed or does not lead
+/// to a value. On error `c` is unchanged.
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{compress, decompress, splice_path};
+/// use serde_json::json;
+///
+/// let user = compress(&json!({"name": "Alice", "roles": ["admin"]}));
+/// let mut page = compress(&json!({"title": "Profile", "user": null}));
+///
+/// splice_path(&mut page, "/user", &user).unwrap();
+/// assert_eq!(
+///     decompress(page),
+///     json!({"title": "Profile", "user": {"name": "Alice", "roles": ["admin"]}})
+/// );
+/// ```
+///
+/// # Panics
+///
+/// Panics if either compressed value is malformed.
+pub fn splice_path(c: &mut Compressed, pointer: &str, part: &Compressed) -> Result<(), PathError> {
+    replace_path(c, pointer, |mem| add_compressed(mem, part))
+}
+
+/// Store the value `add` adds at `pointer`, re-encoding every container on
+/// the way to it.
+fn replace_path(
+    c: &mut Compressed,
+    pointer: &str,
+    add: impl FnOnce(&mut Memory) -> Key,
+) -> Result<(), PathError> {
+    let tokens = parse_pointer(pointer)?;
+    let mut links = Vec::with_capacity(tokens.len());
+    let mut key = c.root.as_str().to_string();
+    let mut resolved = String::new();
+    for (i, token) in tokens.iter().enumerate() {
+        resolved.push('/');
+        resolved.push_str(&escape_token(token));
+        let not_found = || PathError::NotFound {
+            pointer: resolved.clone(),
+        };
+        let (slot, next) = child(&c.values, &key, token, &mut |k| decode_at(&c.values, k))
+            .ok_or_else(not_found)?;
+        let entry = c.values[decode_key(&key)].clone();
+        match next {
+            Some(next) => key = next,
+            None if i + 1 == tokens.len() => {}
+            None => return Err(not_found()),
+        }
+        links.push(Link { entry, slot });
+    }
+
+    let mut mem = memory_from_values(&c.values, &CONFIG).unwrap_or_else(|e| panic!("{e}"));
+    let mut key = add(&mut mem);
+    for link in links.into_iter().rev() {
+        let mut parts: Vec<String> = match &link.entry[2..] {
+            "" => Vec::new(),
+            // A packed array is rewritten as a plain `a|` array
+            _ if link.entry.starts_with("d|") => packed_elements(&link.entry)
+                .iter()
+                .map(|v| add_value(&mut mem, v).into_string())
+                .collect(),
+            body => body.split('|').map(str::to_string).collect(),
+        };
+        let entry = match link.slot {
+            Slot::Element(i) => {
+                parts[i] = if key.is_null() {
+                    "_".to_string()
+                } else {
+                    key.into_string()
+                };
+                format!("a|{}", parts.join("|"))
+            }
+            Slot::Field(i) => {
+                parts[i + 1] = key.into_string();
+                format!("o|{}", parts.join("|"))
+            }
+            Slot::NewField(name) => {
+                let mut keys = match parts.first() {
+                    Some(schema) => schema_keys(decode_at(&c.values, schema)),
+                    None => Vec::new(),
+                };
+                keys.push(name);
+                let schema = get_schema(&mut mem, &keys);
+                let mut refs = parts.split_off(parts.len().min(1));
+                refs.push(key.into_string());
+                format!("o|{}|{}", schema, refs.join("|"))
+            }
+        };
+        key = get_value_key_owned(&mut mem, entry);
+    }
+    c.values = mem.store;
+    c.root = key;
+    Ok(())
+}
+
+/// Resolve the remaining `tokens` of a pointer in the packed (`d|`) integer
+/// array stored under `key`.
+///
+/// Returns `None` if `key` is not a packed array, otherwise the value found,
+/// if any. Elements are integers, so at most one token can match.
+fn packed_get(values: &[String], key: &str, tokens: &[String]) -> Option<Option<Value>> {
+    if is_null_key(key) {
+        return None;
+    }
+    let entry = values[decode_key(key)].as_str();
+    if !entry.starts_with("d|") {
+        return None;
+    }
+    let [token] = tokens else {
+        return Some(None);
+    };
+    let index = parse_index(token);
+    Some(index.and_then(|i| packed_elements(entry).into_iter().nth(i)))
+}
+
+/// Look up `token` in the container stored under `key`.
+///
+/// Returns where the token sits in the container's entry and the child's
+/// key, which is `None` for a key an object does not have yet and for an
+/// element of a packed (`d|`) array, which has no entry of its own. Returns
+/// `None` if `key` is not a container or has no such child.
+///
+/// Object schemas are decoded through `resolve`.
+fn child(
+    values: &[String],
+    key: &str,
+    token: &str,
+    resolve: &mut impl FnMut(&str) -> Value,
+) -> Option<(Slot, Option<String>)> {
+    if is_null_key(key) {
+        return None;
+    }
+    let entry = values[decode_key(key)].as_str();
+    if let Some(refs) = entry.strip_prefix("a|") {
+        let index = parse_index(token)?;
+        if refs.is_empty() {
+            return None;
+        }
+        let r = refs.split('|').nth(index)?;
+        return Some((Slot::Element(index), Some(r.to_string())));
+    }
+    if let Some(packed) = entry.strip_prefix("d|") {
+        let index = parse_index(token)?;
+        packed.split('|').nth(index)?;
+        return Some((Slot::Element(index), None));
+    }
+    let rest = entry.strip_prefix("o|")?;
+    if rest.is_empty() {
+        return Some((Slot::NewField(token.to_string()), None));
+    }
+    let mut parts = rest.split('|');
+    let keys = schema_keys(resolve(parts.next()?));
+    match keys.iter().position(|k| k == token) {
+        Some(i) => Some((Slot::Field(i), Some(parts.nth(i)?.to_string()))),
+        None => Some((Slot::NewField(token.to_string()), None)),
+    }
+}
+
+/// Split a JSON Pointer into its unescaped reference tokens.
+fn parse_pointer(pointer: &str) -> Result<Vec<String>, PathError> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(rest) = pointer.strip_prefix('/') else {
+        return Err(PathError::InvalidPointer {
+            pointer: pointer.to_string(),
+        });
+    };
+    Ok(rest
+        .split('/')
+        .map(|t| t.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+/// Escape a reference token for use in a JSON Pointer.
+pub(crate) fn escape_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// Parse an array index token: decimal digits without leading zeros.
+fn parse_index(token: &str) -> Option<usize> {
+    if token.is_empty()
+        || !token.bytes().all(|b| b.is_ascii_digit())
+        || (token.len() > 1 && token.starts_with('0'))
+    {
+        return None;
+    }
+    token.parse().ok()
+}
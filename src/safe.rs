@@ -0,0 +1,776 @@
+//! Fallible decompression for untrusted input.
+//!
+//! [`decompress`](crate::decompress) assumes well-formed data: a dangling
+//! reference or an unparsable entry panics, a self-referencing entry
+//! recurses until the stack overflows, and a small store whose references
+//! fan out can expand into an enormous tree. The functions here decode the
+//! same way but return a [`DecompressError`] instead.
+//!
+//! # Checks
+//!
+//! | Check | When |
+//! |-------|------|
+//! | Reference bounds | Always |
+//! | Cycle detection | Always |
+//! | Malformed entries | Always |
+//! | Nesting depth | With [`Config::max_depth`] |
+//! | Output size | With [`Config::max_nodes`] |
+//! | Array length | With [`Config::max_array_len`] |
+//! | Object size | With [`Config::max_object_keys`] |
+//!
+//! With [`Config::lenient`], stray trailing empty references past the end
+//! of an object's schema are ignored instead of failing, and numbers
+//! written as `n|+42` or `n|1_000` are accepted rather than reported as
+//! malformed.
+//!
+//! [`Config::untrusted`] enables every limit with conservative defaults;
+//! [`decompress_safe`] decodes with it.
+//!
+//! [`validate`] runs the checks that are always enabled, plus the nesting
+//! limit of [`Config::untrusted`], without decoding: each entry reachable
+//! from the root is checked once, however often it is
+//! referenced, so a payload can be vetted in time proportional to its store
+//! rather than its expanded tree. [`validate_reader`] does so for a
+//! serialized payload read from a stream.
+//!
+//! # Example
+//!
+//! ```rust
+//! use compress_json_rs::{Compressed, Config, DecompressError, try_decompress_with_config};
+//!
+//! // An array that contains itself
+//! let malicious = Compressed::new(vec!["a|0".to_string()], "0".to_string());
+//!
+//! let result = try_decompress_with_config(&malicious, &Config::untrusted());
+//! assert_eq!(result, Err(DecompressError::Cycle { index: 0 }));
+//! ```
+
+use crate::config::{CONFIG, Config};
+use crate::core::{Compressed, decode_entry, is_null_key, root_store};
+#[cfg(feature = "arbitrary_precision")]
+use crate::encode::f64_loses_precision;
+use crate::encode::lenient_num;
+use crate::error::DecompressError;
+use crate::number::checked_s_to_int;
+use crate::packed::{int_value, unpack_ints};
+#[cfg(feature = "arbitrary_precision")]
+use serde_json::Number;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::io::Read;
+use std::rc::Rc;
+
+/// Decompress without panicking on malformed input.
+///
+/// Applies the checks that are always enabled (reference bounds, cycles and
+/// malformed entries) but no size limits; use
+/// [`try_decompress_with_config`] with [`Config::untrusted`] for those.
+///
+/// # Arguments
+///
+/// * `c` - The compressed representation
+///
+/// # Returns
+///
+/// The same value [`decompress`](crate::decompress) returns, or the first
+/// problem found
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{compress, try_decompress};
+/// use serde_json::json;
+///
+/// let data = json!({"id": 1, "tags": ["a", "b"]});
+/// assert_eq!(try_decompress(&compress(&data)), Ok(data));
+/// ```
+pub fn try_decompress(c: &Compressed) -> Result<Value, DecompressError> {
+    try_decompress_with_config(c, &CONFIG)
+}
+
+/// Decompress without panicking, enforcing the decode limits of `config`.
+///
+/// # Arguments
+///
+/// * `c` - The compressed representation
+/// * `config` - Configuration providing the decode limits
+///   ([`Config::max_depth`], [`Config::max_nodes`], [`Config::max_array_len`]
+///   and [`Config::max_object_keys`]), [`Config::lenient`] and
+///   [`Config::treat_empty_string_as_null`]
+///
+/// # Returns
+///
+/// The same value [`decompress`](crate::decompress) returns, or the first
+/// problem found
+pub fn try_decompress_with_config(
+    c: &Compressed,
+    config: &Config,
+) -> Result<Value, DecompressError> {
+    let (values, root) = root_store(&c.values, c.root.as_str());
+    let values = &values[..];
+    CheckedDecoder::new(values, config).decode(root, 1)
+}
+
+/// Decompress any input without panicking, with every decode limit on.
+///
+/// This is [`try_decompress_with_config`] with [`Config::untrusted`]: every
+/// reference is bounds-checked, cycles are detected, numbers and keys are
+/// parsed without overflow, and nesting depth and output size are bounded so
+/// decoding cannot exhaust the stack or memory. It is the single entry
+/// point for callers that must never panic on a payload, whatever its
+/// contents, and is exercised with randomly generated stores in the tests.
+///
+/// # Arguments
+///
+/// * `c` - The compressed representation
+///
+/// # Returns
+///
+/// The same value [`decompress`](crate::decompress) returns, or the first
+/// problem found
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{Compressed, compress, decompress_safe};
+/// use serde_json::json;
+///
+/// let data = json!({"id": 1, "tags": ["a", "b"]});
+/// assert_eq!(decompress_safe(&compress(&data)), Ok(data));
+///
+/// let garbage = Compressed::new(vec!["o|9|z".to_string()], "0");
+/// assert!(decompress_safe(&garbage).is_err());
+/// ```
+pub fn decompress_safe(c: &Compressed) -> Result<Value, DecompressError> {
+    try_decompress_with_config(c, &Config::untrusted())
+}
+
+/// Decode one element of an array root without decoding its siblings.
+///
+/// Only the root entry and the element's own subtree are read, with the
+/// same checks as [`try_decompress`]; malformed siblings are not noticed.
+/// This is the array counterpart of [`get_path`](crate::get_path) for a
+/// single index.
+///
+/// # Arguments
+///
+/// * `c` - The compressed representation
+/// * `index` - Position of the element in the root array
+///
+/// # Returns
+///
+/// The element (`Value::Null` for a `null` element), `None` if the root is
+/// not an array or `index` is out of range, or the first problem found
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{array_get, compress};
+/// use serde_json::json;
+///
+/// let c = compress(&json!([{"id": 1}, null, {"id": 3}]));
+/// assert_eq!(array_get(&c, 2), Ok(Some(json!({"id": 3}))));
+/// assert_eq!(array_get(&c, 1), Ok(Some(json!(null))));
+/// assert_eq!(array_get(&c, 3), Ok(None));
+/// ```
+pub fn array_get(c: &Compressed, index: usize) -> Result<Option<Value>, DecompressError> {
+    let (values, root) = root_store(&c.values, c.root.as_str());
+    if is_null_key(root) {
+        return Ok(None);
+    }
+    let mut decoder = CheckedDecoder::new(&values, &CONFIG);
+    let root = decoder.index(root)?;
+    decoder.element(root, index)
+}
+
+/// Check that a payload decodes, without decoding it.
+///
+/// Applies the checks that are always enabled in fallible decoding
+/// (reference bounds, cycles and malformed entries) to every entry reachable
+/// from the root, visiting each entry once. No output is built, so even a
+/// payload whose shared references expand into a huge tree is checked in
+/// time and memory proportional to its store. Nesting deeper than the
+/// `max_depth` of [`Config::untrusted`] is rejected too, since
+/// [`decompress`](crate::decompress) recurses once per level and would
+/// overflow the stack on it. If validation succeeds, `decompress` does not
+/// panic on the payload; the other size limits of [`Config`] are not
+/// checked.
+///
+/// # Arguments
+///
+/// * `c` - The compressed representation
+///
+/// # Returns
+///
+/// `Ok(())`, or the first problem found, as [`try_decompress`] would
+/// report it; [`DecompressError::MaxDepthExceeded`] for nesting that is
+/// too deep
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{Compressed, DecompressError, compress, validate};
+/// use serde_json::json;
+///
+/// assert_eq!(validate(&compress(&json!({"id": 1, "tags": ["a"]}))), Ok(()));
+///
+/// let dangling = Compressed::new(vec!["a|0|5".to_string()], "0");
+/// assert_eq!(
+///     validate(&dangling),
+///     Err(DecompressError::IndexOutOfBounds { index: 5, len: 1 })
+/// );
+/// ```
+pub fn validate(c: &Compressed) -> Result<(), DecompressError> {
+    let (values, root) = root_store(&c.values, c.root.as_str());
+    let values = &values[..];
+    if is_null_key(root) {
+        return Ok(());
+    }
+    let mut checker = CheckedDecoder::new(values, &CONFIG);
+    let limit = Config::untrusted().max_depth.unwrap_or(usize::MAX);
+    let too_deep = Err(DecompressError::MaxDepthExceeded { limit });
+    let mut state = vec![Visit::New; values.len()];
+    // Levels spanned by each checked entry and what it contains
+    let mut heights = vec![0; values.len()];
+    let start = checker.index(root)?;
+    if checker.min_height(start) > limit {
+        return too_deep;
+    }
+    state[start] = Visit::Active;
+    // Entries on the path from the root, with their children, the position
+    // of the next child to visit and the tallest child checked so far
+    let mut stack = vec![(start, checker.children(start)?, 0, 0)];
+    // The children of the top of the stack sit one level below it
+    while let (depth, Some(top)) = (stack.len() + 1, stack.last_mut()) {
+        let Some(&child) = top.1.get(top.2) else {
+            let index = top.0;
+            heights[index] = checker.min_height(index).max(top.3 + 1);
+            state[index] = Visit::Done;
+            stack.pop();
+            if let Some(parent) = stack.last_mut() {
+                parent.3 = parent.3.max(heights[index]);
+            }
+            continue;
+        };
+        top.2 += 1;
+        match state[child] {
+            Visit::Active => return Err(DecompressError::Cycle { index: child }),
+            Visit::Done => {
+                if depth - 1 + heights[child] > limit {
+                    return too_deep;
+                }
+                top.3 = top.3.max(heights[child]);
+            }
+            Visit::New => {
+                if depth - 1 + checker.min_height(child) > limit {
+                    return too_deep;
+                }
+                state[child] = Visit::Active;
+                stack.push((child, checker.children(child)?, 0, 0));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Read a serialized payload from a stream and [`validate`] it.
+///
+/// The `[values, root]` form is parsed straight from `r`, without reading
+/// the input into a string first, and checked without decoding it. A proxy
+/// can vet payloads this way before passing them on. Wrap unbuffered sources
+/// (files, sockets) in an [`io::BufReader`](std::io::BufReader).
+///
+/// # Arguments
+///
+/// * `r` - Source of the serialized payload
+///
+/// # Returns
+///
+/// `Ok(())`, a [`DecompressError::Parse`] if the input is not a serialized
+/// compressed value, or the first problem [`validate`] finds
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{DecompressError, validate_reader};
+///
+/// let payload = br#"[["Alice","a|0|0"],"1"]"#;
+/// assert_eq!(validate_reader(&payload[..]), Ok(()));
+///
+/// let dangling = br#"[["Alice","a|0|2"],"1"]"#;
+/// assert_eq!(
+///     validate_reader(&dangling[..]),
+///     Err(DecompressError::IndexOutOfBounds { index: 2, len: 2 })
+/// );
+/// assert!(matches!(
+///     validate_reader(&b"[[1]"[..]),
+///     Err(DecompressError::Parse { .. })
+/// ));
+/// ```
+pub fn validate_reader<R: Read>(r: R) -> Result<(), DecompressError> {
+    let c: Compressed = serde_json::from_reader(r).map_err(|err| DecompressError::Parse {
+        message: err.to_string(),
+    })?;
+    validate(&c)
+}
+
+/// Progress of [`validate`] through an entry.
+#[derive(Copy, Clone)]
+enum Visit {
+    /// Not reached yet
+    New,
+    /// On the path from the root to the entry being checked
+    Active,
+    /// Checked along with everything it references
+    Done,
+}
+
+/// Resolve the schema of every object entry in `values`, checking each as
+/// fallible decoding does.
+///
+/// Returns the schema key of each object with fields, in store order, with
+/// the schema's keys.
+pub(crate) fn object_schemas(
+    values: &[String],
+) -> Result<Vec<(&str, Vec<String>)>, DecompressError> {
+    let mut checker = CheckedDecoder::new(values, &CONFIG);
+    let mut schemas = Vec::new();
+    for value in values {
+        if let Some(schema) = value.strip_prefix("o|").and_then(|s| s.split('|').next())
+            && !schema.is_empty()
+        {
+            schemas.push((schema, checker.schema(schema)?.to_vec()));
+        }
+    }
+    Ok(schemas)
+}
+
+/// Decoder state for one fallible decompression.
+struct CheckedDecoder<'a> {
+    /// The values array being decoded
+    values: &'a [String],
+    /// Limits to enforce
+    config: &'a Config,
+    /// Entries currently being decoded, to detect cycles
+    active: Vec<bool>,
+    /// Number of values produced so far
+    nodes: usize,
+    /// Key counts of the schemas checked so far, by index
+    schema_lens: HashMap<usize, usize>,
+    /// Keys of the schemas resolved so far, by index
+    schemas: HashMap<usize, Rc<[String]>>,
+}
+
+impl<'a> CheckedDecoder<'a> {
+    /// Create a decoder over `values` enforcing the limits of `config`.
+    fn new(values: &'a [String], config: &'a Config) -> Self {
+        CheckedDecoder {
+            values,
+            config,
+            active: vec![false; values.len()],
+            nodes: 0,
+            schema_lens: HashMap::new(),
+            schemas: HashMap::new(),
+        }
+    }
+
+    /// Decode the value referenced by `key` at nesting level `depth`.
+    fn decode(&mut self, key: &str, depth: usize) -> Result<Value, DecompressError> {
+        if let Some(limit) = self.config.max_depth
+            && depth > limit
+        {
+            return Err(DecompressError::MaxDepthExceeded { limit });
+        }
+        self.nodes += 1;
+        if let Some(limit) = self.config.max_nodes
+            && self.nodes > limit
+        {
+            return Err(DecompressError::MaxNodesExceeded { limit });
+        }
+        if is_null_key(key) {
+            return Ok(Value::Null);
+        }
+        let index = self.index(key)?;
+        if self.active[index] {
+            return Err(DecompressError::Cycle { index });
+        }
+        self.active[index] = true;
+        let result = self.decode_index(index, depth);
+        self.active[index] = false;
+        result
+    }
+
+    /// Decode the entry stored at `index`.
+    fn decode_index(&mut self, index: usize, depth: usize) -> Result<Value, DecompressError> {
+        let values = self.values;
+        let v_str = values[index].as_str();
+        if let Some(refs) = v_str.strip_prefix("a|") {
+            if refs.is_empty() {
+                return Ok(Value::Array(Vec::new()));
+            }
+            if let Some(limit) = self.config.max_array_len
+                && refs.split('|').count() > limit
+            {
+                return Err(DecompressError::MaxArrayLenExceeded { index, limit });
+            }
+            return refs
+                .split('|')
+                .map(|k| self.decode(k, depth + 1))
+                .collect::<Result<_, _>>()
+                .map(Value::Array);
+        }
+        if let Some(rest) = v_str.strip_prefix("o|") {
+            if rest.is_empty() {
+                return Ok(Value::Object(Map::new()));
+            }
+            let object_limit = self.config.max_object_keys;
+            if let Some(limit) = object_limit
+                && rest.split('|').count() - 1 > limit
+            {
+                return Err(DecompressError::MaxObjectKeysExceeded { index, limit });
+            }
+            let mut parts = rest.split('|');
+            let schema = parts.next().unwrap_or_default();
+            // Counted before the keys are resolved
+            if let Some(limit) = object_limit
+                && self.schema_len(schema)? > limit
+            {
+                return Err(DecompressError::MaxObjectKeysExceeded { index, limit });
+            }
+            let keys = self.schema(schema)?;
+            let mut refs: Vec<&str> = parts.collect();
+            if self.config.lenient {
+                while refs.len() > keys.len() && refs.last() == Some(&"") {
+                    refs.pop();
+                }
+            }
+            if keys.len() < refs.len() {
+                return Err(DecompressError::InvalidEntry { index });
+            }
+            let mut map = Map::with_capacity(refs.len());
+            for (key, r) in keys.iter().zip(refs) {
+                let v = self.decode(r, depth + 1)?;
+                map.insert(key.clone(), v);
+            }
+            return Ok(Value::Object(map));
+        }
+        if let Some(packed) = v_str.strip_prefix("d|") {
+            return self.packed(index, packed, depth);
+        }
+        if v_str.starts_with("c|") {
+            return self.string(index).map(Value::String);
+        }
+        match self.scalar(index)? {
+            Value::String(s) if s.is_empty() && self.config.treat_empty_string_as_null => {
+                Ok(Value::Null)
+            }
+            value => Ok(value),
+        }
+    }
+
+    /// Decode the packed (`d|`) integer array at `index`, whose elements
+    /// are at nesting level `depth + 1`.
+    fn packed(
+        &mut self,
+        index: usize,
+        packed: &str,
+        depth: usize,
+    ) -> Result<Value, DecompressError> {
+        let len = packed.split('|').count();
+        if let Some(limit) = self.config.max_array_len
+            && len > limit
+        {
+            return Err(DecompressError::MaxArrayLenExceeded { index, limit });
+        }
+        if let Some(limit) = self.config.max_depth
+            && depth + 1 > limit
+        {
+            return Err(DecompressError::MaxDepthExceeded { limit });
+        }
+        self.nodes += len;
+        if let Some(limit) = self.config.max_nodes
+            && self.nodes > limit
+        {
+            return Err(DecompressError::MaxNodesExceeded { limit });
+        }
+        let ints = unpack_ints(packed).ok_or(DecompressError::InvalidEntry { index })?;
+        Ok(Value::Array(ints.into_iter().map(int_value).collect()))
+    }
+
+    /// Check the entry at `index` on its own, returning the indices of the
+    /// values it contains.
+    ///
+    /// Object schemas and chunks are checked here, since they are not part
+    /// of the output tree.
+    fn children(&mut self, index: usize) -> Result<Vec<usize>, DecompressError> {
+        let v_str = self.values[index].as_str();
+        let refs: Vec<&str> = if let Some(refs) = v_str.strip_prefix("a|") {
+            refs.split('|').filter(|_| !refs.is_empty()).collect()
+        } else if let Some(rest) = v_str.strip_prefix("o|") {
+            if rest.is_empty() {
+                return Ok(Vec::new());
+            }
+            let mut parts = rest.split('|');
+            let len = self.schema_len(parts.next().unwrap_or_default())?;
+            let refs: Vec<&str> = parts.collect();
+            if len < refs.len() {
+                return Err(DecompressError::InvalidEntry { index });
+            }
+            refs
+        } else {
+            if let Some(packed) = v_str.strip_prefix("d|") {
+                unpack_ints(packed).ok_or(DecompressError::InvalidEntry { index })?;
+            } else if v_str.starts_with("c|") {
+                self.string(index)?;
+            } else {
+                self.scalar(index)?;
+            }
+            return Ok(Vec::new());
+        };
+        refs.into_iter()
+            .filter(|r| !is_null_key(r))
+            .map(|r| self.index(r))
+            .collect()
+    }
+
+    /// Levels that decoding the entry at `index` spans at least: two for a
+    /// container with elements or fields (even `null` ones), one otherwise.
+    fn min_height(&self, index: usize) -> usize {
+        let v_str = self.values[index].as_str();
+        let has_refs = match v_str.get(..2) {
+            Some("a|" | "d|") => v_str.len() > 2,
+            Some("o|") => v_str[2..].contains('|'),
+            _ => false,
+        };
+        if has_refs { 2 } else { 1 }
+    }
+
+    /// Decode element `i` of the array at `index`, if it is an array that
+    /// long.
+    fn element(&mut self, index: usize, i: usize) -> Result<Option<Value>, DecompressError> {
+        if let Some(packed) = self.values[index].strip_prefix("d|") {
+            let ints = unpack_ints(packed).ok_or(DecompressError::InvalidEntry { index })?;
+            return Ok(ints.get(i).copied().map(int_value));
+        }
+        let Some(refs) = self.values[index].strip_prefix("a|") else {
+            return Ok(None);
+        };
+        if refs.is_empty() {
+            return Ok(None);
+        }
+        let Some(r) = refs.split('|').nth(i) else {
+            return Ok(None);
+        };
+        self.active[index] = true;
+        let result = self.decode(r, 2);
+        self.active[index] = false;
+        result.map(Some)
+    }
+
+    /// Check an object schema and count its keys, without resolving them.
+    ///
+    /// A schema is an array of strings or a single string. An array may
+    /// start with a base schema (see [`Config::extend_schemas`]), which must
+    /// be stored before it, so a chain of bases always ends. Chains are
+    /// walked iteratively and every schema on them is checked once: the
+    /// counts are memoized. Any other entry, or a key that is not a string,
+    /// is an [`DecompressError::InvalidSchema`].
+    fn schema_len(&mut self, key: &str) -> Result<usize, DecompressError> {
+        let index = self.index(key)?;
+        // Schemas down to the first one already counted, with their own keys
+        let mut chain = Vec::new();
+        let mut at = index;
+        let mut len = loop {
+            if let Some(&len) = self.schema_lens.get(&at) {
+                break len;
+            }
+            let (own, base) = self.schema_link(at).map_err(|err| match err {
+                DecompressError::InvalidEntry { .. } => {
+                    DecompressError::InvalidSchema { index: at }
+                }
+                err => err,
+            })?;
+            chain.push((at, own));
+            match base {
+                Some(base) => at = base,
+                None => break 0,
+            }
+        };
+        for (at, own) in chain.into_iter().rev() {
+            len = len.saturating_add(own);
+            self.schema_lens.insert(at, len);
+        }
+        Ok(len)
+    }
+
+    /// Check the schema at `index` on its own, returning the number of keys
+    /// it adds and the index of its base schema, if it has one.
+    fn schema_link(&self, index: usize) -> Result<(usize, Option<usize>), DecompressError> {
+        let Some(refs) = self.values[index].strip_prefix("a|") else {
+            self.string(index)?;
+            return Ok((1, None));
+        };
+        if refs.is_empty() {
+            return Ok((0, None));
+        }
+        let mut own = 0;
+        let mut base = None;
+        for (i, r) in refs.split('|').enumerate() {
+            let at = self.index(r)?;
+            if i == 0 && self.values[at].starts_with("a|") {
+                if at >= index {
+                    return Err(DecompressError::InvalidSchema { index });
+                }
+                base = Some(at);
+            } else {
+                self.string(at)?;
+                own += 1;
+            }
+        }
+        Ok((own, base))
+    }
+
+    /// Resolve an object schema into its keys.
+    ///
+    /// Schemas are not part of the output, so they are resolved directly
+    /// without counting nodes, after [`schema_len`](Self::schema_len) has
+    /// checked them. Resolved key lists are memoized, so objects sharing a
+    /// schema resolve it once.
+    fn schema(&mut self, key: &str) -> Result<Rc<[String]>, DecompressError> {
+        let len = self.schema_len(key)?;
+        let index = self.index(key)?;
+        if let Some(keys) = self.schemas.get(&index) {
+            return Ok(Rc::clone(keys));
+        }
+        let values = self.values;
+        // Reference lists of the schemas down to the end of the chain
+        let mut chain = Vec::new();
+        let mut at = index;
+        let mut keys = Vec::with_capacity(len);
+        while let Some(refs) = values[at].strip_prefix("a|") {
+            if refs.is_empty() {
+                break;
+            }
+            chain.push(refs);
+            match self.index(refs.split('|').next().unwrap_or_default()) {
+                Ok(base) if values[base].starts_with("a|") => at = base,
+                _ => break,
+            }
+        }
+        if chain.is_empty() && !values[at].starts_with("a|") {
+            keys.push(self.string(at)?);
+        }
+        for refs in chain.into_iter().rev() {
+            for (i, r) in refs.split('|').enumerate() {
+                let r_at = self.index(r)?;
+                // The base's keys are already in place
+                if i > 0 || !values[r_at].starts_with("a|") {
+                    keys.push(self.string(r_at)?);
+                }
+            }
+        }
+        let keys: Rc<[String]> = keys.into();
+        self.schemas.insert(index, Rc::clone(&keys));
+        Ok(keys)
+    }
+
+    /// Decode the entry at `index`, which must be a string.
+    ///
+    /// A chunked (`c|`) string is concatenated from its chunks, each of
+    /// which must be a plain string entry.
+    fn string(&self, index: usize) -> Result<String, DecompressError> {
+        if let Some(refs) = self.values[index].strip_prefix("c|") {
+            let mut out = String::new();
+            for r in refs.split('|') {
+                let chunk = self.index(r)?;
+                match self.scalar(chunk)? {
+                    Value::String(s) => out.push_str(&s),
+                    _ => return Err(DecompressError::InvalidEntry { index: chunk }),
+                }
+            }
+            return Ok(out);
+        }
+        match self.scalar(index)? {
+            Value::String(s) => Ok(s),
+            _ => Err(DecompressError::InvalidEntry { index }),
+        }
+    }
+
+    /// Decode the entry at `index`, which must not reference other entries.
+    fn scalar(&self, index: usize) -> Result<Value, DecompressError> {
+        let v_str = self.values[index].as_str();
+        if self.config.lenient
+            && let Some(n) = v_str.strip_prefix("n|")
+        {
+            let n = lenient_num(n);
+            if !is_valid_number(&n) {
+                return Err(DecompressError::InvalidEntry { index });
+            }
+            return Ok(decode_entry(&format!("n|{n}"), &mut |_| Value::Null));
+        }
+        // Packed (`d|`) arrays reference nothing but are not scalars either
+        let malformed = ["a|", "o|", "c|", "d|"]
+            .iter()
+            .any(|p| v_str.starts_with(p))
+            || v_str
+                .strip_prefix("n|")
+                .is_some_and(|n| !is_valid_number(n))
+            || (v_str.starts_with("N|") && !matches!(v_str, "N|+" | "N|-" | "N|0"));
+        if malformed {
+            return Err(DecompressError::InvalidEntry { index });
+        }
+        Ok(decode_entry(v_str, &mut |_| Value::Null))
+    }
+
+    /// Resolve a reference key to an index within the values array.
+    fn index(&self, key: &str) -> Result<usize, DecompressError> {
+        let index = checked_s_to_int(key).ok_or_else(|| DecompressError::InvalidKey {
+            key: key.to_string(),
+        })?;
+        if index >= self.values.len() {
+            return Err(DecompressError::IndexOutOfBounds {
+                index,
+                len: self.values.len(),
+            });
+        }
+        Ok(index)
+    }
+}
+
+/// Check whether the payload of an `n|` entry is a well-formed number.
+///
+/// Rust's parsers accept a leading `+`, which JSON and other compress-json
+/// implementations do not write, so it is rejected here.
+fn is_valid_number(s: &str) -> bool {
+    if s.starts_with('+') {
+        return false;
+    }
+    if s.parse::<i64>().is_ok() || s.parse::<u64>().is_ok() {
+        return true;
+    }
+    #[cfg(feature = "arbitrary_precision")]
+    if f64_loses_precision(s) && s.parse::<Number>().is_ok() {
+        return true;
+    }
+    s.parse::<f64>().is_ok_and(f64::is_finite)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compress;
+    use serde_json::json;
+
+    #[test]
+    fn test_element_decodes_only_its_subtree() {
+        let rows: Vec<Value> = (0..10)
+            .map(|i| json!({"id": i, "tags": [i, "x"]}))
+            .collect();
+        let c = compress(&Value::Array(rows.clone()));
+        let mut decoder = CheckedDecoder::new(&c.values, &CONFIG);
+        let root = decoder.index(c.root.as_str()).unwrap();
+        assert_eq!(decoder.element(root, 5), Ok(Some(rows[5].clone())));
+        // The object, its two fields and the two tags
+        assert_eq!(decoder.nodes, 5);
+    }
+}
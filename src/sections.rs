@@ -0,0 +1,31 @@
+//! Type-grouped store layout.
+//!
+//! With [`Config::typed_sections`](crate::Config::typed_sections) the store is
+//! renumbered after compression so that entries of the same type sit in one
+//! contiguous range: numbers first, then strings, then booleans, then
+//! composites. Decoders only follow references, so the layout needs no
+//! marker in the output and any compress-json decoder reads it.
+
+use crate::core::Compressed;
+
+/// The entry order that groups a store by type.
+///
+/// Entries keep their relative order within each section, so this composes
+/// with frequency ordering.
+pub(crate) fn section_order(c: &Compressed) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..c.values.len()).collect();
+    // Stable, so each section keeps the existing order
+    order.sort_by_key(|&i| section(&c.values[i]));
+    order
+}
+
+/// Position of an entry's section in the store.
+fn section(value: &str) -> u8 {
+    match value.get(..2) {
+        Some("n|" | "N|") => 0,
+        Some("b|") => 2,
+        Some("a|" | "o|" | "c|" | "d|") => 3,
+        // Plain, escaped (`s|`) and binary (`x|`) strings
+        _ => 1,
+    }
+}
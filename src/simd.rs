@@ -0,0 +1,289 @@
+//! Compression of `simd-json` values.
+//!
+//! Pipelines that parse with [`simd_json`] can compress a
+//! [`simd_json::OwnedValue`] directly and decompress back into one, without
+//! converting the whole tree to a `serde_json::Value` on either side.
+//!
+//! The compressed form is the same as for [`compress`](crate::compress):
+//! object keys are stored in sorted order (like `serde_json`'s default map),
+//! so a document compresses to the same store whichever parser read it.
+//!
+//! # Example
+//!
+//! ```rust
+//! use compress_json_rs::{compress_simd, decompress, decompress_simd};
+//! use serde_json::json;
+//!
+//! let mut text = br#"{"name": "Alice", "tags": ["a", "b"]}"#.to_vec();
+//! let value = simd_json::to_owned_value(&mut text).unwrap();
+//!
+//! let compressed = compress_simd(&value);
+//! assert_eq!(decompress(compressed.clone()), json!({"name": "Alice", "tags": ["a", "b"]}));
+//! assert_eq!(decompress_simd(compressed), value);
+//! ```
+
+use crate::config::{CONFIG, Config};
+use crate::core::{
+    Compressed, arrange_store, check_value, decode_at, decode_entry, is_null_key, report_value,
+    root_store, schema_keys,
+};
+use crate::encode::decode_key;
+use crate::error::{CompressError, Warning};
+use crate::memory::{JsonNode, NodeKind, add_child, make_memory_with_config};
+use serde_json::{Number, Value};
+use simd_json::prelude::*;
+use simd_json::{OwnedValue, StaticNode, owned::Object};
+use std::borrow::Cow;
+
+/// Compress a `simd-json` value into its compressed form.
+///
+/// Produces the same [`Compressed`] as [`compress`](crate::compress) on the
+/// equivalent `serde_json::Value`.
+///
+/// # Arguments
+///
+/// * `o` - The value to compress
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{compress, compress_simd};
+/// use serde_json::json;
+///
+/// let value = simd_json::json!({"b": [1, 2.5, null], "a": true});
+/// assert_eq!(compress_simd(&value), compress(&json!({"b": [1, 2.5, null], "a": true})));
+/// ```
+pub fn compress_simd(o: &OwnedValue) -> Compressed {
+    compress_simd_with_config(o, &CONFIG)
+}
+
+/// Compress a `simd-json` value with a custom configuration.
+///
+/// The store is laid out as by
+/// [`compress_with_config`](crate::compress_with_config), including
+/// [`Config::sort_values`], [`Config::frequency_keys`] and
+/// [`Config::typed_sections`].
+/// [`Config::verify_on_compress`] is ignored: the input is not a
+/// `serde_json::Value` to compare the output against.
+///
+/// # Arguments
+///
+/// * `o` - The value to compress
+/// * `config` - Configuration controlling compression behavior
+///
+/// # Panics
+///
+/// Panics on NaN or infinite numbers when the configuration asks to reject
+/// them, as [`compress_with_config`](crate::compress_with_config) does.
+pub fn compress_simd_with_config(o: &OwnedValue, config: &Config) -> Compressed {
+    let mut mem = make_memory_with_config(config);
+    let root = add_child(&mut mem, o, &mut |_, _| {});
+    let compressed = Compressed {
+        values: mem.store,
+        root,
+    };
+    arrange_store(compressed, config)
+}
+
+/// Compress a `simd-json` value, reporting where a rejected value sits.
+///
+/// Unlike a `serde_json::Value`, a [`OwnedValue`] can hold NaN and infinite
+/// numbers. Where [`compress_simd_with_config`] panics on a number that the
+/// configuration rejects (`error_on_nan`, `error_on_infinite` or
+/// `error_on_precision_loss`) or a string longer than `max_value_len`, this
+/// returns an error with the JSON Pointer to the offending value.
+///
+/// # Arguments
+///
+/// * `o` - The value to compress
+/// * `config` - Configuration controlling compression behavior
+///
+/// # Returns
+///
+/// The compressed form, or a [`CompressError`] naming the first rejected
+/// value (object keys are visited in sorted order)
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{CompressError, Config, try_compress_simd};
+/// use simd_json::OwnedValue;
+///
+/// let mut value = simd_json::json!({"metrics": [{"value": 1.0}]});
+/// value["metrics"][0]["value"] = OwnedValue::from(f64::NAN);
+///
+/// let config = Config { error_on_nan: true, ..Config::default() };
+/// assert_eq!(
+///     try_compress_simd(&value, &config),
+///     Err(CompressError::UnsupportedValue {
+///         path: "/metrics/0/value".to_string(),
+///         value: "NaN".to_string(),
+///     })
+/// );
+/// ```
+pub fn try_compress_simd(o: &OwnedValue, config: &Config) -> Result<Compressed, CompressError> {
+    check_value(o, config, &mut String::new())?;
+    Ok(compress_simd_with_config(o, config))
+}
+
+/// Compress a `simd-json` value, reporting each lossy change made to it.
+///
+/// Like [`compress_with_report`](crate::compress_with_report): NaN and
+/// infinite numbers that the configuration neither preserves nor rejects
+/// are stored as `null`, and integers beyond `2^53` are rounded through
+/// `f64`; each such value gets a [`Warning`] with its path.
+///
+/// # Arguments
+///
+/// * `o` - The value to compress
+/// * `config` - Configuration controlling compression behavior
+///
+/// # Returns
+///
+/// The compressed form and the warnings (object keys are visited in sorted
+/// order), empty if nothing was lost
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{CONFIG, compress_simd_with_report};
+/// use simd_json::OwnedValue;
+///
+/// let value = OwnedValue::from(vec![OwnedValue::from(f64::INFINITY)]);
+/// let (_, warnings) = compress_simd_with_report(&value, &CONFIG);
+/// assert_eq!(warnings[0].to_string(), "Infinity at /0 replaced with null");
+/// ```
+///
+/// # Panics
+///
+/// Panics where [`compress_simd_with_config`] does.
+pub fn compress_simd_with_report(o: &OwnedValue, config: &Config) -> (Compressed, Vec<Warning>) {
+    let compressed = compress_simd_with_config(o, config);
+    let mut warnings = Vec::new();
+    report_value(o, config, &mut String::new(), &mut warnings);
+    (compressed, warnings)
+}
+
+/// Decompress into a `simd-json` value.
+///
+/// # Arguments
+///
+/// * `c` - The compressed representation
+///
+/// # Returns
+///
+/// The same value [`decompress`](crate::decompress) returns, as an
+/// [`OwnedValue`]. Numbers beyond `f64` precision (with the
+/// `arbitrary_precision` feature) are rounded to the nearest `f64`.
+///
+/// # Panics
+///
+/// Panics if the root key references an invalid index or the encoded value
+/// is malformed.
+pub fn decompress_simd(c: Compressed) -> OwnedValue {
+    let (values, root) = root_store(&c.values, c.root.as_str());
+    decode_simd(&values, root)
+}
+
+/// `simd-json` values are stored like `serde_json` ones, with object
+/// fields taken in sorted order (an [`Object`] keeps no order of its own).
+impl<'a> JsonNode<'a> for &'a OwnedValue {
+    type Array = &'a Vec<OwnedValue>;
+    type Object = Vec<(&'a String, &'a OwnedValue)>;
+    type Name = &'a String;
+    type Handle = &'a OwnedValue;
+
+    fn into_kind(self) -> NodeKind<'a, Self> {
+        match self {
+            OwnedValue::Static(StaticNode::Null) => NodeKind::Null,
+            OwnedValue::Static(StaticNode::Bool(b)) => NodeKind::Bool(*b),
+            OwnedValue::Static(StaticNode::I64(i)) => NodeKind::Int(i128::from(*i)),
+            OwnedValue::Static(StaticNode::U64(u)) => NodeKind::Int(i128::from(*u)),
+            OwnedValue::Static(n) => NodeKind::Float(n.cast_f64().unwrap_or(0.0)),
+            OwnedValue::String(s) => NodeKind::Str(Cow::Borrowed(s)),
+            OwnedValue::Array(arr) => NodeKind::Array(arr.as_ref()),
+            OwnedValue::Object(map) => {
+                let mut fields: Vec<(&String, &OwnedValue)> = map.iter().collect();
+                fields.sort_unstable_by(|a, b| a.0.cmp(b.0));
+                NodeKind::Object(fields)
+            }
+        }
+    }
+
+    fn handle(&self) -> &'a OwnedValue {
+        self
+    }
+
+    fn is_null(&self) -> bool {
+        OwnedValue::is_null(self)
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        OwnedValue::as_str(self)
+    }
+
+    fn array_ints(array: &&'a Vec<OwnedValue>) -> impl Iterator<Item = Option<i128>> {
+        array.iter().map(simd_int)
+    }
+}
+
+/// The integer a `simd-json` value holds, if it is an integer.
+fn simd_int(v: &OwnedValue) -> Option<i128> {
+    match v {
+        OwnedValue::Static(StaticNode::I64(i)) => Some(i128::from(*i)),
+        OwnedValue::Static(StaticNode::U64(u)) => Some(i128::from(*u)),
+        _ => None,
+    }
+}
+
+/// Decode the value referenced by `key` into a `simd-json` value.
+fn decode_simd(values: &[String], key: &str) -> OwnedValue {
+    if is_null_key(key) {
+        return OwnedValue::null();
+    }
+    let v_str = values[decode_key(key)].as_str();
+    if let Some(refs) = v_str.strip_prefix("a|") {
+        if refs.is_empty() {
+            return OwnedValue::array();
+        }
+        return refs.split('|').map(|k| decode_simd(values, k)).collect();
+    }
+    if let Some(rest) = v_str.strip_prefix("o|") {
+        if rest.is_empty() {
+            return OwnedValue::object();
+        }
+        let mut parts = rest.split('|');
+        let schema = parts.next().unwrap_or_default();
+        let keys = schema_keys(decode_at(values, schema));
+        let mut map = Object::with_capacity_and_hasher(keys.len(), Default::default());
+        for (k, part) in keys.into_iter().zip(parts) {
+            map.insert(k, decode_simd(values, part));
+        }
+        return OwnedValue::from(map);
+    }
+    // Scalars, chunked strings and packed arrays decode to serde_json values
+    simd_leaf(decode_entry(v_str, &mut |k| decode_at(values, k)))
+}
+
+/// The `f64` closest to a number, which is infinite for numbers beyond the
+/// `f64` range (kept only with `arbitrary_precision`).
+fn nearest_f64(n: &Number) -> f64 {
+    n.as_f64()
+        .unwrap_or_else(|| n.to_string().parse().expect("JSON numbers parse as f64"))
+}
+
+/// Convert a decoded leaf entry into a `simd-json` value.
+fn simd_leaf(leaf: Value) -> OwnedValue {
+    match leaf {
+        Value::Bool(b) => OwnedValue::from(b),
+        // Non-negative integers are `U64`, as simd-json parses them
+        Value::Number(n) => match (n.as_u64(), n.as_i64()) {
+            (Some(u), _) => OwnedValue::from(u),
+            (None, Some(i)) => OwnedValue::from(i),
+            _ => OwnedValue::from(nearest_f64(&n)),
+        },
+        Value::String(s) => OwnedValue::from(s),
+        Value::Array(items) => items.into_iter().map(simd_leaf).collect(),
+        _ => OwnedValue::null(),
+    }
+}
@@ -0,0 +1,73 @@
+//! Value-sorted key assignment.
+//!
+//! Keys are normally assigned in first-seen order, so inserting a value near
+//! the start of a document shifts the key of every value after it. With
+//! [`Config::sort_values`](crate::Config::sort_values) the store is
+//! renumbered after compression so that an entry's position depends only on
+//! its content: scalars first, ordered by their stored form, then composites
+//! ordered by a hash of their structure. A small change to the input then
+//! moves only the keys that sort after the changed values, and the same
+//! values always produce the same store.
+
+use crate::core::{Compressed, is_null_key};
+use crate::encode::decode_key;
+use crate::frequency::is_container;
+use crate::hash::{FNV_OFFSET, fnv};
+
+/// The entry order that sorts a store by content.
+///
+/// Composite entries (`a|`, `o|`, `c|` with references) are ordered by a
+/// hash that folds in the hashes of the entries they reference, so it does
+/// not depend on the keys assigned; composites with equal hashes are ordered
+/// by the hashes of their references. Only identical entries (with `dedup`
+/// off) keep their relative order.
+pub(crate) fn sorted_order(c: &Compressed) -> Vec<usize> {
+    let mut hashes = vec![0u64; c.values.len()];
+    for (i, value) in c.values.iter().enumerate() {
+        hashes[i] = entry_hash(value, &hashes);
+    }
+    let mut order: Vec<usize> = (0..c.values.len()).collect();
+    order.sort_by(|&a, &b| {
+        let (va, vb) = (&c.values[a], &c.values[b]);
+        match (is_container(va), is_container(vb)) {
+            (false, false) => va.cmp(vb),
+            (true, true) => hashes[a]
+                .cmp(&hashes[b])
+                .then_with(|| child_hashes(va, &hashes).cmp(&child_hashes(vb, &hashes))),
+            (container_a, container_b) => container_a.cmp(&container_b),
+        }
+    });
+    order
+}
+
+/// Content hash of an entry, given the hashes of the entries before it.
+///
+/// References always point at earlier entries, since a value's children
+/// are stored before it.
+fn entry_hash(value: &str, hashes: &[u64]) -> u64 {
+    if !is_container(value) {
+        return fnv(FNV_OFFSET, value.as_bytes());
+    }
+    let prefix = value.split('|').next().unwrap_or_default();
+    let mut hash = fnv(FNV_OFFSET, prefix.as_bytes());
+    for child in child_hashes(value, hashes) {
+        hash = fnv(hash, &child.to_le_bytes());
+    }
+    hash
+}
+
+/// The hashes of the entries a composite references, in order, to tell
+/// apart composites whose own hashes collide.
+fn child_hashes(value: &str, hashes: &[u64]) -> Vec<u64> {
+    value
+        .split('|')
+        .skip(1)
+        .map(|r| {
+            if is_null_key(r) {
+                fnv(FNV_OFFSET, r.as_bytes())
+            } else {
+                hashes.get(decode_key(r)).copied().unwrap_or_default()
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,228 @@
+//! Source spans for compressing JSON text.
+//!
+//! [`compress_str_with_spans`] compresses JSON text like
+//! [`compress_str_with_config`](crate::compress_str_with_config) and also
+//! reports, for each store entry, the byte range of the source text where
+//! its value first appeared. Tools can use this to point from a compressed
+//! entry back into the original document.
+//!
+//! `serde_json` does not report positions, so the parsed text is scanned a
+//! second time to find the span of every value. The text has already been
+//! validated by then, so the scanner only tracks structure.
+
+use crate::config::Config;
+use crate::core::{Compressed, finish_store};
+use crate::encode::decode_key;
+use crate::memory::{add_value_visited, make_memory_with_config, mem_to_values};
+use crate::parse::parse_json;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Source byte range of each store entry, see [`compress_str_with_spans`].
+pub type SourceSpans = Vec<Option<Range<usize>>>;
+
+/// Parse JSON text and compress it, recording where each stored value came
+/// from in the text.
+///
+/// The spans are parallel to the store: `spans[i]` is the byte range in
+/// `json` of the first value (in document order) stored as entry `i`. For
+/// strings the range includes the quotes, for arrays and objects the
+/// brackets. Entries that do not come from a single JSON value — object
+/// schemas, their key strings, and the pieces of chunked strings — have no
+/// span.
+///
+/// # Arguments
+///
+/// * `json` - JSON text to parse and compress
+/// * `config` - The configuration to parse and compress with
+///
+/// # Returns
+///
+/// The compressed form, the same as [`compress_str_with_config`] returns,
+/// and one optional span per store entry
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{CONFIG, compress_str_with_spans};
+///
+/// let json = r#"{"a": "x", "b": [1, "x"]}"#;
+/// let (compressed, spans) = compress_str_with_spans(json, &CONFIG).unwrap();
+///
+/// let x = compressed.values.iter().position(|v| v == "x").unwrap();
+/// assert_eq!(spans[x], Some(6..9));
+/// assert_eq!(&json[6..9], r#""x""#);
+/// ```
+///
+/// [`compress_str_with_config`]: crate::compress_str_with_config
+pub fn compress_str_with_spans(
+    json: &str,
+    config: &Config,
+) -> Result<(Compressed, SourceSpans), serde_json::Error> {
+    let value = parse_json(json, config)?;
+    let mut pos = 0;
+    let tree = scan_value(json, &mut pos);
+    let mut node_spans = HashMap::new();
+    collect_spans(&value, &tree, &mut node_spans);
+
+    let mut mem = make_memory_with_config(config);
+    let mut spans = SourceSpans::new();
+    let root = add_value_visited(&mut mem, &value, &mut |node, key| {
+        let Some(span) = node_spans.get(&(node as *const Value)) else {
+            return;
+        };
+        let index = decode_key(key.as_str());
+        if spans.len() <= index {
+            spans.resize(index + 1, None);
+        }
+        // Objects are visited in map order, so keep the earliest in the text
+        let slot = &mut spans[index];
+        if slot.as_ref().is_none_or(|s| span.start < s.start) {
+            *slot = Some(span.clone());
+        }
+    });
+    let values = mem_to_values(&mem);
+    spans.resize(values.len(), None);
+
+    let (compressed, order) =
+        finish_store(&value, Compressed { values, root }, config).unwrap_or_else(|e| panic!("{e}"));
+    if let Some(order) = order {
+        spans = reorder(spans, &order);
+    }
+    Ok((compressed, spans))
+}
+
+/// Apply the entry order of [`renumber`] to the spans.
+fn reorder(mut spans: SourceSpans, order: &[usize]) -> SourceSpans {
+    order.iter().map(|&i| spans[i].take()).collect()
+}
+
+/// A value's span in the source text, with the spans of its children.
+struct SpanNode {
+    span: Range<usize>,
+    children: Children,
+}
+
+/// Children of a [`SpanNode`], in text order.
+enum Children {
+    None,
+    Array(Vec<SpanNode>),
+    Object(Vec<(String, SpanNode)>),
+}
+
+/// Record the span of every node of `value` by its address.
+fn collect_spans(value: &Value, node: &SpanNode, out: &mut HashMap<*const Value, Range<usize>>) {
+    out.insert(value as *const Value, node.span.clone());
+    match (value, &node.children) {
+        (Value::Array(items), Children::Array(nodes)) => {
+            for (item, child) in items.iter().zip(nodes) {
+                collect_spans(item, child, out);
+            }
+        }
+        (Value::Object(map), Children::Object(fields)) => {
+            // A repeated key keeps its last value, as when parsing
+            let by_name: HashMap<&str, &SpanNode> =
+                fields.iter().map(|(name, n)| (name.as_str(), n)).collect();
+            for (k, v) in map {
+                if let Some(child) = by_name.get(k.as_str()) {
+                    collect_spans(v, child, out);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Scan the value starting at `pos` in already validated JSON text.
+fn scan_value(json: &str, pos: &mut usize) -> SpanNode {
+    let b = json.as_bytes();
+    skip_ws(b, pos);
+    let start = *pos;
+    let children = match b[*pos] {
+        b'[' => {
+            *pos += 1;
+            let mut items = Vec::new();
+            loop {
+                skip_ws(b, pos);
+                match b[*pos] {
+                    b']' => break,
+                    b',' => *pos += 1,
+                    _ => items.push(scan_value(json, pos)),
+                }
+            }
+            *pos += 1;
+            Children::Array(items)
+        }
+        b'{' => {
+            *pos += 1;
+            let mut fields = Vec::new();
+            loop {
+                skip_ws(b, pos);
+                match b[*pos] {
+                    b'}' => break,
+                    b',' => *pos += 1,
+                    _ => {
+                        let key_start = *pos;
+                        skip_string(b, pos);
+                        let name = key_text(&json[key_start..*pos]);
+                        skip_ws(b, pos);
+                        // The `:`
+                        *pos += 1;
+                        fields.push((name, scan_value(json, pos)));
+                    }
+                }
+            }
+            *pos += 1;
+            Children::Object(fields)
+        }
+        b'"' => {
+            skip_string(b, pos);
+            Children::None
+        }
+        _ => {
+            // Numbers and literals run until the next delimiter
+            while *pos < b.len()
+                && !matches!(b[*pos], b',' | b']' | b'}' | b' ' | b'\t' | b'\n' | b'\r')
+            {
+                *pos += 1;
+            }
+            Children::None
+        }
+    };
+    SpanNode {
+        span: start..*pos,
+        children,
+    }
+}
+
+/// Move `pos` past the string literal starting at it.
+fn skip_string(b: &[u8], pos: &mut usize) {
+    *pos += 1;
+    loop {
+        match b[*pos] {
+            b'\\' => *pos += 2,
+            b'"' => {
+                *pos += 1;
+                return;
+            }
+            _ => *pos += 1,
+        }
+    }
+}
+
+/// The key a quoted object key stands for.
+fn key_text(raw: &str) -> String {
+    if raw.contains('\\') {
+        serde_json::from_str(raw).unwrap_or_default()
+    } else {
+        raw[1..raw.len() - 1].to_string()
+    }
+}
+
+/// Move `pos` past JSON whitespace.
+fn skip_ws(b: &[u8], pos: &mut usize) {
+    while *pos < b.len() && matches!(b[*pos], b' ' | b'\t' | b'\n' | b'\r') {
+        *pos += 1;
+    }
+}
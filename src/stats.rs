@@ -0,0 +1,133 @@
+//! Size statistics gathered while compressing.
+//!
+//! Deduplication only pays off when values repeat. For small or
+//! high-entropy inputs, the store and its references can serialize larger
+//! than the original JSON. [`compress_with_stats`] reports both sizes so a
+//! caching layer can keep the original instead.
+//!
+//! # Example
+//!
+//! ```rust
+//! use compress_json_rs::{CONFIG, compress_with_stats};
+//! use serde_json::json;
+//!
+//! let data = json!({"id": 7});
+//! let (compressed, stats) = compress_with_stats(&data, &CONFIG);
+//! let stored = if stats.expanded {
+//!     data.to_string()
+//! } else {
+//!     serde_json::to_string(&compressed).unwrap()
+//! };
+//! assert!(stored.len() <= stats.raw_bytes);
+//! ```
+//!
+//! The stats also break the store down by entry type, to show whether
+//! strings or objects dominate a payload.
+
+use crate::config::Config;
+use crate::core::{Compressed, compress_with_config};
+use crate::encode::decode_key;
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// Serialized sizes of a document before and after compression, in bytes,
+/// and the number of store entries of each type.
+///
+/// Every entry is counted in exactly one type, so the counts sum to
+/// `values.len()`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CompressStats {
+    /// Size of the document serialized as JSON
+    pub raw_bytes: usize,
+    /// Size of the compressed `[values, root]` form serialized as JSON
+    pub compressed_bytes: usize,
+    /// Whether the compressed form is larger than the raw JSON
+    pub expanded: bool,
+    /// Number of boolean (`b|`) entries
+    pub booleans: usize,
+    /// Number of number (`n|`, `N|`) entries
+    pub numbers: usize,
+    /// Number of string entries, including escaped (`s|`), chunked (`c|`)
+    /// and binary (`x|`) ones and strings used as single-key schemas
+    pub strings: usize,
+    /// Number of array entries (`a|`, or packed `d|`) not used as an object
+    /// schema
+    pub arrays: usize,
+    /// Number of object (`o|`) entries
+    pub objects: usize,
+    /// Number of array entries used as an object schema
+    pub schemas: usize,
+}
+
+/// Compress a value and measure whether compression made it smaller.
+///
+/// Both sizes are of compact JSON serializations, so they compare what would
+/// actually be stored. Serializing twice makes this slower than
+/// [`compress_with_config`]; use it where the decision is needed.
+///
+/// # Arguments
+///
+/// * `o` - The value to compress
+/// * `config` - Configuration controlling compression behavior
+///
+/// # Returns
+///
+/// The compressed form and its [`CompressStats`]
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{CONFIG, compress_with_stats};
+/// use serde_json::json;
+///
+/// let rows = json!([{"status": "active"}, {"status": "active"}, {"status": "active"}]);
+/// let (_, stats) = compress_with_stats(&rows, &CONFIG);
+/// assert!(!stats.expanded);
+/// assert!(stats.compressed_bytes < stats.raw_bytes);
+/// ```
+///
+/// # Panics
+///
+/// Panics where [`compress_with_config`] does.
+pub fn compress_with_stats(o: &Value, config: &Config) -> (Compressed, CompressStats) {
+    let compressed = compress_with_config(o, config);
+    let raw_bytes = o.to_string().len();
+    let compressed_bytes = serde_json::to_string(&compressed)
+        .expect("serialize compressed")
+        .len();
+    let mut stats = CompressStats {
+        raw_bytes,
+        compressed_bytes,
+        expanded: compressed_bytes > raw_bytes,
+        booleans: 0,
+        numbers: 0,
+        strings: 0,
+        arrays: 0,
+        objects: 0,
+        schemas: 0,
+    };
+    count_types(&compressed.values, &mut stats);
+    (compressed, stats)
+}
+
+/// Tally the store entries by type into `stats`.
+fn count_types(values: &[String], stats: &mut CompressStats) {
+    // The schema is the first reference of every non-empty object
+    let schemas: HashSet<usize> = values
+        .iter()
+        .filter_map(|v| v.strip_prefix("o|"))
+        .filter(|rest| !rest.is_empty())
+        .map(|rest| decode_key(rest.split('|').next().unwrap_or_default()))
+        .collect();
+    for (index, value) in values.iter().enumerate() {
+        let count = match value.get(..2) {
+            Some("b|") => &mut stats.booleans,
+            Some("n|" | "N|") => &mut stats.numbers,
+            Some("a|") if schemas.contains(&index) => &mut stats.schemas,
+            Some("a|" | "d|") => &mut stats.arrays,
+            Some("o|") => &mut stats.objects,
+            _ => &mut stats.strings,
+        };
+        *count += 1;
+    }
+}
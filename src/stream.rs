@@ -0,0 +1,297 @@
+//! Incremental compression of arrays.
+//!
+//! [`ArrayCompressor`] lets producers that generate array elements over time
+//! compress them one by one into a single shared store, without ever holding
+//! the whole array `Value` in memory.
+//!
+//! # Example
+//!
+//! ```rust
+//! use compress_json_rs::{ArrayCompressor, compress, decompress};
+//! use serde_json::json;
+//!
+//! let mut array = ArrayCompressor::new();
+//! for i in 0..3 {
+//!     array.push(&json!({"id": i, "kind": "event"}));
+//! }
+//! let compressed = array.finish();
+//!
+//! let expected = json!([
+//!     {"id": 0, "kind": "event"},
+//!     {"id": 1, "kind": "event"},
+//!     {"id": 2, "kind": "event"},
+//! ]);
+//! assert_eq!(compressed, compress(&expected));
+//! assert_eq!(decompress(compressed), expected);
+//! ```
+//!
+//! [`ArrayWriter`] goes further for very large arrays: store entries are
+//! written to an [`io::Write`] sink as soon as they are created, so only the
+//! deduplication caches stay in memory.
+
+use crate::config::{CONFIG, Config};
+use crate::core::{Compressed, arrange_store, verify_entry};
+use crate::error::CompressError;
+use crate::key::Key;
+use crate::memory::{
+    Memory, add_value, dictionary_len, get_value_key_owned, make_memory_with_config, mem_to_values,
+};
+use serde_json::Value;
+use std::io::{self, Write};
+
+/// Builds the compressed form of an array one element at a time.
+///
+/// Elements share one [`Memory`], so values and schemas repeated across
+/// elements are stored once, exactly as with [`compress`](crate::compress)
+/// on the complete array. The store passes of the configuration
+/// ([`Config::sort_values`], [`Config::frequency_keys`],
+/// [`Config::typed_sections`]) run in [`finish`](ArrayCompressor::finish);
+/// with [`Config::verify_on_compress`], each element is checked as it is
+/// pushed, since the array itself is never held.
+pub struct ArrayCompressor {
+    /// Store shared by all elements
+    mem: Memory,
+    /// Array entry assembled so far (`a|<ref>|<ref>...`)
+    refs: String,
+    /// Number of elements pushed
+    len: usize,
+}
+
+impl ArrayCompressor {
+    /// Create an array compressor with the default configuration.
+    pub fn new() -> Self {
+        Self::with_config(&CONFIG)
+    }
+
+    /// Create an array compressor with a custom configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Configuration applied to every pushed element
+    pub fn with_config(config: &Config) -> Self {
+        ArrayCompressor {
+            mem: make_memory_with_config(config),
+            refs: String::from("a"),
+            len: 0,
+        }
+    }
+
+    /// Compress one element and append it to the array.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The next array element
+    ///
+    /// # Panics
+    ///
+    /// Panics where [`compress_with_config`](crate::compress_with_config)
+    /// does, and with [`Config::verify_on_compress`] if the element does not
+    /// decompress to `value`.
+    pub fn push(&mut self, value: &Value) {
+        let key = if value.is_null() {
+            Key::from("_")
+        } else {
+            add_value(&mut self.mem, value)
+        };
+        if self.mem.config.verify_on_compress
+            && !value.is_null()
+            && let Err(CompressError::VerifyFailed { path }) =
+                verify_entry(value, &self.mem.store, &key, &self.mem.config)
+        {
+            let path = format!("/{}{path}", self.len);
+            panic!("{}", CompressError::VerifyFailed { path });
+        }
+        self.refs.push('|');
+        self.refs.push_str(key.as_str());
+        self.len += 1;
+    }
+
+    /// Number of elements pushed so far.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether no elements have been pushed yet.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Store the array entry, apply the store passes of the configuration
+    /// and return the compressed form of the array.
+    pub fn finish(mut self) -> Compressed {
+        if self.len == 0 {
+            self.refs.push('|');
+        }
+        let root = get_value_key_owned(&mut self.mem, self.refs);
+        let values = mem_to_values(&self.mem);
+        arrange_store(Compressed { values, root }, &self.mem.config)
+    }
+}
+
+impl Default for ArrayCompressor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Compresses an array one element at a time straight into a writer.
+///
+/// Works like [`ArrayCompressor`], but after each element the store entries
+/// it created are written to the sink and dropped; only the caches used for
+/// deduplication stay in memory. The output is the usual serialized
+/// [`Compressed`] form, `[[values...],"root"]`, with the root key written
+/// last by [`finish`](ArrayWriter::finish).
+///
+/// Entries are final once written, so nothing can be renumbered afterwards:
+/// [`Config::frequency_keys`], [`Config::sort_values`],
+/// [`Config::typed_sections`] and [`Config::verify_on_compress`] are ignored.
+/// Wrap unbuffered sinks (files, sockets) in an [`io::BufWriter`].
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{ArrayWriter, Compressed, decompress};
+/// use serde_json::json;
+///
+/// let mut array = ArrayWriter::new(Vec::new());
+/// array.push(&json!({"id": 1})).unwrap();
+/// array.push(&json!({"id": 2})).unwrap();
+/// let bytes = array.finish().unwrap();
+///
+/// let compressed: Compressed = serde_json::from_slice(&bytes).unwrap();
+/// assert_eq!(decompress(compressed), json!([{"id": 1}, {"id": 2}]));
+/// ```
+pub struct ArrayWriter<W: Write> {
+    /// Store whose entries go to the sink as they are created
+    out: StoreWriter<W>,
+    /// Array entry assembled so far (`a|<ref>|<ref>...`)
+    refs: String,
+    /// Number of elements pushed
+    len: usize,
+}
+
+impl<W: Write> ArrayWriter<W> {
+    /// Create an array writer with the default configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Sink for the serialized compressed array
+    pub fn new(writer: W) -> Self {
+        Self::with_config(writer, &CONFIG)
+    }
+
+    /// Create an array writer with a custom configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Sink for the serialized compressed array
+    /// * `config` - Configuration applied to every pushed element
+    pub fn with_config(writer: W, config: &Config) -> Self {
+        ArrayWriter {
+            out: StoreWriter::new(writer, config),
+            refs: String::from("a"),
+            len: 0,
+        }
+    }
+
+    /// Compress one element, append it to the array and write its new
+    /// store entries.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The next array element
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())`, or the error from the writer
+    pub fn push(&mut self, value: &Value) -> io::Result<()> {
+        let key = if value.is_null() {
+            Key::from("_")
+        } else {
+            add_value(&mut self.out.mem, value)
+        };
+        self.refs.push('|');
+        self.refs.push_str(key.as_str());
+        self.len += 1;
+        self.out.write_pending()
+    }
+
+    /// Number of elements pushed so far.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether no elements have been pushed yet.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of values held for deduplication, which is all that stays in
+    /// memory between elements; see [`Config::max_dictionary_len`].
+    pub fn dictionary_len(&self) -> usize {
+        dictionary_len(&self.out.mem)
+    }
+
+    /// Store the array entry, write it and the root key, and return the
+    /// writer.
+    ///
+    /// # Returns
+    ///
+    /// The writer, or the error from writing to it
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.len == 0 {
+            self.refs.push('|');
+        }
+        let root = get_value_key_owned(&mut self.out.mem, self.refs);
+        self.out.finish(&root)
+    }
+}
+
+/// A memory whose new store entries are written out and dropped.
+///
+/// Serializes the `[[values...],"root"]` form incrementally: entries are
+/// written by [`write_pending`](StoreWriter::write_pending) in key order,
+/// and [`finish`](StoreWriter::finish) closes the values array and writes
+/// the root key.
+pub(crate) struct StoreWriter<W: Write> {
+    /// Caches for deduplication; the store only holds unwritten entries
+    pub(crate) mem: Memory,
+    /// Sink receiving the serialized entries
+    writer: W,
+    /// Number of entries written so far
+    written: usize,
+}
+
+impl<W: Write> StoreWriter<W> {
+    /// Create a store writer with an empty memory.
+    pub(crate) fn new(writer: W, config: &Config) -> Self {
+        StoreWriter {
+            mem: make_memory_with_config(config),
+            writer,
+            written: 0,
+        }
+    }
+
+    /// Write the entries stored since the last call and drop them.
+    pub(crate) fn write_pending(&mut self) -> io::Result<()> {
+        for entry in self.mem.store.drain(..) {
+            let sep: &[u8] = if self.written == 0 { b"[[" } else { b"," };
+            self.writer.write_all(sep)?;
+            serde_json::to_writer(&mut self.writer, &entry)?;
+            self.written += 1;
+        }
+        Ok(())
+    }
+
+    /// Write any remaining entries and the root key, and return the writer.
+    pub(crate) fn finish(mut self, root: &Key) -> io::Result<W> {
+        self.write_pending()?;
+        if self.written == 0 {
+            self.writer.write_all(b"[[")?;
+        }
+        self.writer.write_all(b"],")?;
+        serde_json::to_writer(&mut self.writer, root.as_str())?;
+        self.writer.write_all(b"]")?;
+        Ok(self.writer)
+    }
+}
@@ -0,0 +1,396 @@
+//! Streaming compression of JSON text.
+//!
+//! Available with the `serde_transcode` feature.
+//!
+//! [`transcode`] reads JSON from an [`io::Read`](std::io::Read) and writes the compressed
+//! `[[values...],"root"]` form to an [`io::Write`](std::io::Write) without ever building a
+//! `serde_json::Value`: [`serde_transcode`] drives `serde_json`'s streaming
+//! deserializer straight into a serializer that adds each value to the
+//! store, and new store entries are written out as soon as their container
+//! is complete. Memory use is bounded by the deduplication caches and the
+//! nesting currently open, not by the size of the document.
+//!
+//! # Output Layout
+//!
+//! The output decodes to the same value as [`compress`](crate::compress),
+//! but the store can be laid out differently: values are added in document
+//! order, and object schemas list keys in document order unless
+//! `sort_key` is set. Entries are final once written, so `frequency_keys`,
+//! `sort_values`, `typed_sections` and `verify_on_compress` are ignored.
+//! Elements are stored before their array is complete, so
+//! `pack_int_arrays` is ignored too.
+//!
+//! With `strict_duplicate_keys`, an object that repeats a key fails the
+//! transcode, as does a string or object key longer than `max_value_len`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use compress_json_rs::{Compressed, decompress, transcode};
+//! use serde_json::json;
+//!
+//! let input = br#"{"users": [{"name": "Alice"}, {"name": "Bob"}]}"#;
+//! let bytes = transcode(&input[..], Vec::new()).unwrap();
+//!
+//! let compressed: Compressed = serde_json::from_slice(&bytes).unwrap();
+//! assert_eq!(
+//!     decompress(compressed),
+//!     json!({"users": [{"name": "Alice"}, {"name": "Bob"}]})
+//! );
+//! ```
+
+use crate::config::{CONFIG, Config};
+use crate::encode::encode_bool;
+use crate::key::Key;
+use crate::memory::{
+    add_f64, add_object, add_str, add_value, exceeds_max_value_len, get_value_key,
+    get_value_key_owned,
+};
+#[cfg(feature = "arbitrary_precision")]
+use crate::parse::NUMBER_TOKEN;
+use crate::stream::StoreWriter;
+use serde::ser::{self, Impossible, Serialize};
+use serde_json::{Number, Value};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+/// Compress JSON text from a reader into a writer.
+///
+/// # Arguments
+///
+/// * `reader` - Source of the JSON text
+/// * `writer` - Sink for the serialized compressed form
+///
+/// # Returns
+///
+/// The writer, or the error from parsing or writing
+pub fn transcode<R: Read, W: Write>(reader: R, writer: W) -> Result<W, serde_json::Error> {
+    transcode_with_config(reader, writer, &CONFIG)
+}
+
+/// Compress JSON text from a reader into a writer with a custom
+/// configuration.
+///
+/// # Arguments
+///
+/// * `reader` - Source of the JSON text
+/// * `writer` - Sink for the serialized compressed form
+/// * `config` - Configuration controlling compression behavior
+///
+/// # Returns
+///
+/// The writer, or the error from parsing or writing. Wrap unbuffered
+/// readers and writers in [`BufReader`](std::io::BufReader) / [`BufWriter`](std::io::BufWriter).
+pub fn transcode_with_config<R: Read, W: Write>(
+    reader: R,
+    writer: W,
+    config: &Config,
+) -> Result<W, serde_json::Error> {
+    let mut out = StoreWriter::new(writer, config);
+    let mut de = serde_json::Deserializer::from_reader(reader);
+    let root = serde_transcode::transcode(&mut de, EntrySerializer { out: &mut out })?;
+    de.end()?;
+    out.finish(&root).map_err(serde_json::Error::io)
+}
+
+/// Serializer that adds the value it receives to the store and returns its key.
+struct EntrySerializer<'a, W: Write> {
+    out: &'a mut StoreWriter<W>,
+}
+
+/// Builds an `a|` entry from the elements of a sequence.
+struct SeqBuilder<'a, W: Write> {
+    out: &'a mut StoreWriter<W>,
+    /// Array entry assembled so far (`a|<ref>|<ref>...`)
+    acc: String,
+}
+
+/// Builds an `o|` entry from the fields of a map.
+struct MapBuilder<'a, W: Write> {
+    out: &'a mut StoreWriter<W>,
+    /// Field names in document order
+    keys: Vec<String>,
+    /// Field value keys, parallel to `keys`
+    refs: Vec<Key>,
+    /// Position of each field name, so a repeated key keeps its last value
+    index: HashMap<String, usize>,
+    /// Name of the field whose value comes next
+    pending: Option<String>,
+    /// Number text, when this map is `serde_json`'s number wrapper
+    number: Option<String>,
+}
+
+impl<W: Write> EntrySerializer<'_, W> {
+    /// Store a scalar through [`add_value`].
+    fn add(self, value: &Value) -> Result<Key, serde_json::Error> {
+        Ok(add_value(&mut self.out.mem, value))
+    }
+}
+
+/// Reject a string longer than [`Config::max_value_len`], if so configured.
+fn check_len(config: &Config, s: &str) -> Result<(), serde_json::Error> {
+    match exceeds_max_value_len(config, s) {
+        Some((len, limit)) => Err(ser::Error::custom(format!(
+            "string of {len} bytes exceeds max_value_len {limit}"
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Error for Serde data types that JSON text never produces.
+fn unsupported(kind: &str) -> serde_json::Error {
+    ser::Error::custom(format!("cannot transcode {kind} from JSON"))
+}
+
+impl<'a, W: Write> ser::Serializer for EntrySerializer<'a, W> {
+    type Ok = Key;
+    type Error = serde_json::Error;
+    type SerializeSeq = SeqBuilder<'a, W>;
+    type SerializeTuple = Impossible<Key, serde_json::Error>;
+    type SerializeTupleStruct = Impossible<Key, serde_json::Error>;
+    type SerializeTupleVariant = Impossible<Key, serde_json::Error>;
+    type SerializeMap = MapBuilder<'a, W>;
+    type SerializeStruct = Impossible<Key, serde_json::Error>;
+    type SerializeStructVariant = Impossible<Key, serde_json::Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<Key, serde_json::Error> {
+        Ok(get_value_key(&mut self.out.mem, &encode_bool(v)))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Key, serde_json::Error> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Key, serde_json::Error> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Key, serde_json::Error> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Key, serde_json::Error> {
+        self.add(&Value::from(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Key, serde_json::Error> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Key, serde_json::Error> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Key, serde_json::Error> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Key, serde_json::Error> {
+        self.add(&Value::from(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Key, serde_json::Error> {
+        self.serialize_f64(f64::from(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Key, serde_json::Error> {
+        Ok(add_f64(&mut self.out.mem, v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Key, serde_json::Error> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Key, serde_json::Error> {
+        check_len(&self.out.mem.config, v)?;
+        Ok(add_str(&mut self.out.mem, v))
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Key, serde_json::Error> {
+        Err(unsupported("bytes"))
+    }
+
+    fn serialize_none(self) -> Result<Key, serde_json::Error> {
+        Ok(Key::default())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Key, serde_json::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Key, serde_json::Error> {
+        Ok(Key::default())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Key, serde_json::Error> {
+        Ok(Key::default())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+    ) -> Result<Key, serde_json::Error> {
+        Err(unsupported("an enum"))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Key, serde_json::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Key, serde_json::Error> {
+        Err(unsupported("an enum"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<SeqBuilder<'a, W>, serde_json::Error> {
+        Ok(SeqBuilder {
+            out: self.out,
+            acc: String::from("a"),
+        })
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, serde_json::Error> {
+        Err(unsupported("a tuple"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, serde_json::Error> {
+        Err(unsupported("a tuple struct"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, serde_json::Error> {
+        Err(unsupported("an enum"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<MapBuilder<'a, W>, serde_json::Error> {
+        Ok(MapBuilder {
+            out: self.out,
+            keys: Vec::new(),
+            refs: Vec::new(),
+            index: HashMap::new(),
+            pending: None,
+            number: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, serde_json::Error> {
+        Err(unsupported("a struct"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, serde_json::Error> {
+        Err(unsupported("an enum"))
+    }
+}
+
+impl<W: Write> ser::SerializeSeq for SeqBuilder<'_, W> {
+    type Ok = Key;
+    type Error = serde_json::Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), serde_json::Error> {
+        let key = value.serialize(EntrySerializer { out: self.out })?;
+        self.acc.push('|');
+        self.acc
+            .push_str(if key.is_null() { "_" } else { key.as_str() });
+        self.out.write_pending().map_err(serde_json::Error::io)
+    }
+
+    fn end(mut self) -> Result<Key, serde_json::Error> {
+        if self.acc == "a" {
+            self.acc.push('|');
+        }
+        Ok(get_value_key_owned(&mut self.out.mem, self.acc))
+    }
+}
+
+impl<W: Write> ser::SerializeMap for MapBuilder<'_, W> {
+    type Ok = Key;
+    type Error = serde_json::Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), serde_json::Error> {
+        match key.serialize(serde_json::value::Serializer)? {
+            Value::String(name) => {
+                check_len(&self.out.mem.config, &name)?;
+                self.pending = Some(name);
+                Ok(())
+            }
+            _ => Err(ser::Error::custom("object key must be a string")),
+        }
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), serde_json::Error> {
+        let name = self.pending.take().unwrap_or_default();
+        // With `arbitrary_precision`, serde_json hands numbers over as a
+        // single-entry map holding the number's text
+        #[cfg(feature = "arbitrary_precision")]
+        if name == NUMBER_TOKEN && self.keys.is_empty() {
+            if let Value::String(text) = value.serialize(serde_json::value::Serializer)? {
+                self.number = Some(text);
+            }
+            return Ok(());
+        }
+        if self.out.mem.config.strict_duplicate_keys && self.index.contains_key(&name) {
+            return Err(ser::Error::custom(format!("duplicate key `{name}`")));
+        }
+        let key = value.serialize(EntrySerializer { out: self.out })?;
+        match self.index.get(&name) {
+            Some(&i) => self.refs[i] = key,
+            None => {
+                self.index.insert(name.clone(), self.keys.len());
+                self.keys.push(name);
+                self.refs.push(key);
+            }
+        }
+        self.out.write_pending().map_err(serde_json::Error::io)
+    }
+
+    fn end(self) -> Result<Key, serde_json::Error> {
+        let mem = &mut self.out.mem;
+        if let Some(text) = self.number {
+            let number: Number = text.parse().map_err(ser::Error::custom)?;
+            return Ok(add_value(mem, &Value::Number(number)));
+        }
+        let fields = self.keys.into_iter().zip(self.refs);
+        Ok(add_object(
+            mem,
+            fields,
+            |key, _| key.is_null(),
+            |_, key| key,
+        ))
+    }
+}
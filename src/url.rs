@@ -0,0 +1,74 @@
+//! URL-safe state tokens.
+//!
+//! Available with the `base64` feature.
+//!
+//! Web applications often keep shareable state, such as filters or an editor
+//! document, in a URL query parameter. [`compress_to_urlsafe`] compresses a
+//! value and encodes its `[values, root]` form as unpadded base64url
+//! (RFC 4648, section 5), which uses only `A-Z`, `a-z`, `0-9`, `-` and `_`
+//! and so needs no percent-encoding. [`decompress_from_urlsafe`] reverses
+//! it.
+//!
+//! Tokens arrive from whoever crafted the URL, so decoding never panics: it
+//! uses [`decompress_safe`] and its decode limits, and reports malformed
+//! tokens as a [`DecompressError`].
+//!
+//! # Example
+//!
+//! ```rust
+//! use compress_json_rs::{compress_to_urlsafe, decompress_from_urlsafe};
+//! use serde_json::json;
+//!
+//! let state = json!({"filters": ["open", "mine"], "page": 2});
+//! let token = compress_to_urlsafe(&state);
+//! let url = format!("https://example.com/issues?state={token}");
+//!
+//! let token = url.split("state=").nth(1).unwrap();
+//! assert_eq!(decompress_from_urlsafe(token), Ok(state));
+//! ```
+
+use crate::core::Compressed;
+use crate::error::DecompressError;
+use crate::safe::decompress_safe;
+use crate::wire::compress_to_writer_string;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use serde_json::Value;
+
+/// Compress a JSON value into a base64url token.
+///
+/// # Arguments
+///
+/// * `o` - A reference to the JSON value to compress
+///
+/// # Returns
+///
+/// The serialized compressed form, base64url-encoded without padding
+pub fn compress_to_urlsafe(o: &Value) -> String {
+    let mut json = String::new();
+    compress_to_writer_string(o, &mut json);
+    URL_SAFE_NO_PAD.encode(json)
+}
+
+/// Decompress a token produced by [`compress_to_urlsafe`].
+///
+/// # Arguments
+///
+/// * `token` - The base64url text, without padding
+///
+/// # Returns
+///
+/// The decompressed value, or [`DecompressError::Parse`] if the token is
+/// not base64url-encoded compressed data, or the error
+/// [`decompress_safe`] reports for its contents
+pub fn decompress_from_urlsafe(token: &str) -> Result<Value, DecompressError> {
+    let json = URL_SAFE_NO_PAD
+        .decode(token)
+        .map_err(|e| DecompressError::Parse {
+            message: e.to_string(),
+        })?;
+    let c: Compressed = serde_json::from_slice(&json).map_err(|e| DecompressError::Parse {
+        message: e.to_string(),
+    })?;
+    decompress_safe(&c)
+}
@@ -0,0 +1,205 @@
+//! Event-based (SAX-style) decompression.
+//!
+//! Instead of building a `serde_json::Value`, [`decompress_visit`] walks the
+//! compressed store and reports each node to a [`DecodeVisitor`]. This lets
+//! consumers build their own in-memory representation, filter data, or
+//! compute aggregates without allocating the full tree.
+//!
+//! # Events
+//!
+//! | Event | Emitted for |
+//! |-------|-------------|
+//! | `null` | `null` values (including array `_` markers) |
+//! | `bool` | Booleans |
+//! | `number` | Numbers |
+//! | `string` | Strings (already unescaped) |
+//! | `begin_array` / `end_array` | Arrays, with the element count |
+//! | `begin_object` / `key` / `end_object` | Objects, with the key count |
+//!
+//! Every method has an empty default, so visitors only implement the
+//! events they care about.
+//!
+//! # Example
+//!
+//! ```rust
+//! use compress_json_rs::{DecodeVisitor, compress, decompress_visit};
+//! use serde_json::json;
+//!
+//! struct StringCollector(Vec<String>);
+//!
+//! impl DecodeVisitor for StringCollector {
+//!     fn string(&mut self, s: &str) {
+//!         self.0.push(s.to_string());
+//!     }
+//! }
+//!
+//! let compressed = compress(&json!({"name": "Alice", "tags": ["a", "b"]}));
+//! let mut collector = StringCollector(Vec::new());
+//! decompress_visit(&compressed, &mut collector);
+//!
+//! assert_eq!(collector.0, ["Alice", "a", "b"]);
+//! ```
+
+use crate::core::{Compressed, decode_at, decode_entry, is_null_key, root_store, schema_keys};
+use crate::encode::decode_key;
+use serde_json::{Number, Value};
+use std::collections::HashMap;
+
+/// Receiver of decompression events.
+///
+/// Nodes are reported depth-first in document order; containers are
+/// bracketed by their `begin_*`/`end_*` events.
+pub trait DecodeVisitor {
+    /// Called for a `null` value.
+    fn null(&mut self) {}
+
+    /// Called for a boolean value.
+    fn bool(&mut self, _b: bool) {}
+
+    /// Called for a number value.
+    fn number(&mut self, _n: &Number) {}
+
+    /// Called for a string value.
+    fn string(&mut self, _s: &str) {}
+
+    /// Called before the elements of an array with `len` elements.
+    fn begin_array(&mut self, _len: usize) {}
+
+    /// Called after the last element of an array.
+    fn end_array(&mut self) {}
+
+    /// Called before the entries of an object with `len` keys.
+    fn begin_object(&mut self, _len: usize) {}
+
+    /// Called before each object value with the entry's key.
+    fn key(&mut self, _key: &str) {}
+
+    /// Called after the last entry of an object.
+    fn end_object(&mut self) {}
+}
+
+/// Walk a compressed representation, reporting each node to `visitor`.
+///
+/// Nodes are visited depth-first in document order, exactly as
+/// [`decompress`](crate::decompress) would produce them.
+///
+/// # Arguments
+///
+/// * `c` - The compressed representation to walk
+/// * `visitor` - Receiver of the decoding events
+///
+/// # Panics
+///
+/// Panics if a key references an invalid index or an entry is malformed.
+pub fn decompress_visit<V: DecodeVisitor>(c: &Compressed, visitor: &mut V) {
+    let (values, root) = root_store(&c.values, c.root.as_str());
+    visit_key(&values, root, visitor);
+}
+
+/// Report the value referenced by `key` and its descendants.
+fn visit_key<V: DecodeVisitor>(values: &[String], key: &str, visitor: &mut V) {
+    if is_null_key(key) {
+        visitor.null();
+        return;
+    }
+    let v_str = &values[decode_key(key)];
+    if let Some(refs) = v_str.strip_prefix("a|") {
+        let refs: Vec<&str> = if refs.is_empty() {
+            Vec::new()
+        } else {
+            refs.split('|').collect()
+        };
+        visitor.begin_array(refs.len());
+        for r in refs {
+            visit_key(values, r, visitor);
+        }
+        visitor.end_array();
+    } else if v_str.starts_with("o|") {
+        if v_str == "o|" {
+            visitor.begin_object(0);
+            visitor.end_object();
+            return;
+        }
+        let parts: Vec<&str> = v_str.split('|').collect();
+        let keys = schema_keys(decode_at(values, parts[1]));
+        visitor.begin_object(parts.len() - 2);
+        for (key, r) in keys.iter().zip(&parts[2..]) {
+            visitor.key(key);
+            visit_key(values, r, visitor);
+        }
+        visitor.end_object();
+    } else {
+        // Leaf entry (chunked strings resolve their chunks through `decode`)
+        match decode_entry(v_str, &mut |k| decode_at(values, k)) {
+            Value::Null => visitor.null(),
+            Value::Bool(b) => visitor.bool(b),
+            Value::Number(n) => visitor.number(&n),
+            Value::String(s) => visitor.string(&s),
+            // Packed (`d|`) integer arrays
+            Value::Array(items) => {
+                visitor.begin_array(items.len());
+                for item in items {
+                    if let Value::Number(n) = item {
+                        visitor.number(&n);
+                    }
+                }
+                visitor.end_array();
+            }
+            Value::Object(_) => unreachable!("composite handled above"),
+        }
+    }
+}
+
+/// Count the nodes of the fully decompressed tree without building it.
+///
+/// Every value counts as one node: each scalar, each array and each object
+/// (object keys are not counted separately). Counts are memoized per store
+/// entry, so heavily shared subtrees are cheap to measure even when their
+/// expansion is huge; a count beyond `usize::MAX` saturates.
+///
+/// # Arguments
+///
+/// * `c` - The compressed representation to measure
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{compress, expanded_node_count};
+/// use serde_json::json;
+///
+/// // The object, the array, and its three elements
+/// let compressed = compress(&json!({"list": [1, 2, null]}));
+/// assert_eq!(expanded_node_count(&compressed), 5);
+/// ```
+pub fn expanded_node_count(c: &Compressed) -> usize {
+    let (values, root) = root_store(&c.values, c.root.as_str());
+    count_nodes(&values, root, &mut HashMap::new())
+}
+
+/// Count the nodes under `key`, memoizing counts by store index.
+fn count_nodes(values: &[String], key: &str, memo: &mut HashMap<usize, usize>) -> usize {
+    if is_null_key(key) {
+        return 1;
+    }
+    let id = decode_key(key);
+    if let Some(&count) = memo.get(&id) {
+        return count;
+    }
+    let v_str = &values[id];
+    let children: Vec<&str> = if let Some(refs) = v_str.strip_prefix("a|") {
+        refs.split('|').filter(|_| !refs.is_empty()).collect()
+    } else if v_str.starts_with("o|") {
+        // Skip the schema reference, which is not part of the output tree
+        v_str.split('|').skip(2).collect()
+    } else if let Some(packed) = v_str.strip_prefix("d|") {
+        // One integer per `|`-separated delta
+        return packed.split('|').count().saturating_add(1);
+    } else {
+        Vec::new()
+    };
+    let count = children.into_iter().fold(1usize, |n, k| {
+        n.saturating_add(count_nodes(values, k, memo))
+    });
+    memo.insert(id, count);
+    count
+}
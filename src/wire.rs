@@ -0,0 +1,55 @@
+//! Writing the wire form into a reused buffer.
+//!
+//! The wire form of a [`Compressed`] value is the JSON text
+//! `[[...values...],"root"]` that `serde_json::to_string` produces. In a
+//! hot loop, such as a response serializer, allocating a fresh `String`
+//! for every payload adds up; [`compress_to_writer_string`] appends the
+//! text to a caller-provided buffer instead, so one buffer can be cleared
+//! and reused across requests.
+//!
+//! # Example
+//!
+//! ```rust
+//! use compress_json_rs::{Compressed, compress_to_writer_string, decompress};
+//! use serde_json::json;
+//!
+//! let mut buf = String::new();
+//! for id in 0..3 {
+//!     buf.clear();
+//!     compress_to_writer_string(&json!({"id": id}), &mut buf);
+//!     let c: Compressed = serde_json::from_str(&buf).unwrap();
+//!     assert_eq!(decompress(c), json!({"id": id}));
+//! }
+//! ```
+
+use crate::core::compress;
+use serde_json::Value;
+
+/// Compress a JSON value, appending its wire form to `buf`.
+///
+/// The appended text is exactly what `serde_json::to_string(&compress(o))`
+/// returns; existing contents of `buf` are kept.
+///
+/// # Arguments
+///
+/// * `o` - A reference to the JSON value to compress
+/// * `buf` - The buffer to append the `[values, root]` JSON to
+///
+/// # Example
+///
+/// ```rust
+/// use compress_json_rs::{compress, compress_to_writer_string};
+/// use serde_json::json;
+///
+/// let data = json!({"name": "Alice"});
+/// let mut buf = String::from("payload=");
+/// compress_to_writer_string(&data, &mut buf);
+///
+/// assert_eq!(buf, format!("payload={}", serde_json::to_string(&compress(&data)).unwrap()));
+/// ```
+pub fn compress_to_writer_string(o: &Value, buf: &mut String) {
+    // Serialize into the buffer's own bytes, so its capacity is reused
+    let mut bytes = std::mem::take(buf).into_bytes();
+    serde_json::to_writer(&mut bytes, &compress(o)).expect("write to Vec");
+    *buf = String::from_utf8(bytes).expect("serde_json writes valid UTF-8");
+}
@@ -0,0 +1,43 @@
+//! Allocation counts while compressing
+//!
+//! Kept to a single test: the counter is global to this test binary.
+
+use compress_json_rs::{compress, decompress};
+use serde_json::{Value, json};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+#[test]
+fn test_same_shaped_objects_reuse_cached_schema() {
+    let rows: Vec<Value> = (0..10_000usize)
+        .map(|i| json!({"id": if i % 2 == 0 { "a" } else { "b" }, "name": "row", "active": true, "on": false, "tags": []}))
+        .collect();
+    let data = Value::Array(rows);
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let compressed = compress(&data);
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+
+    // Each object still builds its `o|` entry, but no longer copies its five
+    // key strings twice (25 allocations per object when it did)
+    assert!(allocations < 18 * 10_000, "{allocations} allocations");
+    assert_eq!(decompress(compressed), data);
+}
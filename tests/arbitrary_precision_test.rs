@@ -0,0 +1,73 @@
+//! Tests for numbers parsed with serde_json's `arbitrary_precision`
+#![cfg(feature = "arbitrary_precision")]
+
+use compress_json_rs::{
+    Config, compress, compress_owned, compress_str, compress_with_config, decompress,
+    try_compress_with_config, try_decompress,
+};
+use serde_json::Value;
+
+#[test]
+fn test_40_digit_number_roundtrip() {
+    let text = "1234567890123456789012345678901234567890";
+    let data: Value = serde_json::from_str(text).unwrap();
+    let compressed = compress(&data);
+    assert!(compressed.values.contains(&format!("n|{text}")));
+
+    let restored = decompress(compressed);
+    assert_eq!(restored.to_string(), text);
+    assert_eq!(restored, data);
+}
+
+#[test]
+fn test_precise_decimals_roundtrip() {
+    let json = r#"[0.10000000000000000000000001,-98765432109876543210.5,1e400,1.5,42]"#;
+    let compressed = compress_str(json).unwrap();
+    assert_eq!(decompress(compressed).to_string(), json);
+}
+
+#[test]
+fn test_representable_numbers_use_regular_encoding() {
+    let data: Value = serde_json::from_str("[1.5, 100, -0.25]").unwrap();
+    let compressed = compress(&data);
+    for v in ["n|1.5", "n|100", "n|-0.25"] {
+        assert!(compressed.values.contains(&v.to_string()), "{v}");
+    }
+}
+
+#[test]
+fn test_numbers_beyond_f64_are_not_zeroed() {
+    let huge = format!("-{}", "9".repeat(400));
+    let text = format!("[1e400,-1e400,{huge},1e-400]");
+    let data: Value = serde_json::from_str(&text).unwrap();
+    let config = Config {
+        error_on_precision_loss: true,
+        ..Config::default()
+    };
+
+    for compressed in [
+        compress(&data),
+        compress_owned(data.clone()),
+        compress_str(&text).unwrap(),
+        try_compress_with_config(&data, &config).unwrap(),
+        compress_with_config(&data, &config),
+    ] {
+        assert!(!compressed.values.iter().any(|v| v == "n|0" || v == "n|0.0"));
+        assert!(compressed.values.contains(&format!("n|{huge}")));
+        assert_eq!(try_decompress(&compressed).as_ref(), Ok(&data));
+        assert_eq!(decompress(compressed).to_string(), text);
+    }
+}
+
+#[cfg(feature = "simd-json")]
+#[test]
+fn test_simd_decoding_keeps_the_sign_of_overflowing_numbers() {
+    use compress_json_rs::decompress_simd;
+    use simd_json::prelude::*;
+
+    let data: Value = serde_json::from_str("[1e400,-1e400]").unwrap();
+    let decoded = decompress_simd(compress(&data));
+    let items = decoded.as_array().unwrap();
+    assert_eq!(items[0].as_f64(), Some(f64::INFINITY));
+    assert_eq!(items[1].as_f64(), Some(f64::NEG_INFINITY));
+}
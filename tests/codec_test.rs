@@ -0,0 +1,83 @@
+//! Tests for the public per-type codec functions
+
+use compress_json_rs::codec::{
+    RESERVED_PREFIXES, decode_bool, decode_key, decode_num, decode_special, decode_str,
+    encode_bool, encode_num, encode_str, encode_string, is_special_value,
+};
+use compress_json_rs::{Config, compress, compress_with_config, decompress, key_for_index};
+use serde_json::json;
+
+#[test]
+fn test_decode_bool_entries() {
+    assert!(decode_bool("b|T"));
+    assert!(!decode_bool("b|F"));
+    assert_eq!(encode_bool(true), "b|T");
+    assert_eq!(encode_bool(false), "b|F");
+}
+
+#[test]
+fn test_decode_num_entries() {
+    assert_eq!(decode_num("n|0"), 0.0);
+    assert_eq!(decode_num("n|-12"), -12.0);
+    assert_eq!(decode_num("n|2.5e-3"), 0.0025);
+    assert_eq!(decode_num(&encode_num(1234.5)), 1234.5);
+}
+
+#[test]
+fn test_decode_special_entries() {
+    assert!(is_special_value("N|+"));
+    assert!(!is_special_value("n|1"));
+    assert_eq!(decode_special("N|+"), f64::INFINITY);
+    assert_eq!(decode_special("N|-"), f64::NEG_INFINITY);
+    assert!(decode_special("N|0").is_nan());
+}
+
+#[test]
+fn test_decode_str_entries() {
+    assert_eq!(decode_str("plain"), "plain");
+    assert_eq!(decode_str(""), "");
+    assert_eq!(decode_str("s|b|T"), "b|T");
+    for s in ["plain", "b|T", "n|1", "s|x", "o|0", "unicode ✓"] {
+        assert_eq!(decode_str(&encode_str(s)), s);
+        assert_eq!(encode_string(s.to_string()), encode_str(s));
+    }
+}
+
+#[test]
+fn test_decode_key_inverts_key_for_index() {
+    for i in [0, 9, 10, 61, 62, 3843, 3844, 1_000_000] {
+        assert_eq!(decode_key(&key_for_index(i)), i);
+    }
+}
+
+#[test]
+fn test_codec_decodes_compressed_entries() {
+    let c = compress(&json!([true, 7, "n|escaped"]));
+    assert!(decode_bool(&c.values[decode_key("0")]));
+    assert_eq!(decode_num(&c.values[decode_key("1")]), 7.0);
+    assert_eq!(decode_str(&c.values[decode_key("2")]), "n|escaped");
+}
+
+#[test]
+fn test_every_reserved_prefix_survives() {
+    let chunked = Config {
+        chunk_strings: Some(2),
+        ..Config::default()
+    };
+    for prefix in RESERVED_PREFIXES {
+        for s in [
+            prefix.to_string(),
+            format!("{prefix}x"),
+            format!("{prefix}0|1"),
+        ] {
+            assert!(encode_str(&s).starts_with("s|"), "{s} not escaped");
+            let data = json!({"value": s, "list": [s, s], s.clone(): true});
+            assert_eq!(decompress(compress(&data)), data, "{s}");
+            assert_eq!(
+                decompress(compress_with_config(&data, &chunked)),
+                data,
+                "{s} (chunked)"
+            );
+        }
+    }
+}
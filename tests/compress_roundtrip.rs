@@ -1,5 +1,12 @@
-use compress_json_rs::{compress, decompress};
+mod sample;
+
+use compress_json_rs::{
+    Compressed, array_iter, compress, compress_batch, compress_owned, compress_shared, decode,
+    decompress, decompress_into_value, decompress_rows, decompress_to_raw,
+};
+use serde::Deserialize;
 use serde_json::{Value, json};
+use std::rc::Rc;
 
 #[test]
 fn compress_decompress_roundtrip_object() {
@@ -50,6 +57,62 @@ fn compress_decompress_roundtrip_primitives() {
     assert_eq!(value_null, decompressed_null);
 }
 
+#[test]
+fn compress_owned_matches_compress() {
+    let value = json!({
+        "users": [
+            { "name": "Alice", "role": "admin", "tags": ["a", "b"] },
+            { "name": "Bob", "role": "admin", "tags": [] },
+            { "name": "n|not a number", "role": null, "tags": ["a", null] }
+        ],
+        "empty": {},
+        "flag": true,
+        "count": 3
+    });
+    let expected = compress(&value);
+    let owned = compress_owned(value.clone());
+    assert_eq!(expected, owned);
+    assert_eq!(value, decompress(owned));
+}
+
+#[test]
+fn decompress_to_raw_roundtrip() {
+    let value = json!({
+        "name": "Alice",
+        "tags": ["a", "b", null],
+        "nested": { "n": 1.5 }
+    });
+    let raw = decompress_to_raw(compress(&value)).unwrap();
+    let parsed: Value = serde_json::from_str(raw.get()).unwrap();
+    assert_eq!(value, parsed);
+}
+
+#[test]
+fn iterate_compressed_values() {
+    let compressed = compress(&json!({"a": "x", "b": "x", "c": [1, 2]}));
+
+    let mut count = 0;
+    for value in &compressed {
+        assert!(compressed.values.contains(value));
+        count += 1;
+    }
+    assert_eq!(count, compressed.values.len());
+    assert_eq!((&compressed).into_iter().filter(|v| *v == "x").count(), 1);
+}
+
+#[test]
+fn compressed_serializes_as_pair() {
+    let compressed = compress(&json!([1, "a"]));
+    let text = serde_json::to_string(&compressed).unwrap();
+    assert_eq!(
+        text,
+        serde_json::to_string(&(&compressed.values, &compressed.root)).unwrap()
+    );
+
+    let loaded: Compressed = serde_json::from_str(&text).unwrap();
+    assert_eq!(loaded, compressed);
+}
+
 #[test]
 fn decompress_specific_case() {
     let compressed_str = r#"[
@@ -115,38 +178,12 @@ fn decompress_specific_case() {
     let parsed_value: Value = serde_json::from_str(compressed_str)
         .expect("Failed to parse compressed JSON string representation");
 
-    // 2. Convert the parsed Value into the Compressed tuple format (Vec<String>, String)
-    let compressed_tuple: (Vec<String>, String) = match parsed_value {
-        Value::Array(mut outer_vec) if outer_vec.len() == 2 => {
-            let key_id_val = outer_vec.pop().expect("Outer array missing second element");
-            let values_val = outer_vec.pop().expect("Outer array missing first element");
-
-            let values: Vec<String> = match values_val {
-                Value::Array(inner_vec) => inner_vec
-                    .into_iter()
-                    .map(|v| match v {
-                        Value::String(s) => s,
-                        _ => panic!("Inner array element is not a string: {:?}", v),
-                    })
-                    .collect(),
-                _ => panic!("First element is not an array: {:?}", values_val),
-            };
-
-            let key_id: String = match key_id_val {
-                Value::String(s) => s,
-                _ => panic!("Second element is not a string: {:?}", key_id_val),
-            };
-
-            (values, key_id)
-        }
-        _ => panic!(
-            "Parsed value is not a two-element array: {:?}",
-            parsed_value
-        ),
-    };
-
-    // 3. Decompress the value using the tuple
-    let decompressed = decompress(compressed_tuple);
+    // 2. Convert the parsed Value into the Compressed form
+    let compressed =
+        Compressed::try_from(parsed_value).expect("Parsed value is not a compressed pair");
+
+    // 3. Decompress the value
+    let decompressed = decompress(compressed);
 
     // 4. Define the expected decompressed value
     let expected_value = json!( [
@@ -239,3 +276,207 @@ fn decompress_specific_case() {
     // 5. Assert equality
     assert_eq!(expected_value, decompressed);
 }
+
+#[test]
+fn compressed_size_metrics() {
+    let data = json!({"name": "Alice", "tags": ["a", "b", "a"], "nested": {"name": "Bob"}});
+    let compressed = compress(&data);
+
+    let value_bytes: usize = compressed.values.iter().map(|v| v.len()).sum();
+    assert_eq!(
+        compressed.byte_len(),
+        value_bytes + compressed.root.as_str().len()
+    );
+    assert_eq!(compressed.store_len(), compressed.values.len());
+
+    let empty = compress(&json!(null));
+    assert_eq!(empty.byte_len(), 0);
+    assert_eq!(empty.store_len(), 0);
+}
+
+#[test]
+fn compressed_value_conversion_roundtrip() {
+    for data in [
+        json!(null),
+        json!([]),
+        json!({"name": "Alice", "tags": ["a", "b"], "n|x": [1.5, null]}),
+    ] {
+        let compressed = compress(&data);
+        let wire = Value::from(compressed.clone());
+        assert_eq!(wire, serde_json::to_value(&compressed).unwrap());
+        assert_eq!(wire[0].as_array().unwrap().len(), compressed.values.len());
+        assert_eq!(Compressed::try_from(wire).unwrap(), compressed);
+    }
+}
+
+#[test]
+fn compressed_try_from_rejects_malformed_shapes() {
+    for bad in [
+        json!(null),
+        json!("a|0"),
+        json!({"values": ["x"], "root": "0"}),
+        json!([["x"]]),
+        json!([["x"], "0", "extra"]),
+        json!([[1, 2], "0"]),
+        json!([["x"], 0]),
+        json!([["x"], "1"]),
+        json!([[], "0"]),
+        json!([["x"], "a|0"]),
+    ] {
+        assert!(Compressed::try_from(bad.clone()).is_err(), "{bad}");
+    }
+    assert!(Compressed::try_from(json!([[], ""])).is_ok());
+    assert!(Compressed::try_from(json!([["x"], "_"])).is_ok());
+}
+
+#[test]
+fn compress_batch_shares_strings_between_documents() {
+    let docs = [
+        json!({"user": "alice", "role": "admin"}),
+        json!({"user": "bob", "role": "admin"}),
+        json!(["alice", "bob", "admin", null]),
+    ];
+    let (batch, roots) = compress_batch(&docs);
+    assert_eq!(roots.len(), 3);
+    for (doc, root) in docs.iter().zip(&roots) {
+        assert_eq!(&decode(&batch.values, root), doc);
+    }
+    for s in ["alice", "bob", "admin"] {
+        assert_eq!(batch.values.iter().filter(|v| *v == s).count(), 1);
+    }
+    let separate: usize = docs.iter().map(|d| compress(d).store_len()).sum();
+    assert!(batch.store_len() < separate);
+    assert_eq!(decompress(batch), Value::Array(docs.to_vec()));
+}
+
+#[test]
+fn compress_batch_null_and_empty() {
+    let (batch, roots) = compress_batch(&[json!(null), json!(1)]);
+    assert!(roots[0].is_null());
+    assert_eq!(decompress(batch), json!([null, 1]));
+
+    let (batch, roots) = compress_batch(&[]);
+    assert!(roots.is_empty());
+    assert_eq!(decompress(batch), json!([]));
+}
+
+#[test]
+fn decompress_into_value_matches_fresh_decompress() {
+    let first = json!({
+        "items": [{"id": 1, "label": "a"}, {"id": 2, "label": "b"}, {"id": 3}],
+        "meta": {"page": 1, "stale": true},
+        "title": "first"
+    });
+    let second = json!({
+        "items": [{"id": 1, "label": "a"}, {"id": 2, "label": "c"}],
+        "meta": {"page": 2, "total": 9},
+        "title": ["now", "an", "array"]
+    });
+    let mut target = Value::Null;
+    decompress_into_value(&compress(&first), &mut target);
+    assert_eq!(target, decompress(compress(&first)));
+
+    let items_ptr = target["items"].as_array().unwrap().as_ptr();
+    decompress_into_value(&compress(&second), &mut target);
+    assert_eq!(target, decompress(compress(&second)));
+    // The shrunken array kept its buffer
+    assert_eq!(target["items"].as_array().unwrap().as_ptr(), items_ptr);
+
+    for next in [
+        json!([null, {}, []]),
+        json!({}),
+        json!(null),
+        json!([1, [2, [3]]]),
+    ] {
+        decompress_into_value(&compress(&next), &mut target);
+        assert_eq!(target, next);
+    }
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct UserStruct {
+    user_id: u32,
+    name: String,
+    region: String,
+    role: String,
+    more: String,
+}
+
+#[test]
+fn test_decompress_rows_collection() {
+    let collection = sample::get_sample("collection");
+    let users: Vec<UserStruct> = decompress_rows(compress(&collection)).unwrap();
+    assert_eq!(users.len(), 10);
+    assert_eq!(users[0].user_id, 1);
+    assert_eq!(users[0].name, collection[0]["name"]);
+    assert!(users.iter().all(|u| u.region == "HK" && u.role == "user"));
+}
+
+#[test]
+fn test_decompress_rows_rejects_non_array_root() {
+    let err = decompress_rows::<Value>(compress(&json!({"a": 1}))).unwrap_err();
+    assert!(err.to_string().contains("not an array"));
+    assert!(decompress_rows::<Value>(compress(&Value::Null)).is_err());
+    let empty: Vec<UserStruct> = decompress_rows(compress(&json!([]))).unwrap();
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn test_decompress_rows_reports_mismatched_row() {
+    let data = json!([{"user_id": 1, "name": "A", "region": "HK", "role": "user", "more": ""}, 5]);
+    assert!(decompress_rows::<UserStruct>(compress(&data)).is_err());
+}
+
+#[test]
+fn test_array_iter_collection() {
+    let collection = sample::get_sample("collection");
+    let compressed = compress(&collection);
+    let mut count = 0;
+    for (i, row) in array_iter(&compressed).unwrap().enumerate() {
+        assert_eq!(row, collection[i]);
+        count += 1;
+    }
+    assert_eq!(count, 10);
+}
+
+#[test]
+fn test_array_iter_non_array_and_empty() {
+    assert!(array_iter(&compress(&json!({"a": [1]}))).is_none());
+    assert!(array_iter(&compress(&json!("a|0"))).is_none());
+    assert!(array_iter(&compress(&Value::Null)).is_none());
+    assert_eq!(array_iter(&compress(&json!([]))).unwrap().count(), 0);
+    let rows: Vec<Value> = array_iter(&compress(&json!([null, [], 1])))
+        .unwrap()
+        .collect();
+    assert_eq!(rows, [Value::Null, json!([]), json!(1)]);
+}
+
+#[test]
+fn compress_shared_stores_shared_subtree_once() {
+    let user = Rc::new(json!({"name": "alice", "roles": ["admin", "dev"]}));
+    let other = Rc::new(json!({"name": "bob", "roles": ["dev"]}));
+    let items = [Rc::clone(&user), Rc::clone(&other), Rc::clone(&user)];
+    let compressed = compress_shared(&items);
+
+    // user's entries + other's name, roles array and object + the root array
+    let alone = compress(&user).store_len();
+    assert_eq!(compressed.store_len(), alone + 3 + 1);
+    assert_eq!(
+        compressed
+            .values
+            .iter()
+            .filter(|v| v.as_str() == "alice")
+            .count(),
+        1
+    );
+    assert_eq!(compressed, compress(&json!([*user, *other, *user])));
+    assert_eq!(decompress(compressed), json!([*user, *other, *user]));
+}
+
+#[test]
+fn compress_shared_handles_null_and_empty() {
+    assert_eq!(decompress(compress_shared(&[])), json!([]));
+    let null = Rc::new(Value::Null);
+    let items = [Rc::clone(&null), Rc::new(json!(1)), null];
+    assert_eq!(decompress(compress_shared(&items)), json!([null, 1, null]));
+}
@@ -0,0 +1,714 @@
+//! Tests for the configuration type
+
+use compress_json_rs::{
+    CONFIG, CompressError, Compressed, Config, DecompressError, NullObjectPolicy, compress,
+    compress_str, compress_str_with_config, compress_with_config, compress_with_report, decompress,
+    get_path, key_for_index, try_compress_with_config, try_decompress, try_decompress_with_config,
+};
+use serde_json::json;
+
+#[test]
+fn test_default_matches_config_constant() {
+    assert_eq!(Config::default(), CONFIG);
+}
+
+#[test]
+fn test_config_comparison() {
+    let custom = Config {
+        sort_key: true,
+        ..Config::default()
+    };
+
+    assert_ne!(custom, CONFIG);
+    assert_eq!(custom, custom.clone());
+}
+
+#[test]
+fn test_numeric_strings_disabled_by_default() {
+    let data = json!({"id": "9007199254740993"});
+    let restored = decompress(compress(&data));
+    assert_eq!(restored, data);
+}
+
+#[test]
+fn test_numeric_strings_roundtrip_as_numbers() {
+    let config = Config {
+        numeric_strings: true,
+        ..Config::default()
+    };
+    let data = json!({"id": "9007199254740993", "neg": "-42"});
+    let restored = decompress(compress_with_config(&data, &config));
+
+    assert_eq!(restored["id"].as_u64(), Some(9007199254740993));
+    assert_eq!(restored["neg"].as_i64(), Some(-42));
+}
+
+#[test]
+fn test_numeric_strings_ignore_non_numeric() {
+    let config = Config {
+        numeric_strings: true,
+        ..Config::default()
+    };
+    let data = json!(["123abc", "007", "-0", "1.5", "", "99999999999999999999999"]);
+    let restored = decompress(compress_with_config(&data, &config));
+    assert_eq!(restored, data);
+}
+
+#[test]
+fn test_duplicate_keys_last_wins_by_default() {
+    let compressed = compress_str(r#"{"a":1,"a":2}"#).unwrap();
+    assert_eq!(decompress(compressed), json!({"a": 2}));
+}
+
+#[test]
+fn test_duplicate_keys_rejected_when_strict() {
+    let config = Config {
+        strict_duplicate_keys: true,
+        ..Config::default()
+    };
+    let err = compress_str_with_config(r#"{"a":1,"a":2}"#, &config).unwrap_err();
+    assert!(err.to_string().contains("duplicate key `a`"), "{err}");
+
+    // Nested duplicates are caught too
+    assert!(compress_str_with_config(r#"[{"b":{"x":1,"x":1}}]"#, &config).is_err());
+
+    // Valid documents parse identically to the lenient path
+    let text = r#"{"a":[1,2.5,"s",null,true],"b":{"c":-3}}"#;
+    let strict = compress_str_with_config(text, &config).unwrap();
+    assert_eq!(strict, compress_str(text).unwrap());
+}
+
+#[test]
+fn test_chunked_strings_share_prefix() {
+    let header = "HEADER-".repeat(100);
+    let first = format!("{header}first body");
+    let second = format!("{header}second body");
+    let data = json!([first, second, "short", "c|looks chunked"]);
+
+    let config = Config {
+        chunk_strings: Some(64),
+        ..Config::default()
+    };
+    let Compressed { values, root } = compress_with_config(&data, &config);
+    let plain = compress(&data).values;
+
+    // Shared header chunks are stored once, so the store is much smaller
+    let size = |v: &[String]| v.iter().map(String::len).sum::<usize>();
+    assert!(size(&values) < size(&plain) * 2 / 3);
+    assert!(values.iter().filter(|v| v.starts_with("c|")).count() == 2);
+    assert!(values.contains(&"s|c|looks chunked".to_string()));
+
+    assert_eq!(decompress(Compressed { values, root }), data);
+}
+
+#[test]
+fn test_chunked_strings_respect_char_boundaries() {
+    let data = json!("日本語のテキスト".repeat(20));
+    let config = Config {
+        chunk_strings: Some(5),
+        ..Config::default()
+    };
+    let restored = decompress(compress_with_config(&data, &config));
+    assert_eq!(restored, data);
+}
+
+#[test]
+fn test_frequency_keys_give_frequent_values_short_keys() {
+    // 100 unique values first, then one value referenced 200 times
+    let unique: Vec<_> = (0..100).map(|i| json!(format!("unique-{i}"))).collect();
+    let repeated = vec![json!("shared"); 200];
+    let data = json!([unique, repeated]);
+
+    let plain = compress(&data);
+    let config = Config {
+        frequency_keys: true,
+        ..Config::default()
+    };
+    let sorted = compress_with_config(&data, &config);
+
+    let key_of = |c: &Compressed| {
+        let index = c.values.iter().position(|v| v == "shared").unwrap();
+        key_for_index(index)
+    };
+    assert!(key_of(&plain).len() > 1);
+    assert_eq!(key_of(&sorted).len(), 1);
+
+    assert_eq!(sorted.values.len(), plain.values.len());
+    assert!(
+        serde_json::to_string(&sorted).unwrap().len()
+            < serde_json::to_string(&plain).unwrap().len()
+    );
+    assert_eq!(decompress(sorted), data);
+}
+
+#[test]
+fn test_frequency_keys_roundtrip() {
+    let config = Config {
+        frequency_keys: true,
+        chunk_strings: Some(3),
+        ..Config::default()
+    };
+    for data in [
+        json!(null),
+        json!("only"),
+        json!([]),
+        json!([null, 1, null, {"a": [null, {}]}]),
+        json!({"a": {"b": [1, 1, 1]}, "c": "chunk me please", "d": "chunk me"}),
+    ] {
+        assert_eq!(decompress(compress_with_config(&data, &config)), data);
+    }
+}
+
+#[test]
+fn test_dedup_disabled_stores_every_occurrence() {
+    let config = Config {
+        dedup: false,
+        ..Config::default()
+    };
+    let data = json!(["same", "same", [1, 2], [1, 2]]);
+
+    let shared = compress(&data);
+    assert_eq!(shared.values.iter().filter(|v| *v == "same").count(), 1);
+
+    let compressed = compress_with_config(&data, &config);
+    assert_eq!(compressed.values.iter().filter(|v| *v == "same").count(), 2);
+    assert!(compressed.values.len() > shared.values.len());
+    assert_eq!(decompress(compressed), data);
+}
+
+#[test]
+fn test_share_schemas_disabled_roundtrip() {
+    let config = Config {
+        share_schemas: false,
+        ..Config::default()
+    };
+    let data = json!([
+        {"id": 1, "name": "a"},
+        {"id": 2, "name": "b"},
+        {"id": 3, "name": "c"},
+        {"only": true},
+        {"only": false},
+        {"id": 4},
+        {},
+        ["id", "name"],
+    ]);
+
+    let shared = compress(&data);
+    let compressed = compress_with_config(&data, &config);
+    assert_eq!(decompress(compressed.clone()), data);
+
+    // Each of the three `id,name` objects has its own key list
+    let lists = |c: &Compressed| c.values.iter().filter(|v| *v == "a|0|1").count();
+    assert_eq!(lists(&shared), 1);
+    assert_eq!(lists(&compressed), 3);
+
+    let sorted = Config {
+        sort_key: true,
+        ..config
+    };
+    assert_eq!(decompress(compress_with_config(&data, &sorted)), data);
+}
+
+#[test]
+fn test_numeric_object_keys_stay_strings() {
+    let data = json!({"123": "456", "7": {"8": 9}});
+    for share_schemas in [true, false] {
+        let config = Config {
+            numeric_strings: true,
+            chunk_strings: Some(1),
+            share_schemas,
+            ..Config::default()
+        };
+        let compressed = compress_with_config(&data, &config);
+        assert_eq!(
+            decompress(compressed),
+            json!({"123": 456, "7": {"8": 9}}),
+            "share_schemas: {share_schemas}"
+        );
+    }
+}
+
+#[test]
+fn test_verify_on_compress_accepts_normal_data() {
+    let data = json!({
+        "users": [{"id": 1, "name": "Alice"}, {"id": 2, "name": "n|Bob"}],
+        "ratio": 0.5,
+        "note": "a string long enough to be chunked",
+        "code": "007",
+        "empty": [{}, []]
+    });
+    for config in [
+        Config::default(),
+        Config {
+            sort_key: true,
+            numeric_strings: true,
+            chunk_strings: Some(8),
+            frequency_keys: true,
+            ..Config::default()
+        },
+        Config {
+            dedup: false,
+            share_schemas: false,
+            ..Config::default()
+        },
+    ] {
+        let config = Config {
+            verify_on_compress: true,
+            ..config
+        };
+        let compressed = compress_with_config(&data, &config);
+        assert_eq!(
+            compressed,
+            compress_with_config(
+                &data,
+                &Config {
+                    verify_on_compress: false,
+                    ..config
+                }
+            )
+        );
+    }
+}
+
+#[test]
+fn test_typed_sections_roundtrip() {
+    let data = json!({
+        "users": [{"id": 1, "name": "Alice", "ok": true}, {"id": 2, "name": "Bob", "ok": false}],
+        "scores": [1.5, 2.5, 1.5],
+        "note": "n|escaped"
+    });
+    for frequency_keys in [false, true] {
+        let config = Config {
+            typed_sections: true,
+            frequency_keys,
+            verify_on_compress: true,
+            ..Config::default()
+        };
+        let compressed = compress_with_config(&data, &config);
+        assert_eq!(compressed.store_len(), compress(&data).store_len());
+        assert_eq!(decompress(compressed), data);
+    }
+}
+
+#[test]
+fn test_typed_sections_numbers_are_contiguous() {
+    let data = json!([1, "a", [2, "b"], {"c": 3, "d": true}, "e", 4.5]);
+    let config = Config {
+        typed_sections: true,
+        ..Config::default()
+    };
+    let compressed = compress_with_config(&data, &config);
+    let sections: Vec<u8> = compressed
+        .values
+        .iter()
+        .map(|v| match &v[..2.min(v.len())] {
+            "n|" => 0,
+            "b|" => 2,
+            "a|" | "o|" => 3,
+            _ => 1,
+        })
+        .collect();
+    let mut sorted = sections.clone();
+    sorted.sort();
+    assert_eq!(sections, sorted);
+    // All four numbers come first
+    assert!(compressed.values[..4].iter().all(|v| v.starts_with("n|")));
+    assert_eq!(decompress(compressed), data);
+}
+
+#[test]
+fn test_strict_preset() {
+    let config = Config::strict();
+    assert!(config.error_on_nan && config.error_on_infinite && config.error_on_precision_loss);
+    assert!(!config.preserve_nan && !config.preserve_infinite && !config.numeric_strings);
+    // Exactly representable integers are fine, including 2^53 itself
+    let data = json!([
+        9007199254740992u64,
+        -9007199254740992i64,
+        1.5,
+        "9007199254740993"
+    ]);
+    assert_eq!(decompress(compress_with_config(&data, &config)), data);
+}
+
+#[test]
+#[cfg(not(feature = "arbitrary_precision"))]
+#[should_panic(expected = "beyond f64 precision")]
+fn test_strict_rejects_u64_beyond_f64_precision() {
+    compress_with_config(&json!({"id": 9007199254740993u64}), &Config::strict());
+}
+
+#[test]
+#[cfg(feature = "arbitrary_precision")]
+fn test_strict_accepts_u64_with_arbitrary_precision() {
+    let data = json!({"id": 9007199254740993u64});
+    assert_eq!(
+        decompress(compress_with_config(&data, &Config::strict())),
+        data
+    );
+}
+
+#[test]
+fn test_precision_loss_allowed_by_default() {
+    let compressed = compress_with_config(&json!(9007199254740993u64), &Config::default());
+    assert_eq!(decompress(compressed).as_f64(), Some(9007199254740992.0));
+}
+
+fn with_null_policy(policy: NullObjectPolicy) -> Config {
+    Config {
+        null_object_policy: policy,
+        ..Config::default()
+    }
+}
+
+#[test]
+fn test_null_object_policy_empty() {
+    let data = json!({"a": null, "b": [null, {"c": null}]});
+    let compressed = compress_with_config(&data, &with_null_policy(NullObjectPolicy::Empty));
+    assert_eq!(compressed, compress(&data));
+    assert!(compressed.values.iter().any(|v| v.ends_with('|')));
+    assert_eq!(decompress(compressed), data);
+}
+
+#[test]
+fn test_null_object_policy_marker() {
+    let data = json!({"a": null, "b": [null, {"c": null}]});
+    let compressed = compress_with_config(&data, &with_null_policy(NullObjectPolicy::Marker));
+    assert_eq!(
+        compressed.values,
+        ["a", "b", "a|0|1", "c", "a|3", "o|4|_", "a|_|5", "o|2|_|6"]
+    );
+    assert_eq!(decompress(compressed), data);
+}
+
+#[test]
+fn test_null_object_policy_drop() {
+    let data = json!({"a": null, "b": [null, {"c": null}], "d": 1});
+    let config = Config {
+        verify_on_compress: true,
+        ..with_null_policy(NullObjectPolicy::Drop)
+    };
+    let compressed = compress_with_config(&data, &config);
+    assert_eq!(decompress(compressed), json!({"b": [null, {}], "d": 1}));
+}
+
+#[test]
+fn test_null_object_policy_applies_to_text_input() {
+    let text = r#"{"a": null, "b": 1}"#;
+    let config = with_null_policy(NullObjectPolicy::Drop);
+    let compressed = compress_str_with_config(text, &config).unwrap();
+    assert_eq!(decompress(compressed), json!({"b": 1}));
+}
+
+fn with_max_value_len(limit: usize) -> Config {
+    Config {
+        max_value_len: Some(limit),
+        ..Config::default()
+    }
+}
+
+#[test]
+fn test_max_value_len_rejects_long_string() {
+    let data = json!({"id": 1, "body": "x".repeat(1000)});
+    let err = try_compress_with_config(&data, &with_max_value_len(64)).unwrap_err();
+    assert_eq!(
+        err,
+        CompressError::ValueTooLong {
+            path: "/body".to_string(),
+            len: 1000,
+            limit: 64,
+        }
+    );
+    assert!(err.to_string().contains("/body"));
+}
+
+#[test]
+fn test_max_value_len_counts_escape_and_keys() {
+    let config = with_max_value_len(4);
+    // `s|n|12` is six bytes once escaped
+    assert_eq!(
+        try_compress_with_config(&json!(["n|12"]), &config),
+        Err(CompressError::ValueTooLong {
+            path: "/0".to_string(),
+            len: 6,
+            limit: 4,
+        })
+    );
+    assert_eq!(
+        try_compress_with_config(&json!({"a": {"a~long/key": 1}}), &config),
+        Err(CompressError::ValueTooLong {
+            path: "/a/a~0long~1key".to_string(),
+            len: 10,
+            limit: 4,
+        })
+    );
+}
+
+#[test]
+fn test_max_value_len_accepts_strings_within_limit() {
+    let data = json!({"name": "Alice", "tags": ["a", "bb"]});
+    let config = with_max_value_len(5);
+    assert_eq!(
+        try_compress_with_config(&data, &config),
+        Ok(compress(&data))
+    );
+}
+
+#[test]
+#[should_panic(expected = "exceeds max_value_len 64")]
+fn test_max_value_len_panics_when_infallible() {
+    compress_with_config(&json!(["x".repeat(1000)]), &with_max_value_len(64));
+}
+
+#[test]
+#[cfg(not(feature = "arbitrary_precision"))]
+fn test_try_compress_reports_precision_loss() {
+    assert_eq!(
+        try_compress_with_config(&json!({"id": 9007199254740993u64}), &Config::strict()),
+        Err(CompressError::UnsupportedValue {
+            path: "/id".to_string(),
+            value: "9007199254740993".to_string(),
+        })
+    );
+}
+
+#[test]
+#[cfg(not(feature = "arbitrary_precision"))]
+fn test_compress_with_report_lists_rounded_integers() {
+    use compress_json_rs::Warning;
+
+    let data = json!({"ids": [1, 9007199254740993u64], "n": -9007199254740995i64});
+    let (compressed, warnings) = compress_with_report(&data, &Config::default());
+    assert_eq!(compressed, compress(&data));
+    assert_eq!(
+        warnings,
+        vec![
+            Warning::Rounded {
+                path: "/ids/1".to_string(),
+                value: "9007199254740993".to_string(),
+                stored: "9007199254740992".to_string(),
+            },
+            Warning::Rounded {
+                path: "/n".to_string(),
+                value: "-9007199254740995".to_string(),
+                stored: "-9007199254740996".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_compress_with_report_is_empty_for_exact_values() {
+    let data = json!({"a": [1, 2.5, "x", null], "b": {"c": true}});
+    assert!(compress_with_report(&data, &CONFIG).1.is_empty());
+}
+
+#[test]
+fn test_extend_schemas_reuses_overlapping_schema() {
+    let data = json!([{"a": 1, "b": 2}, {"a": 3, "b": 4, "c": 5}]);
+    let config = Config {
+        extend_schemas: true,
+        ..Config::default()
+    };
+    let compressed = compress_with_config(&data, &config);
+    let base = compressed.values.iter().position(|v| v == "a|0|1").unwrap();
+    let c = compressed.values.iter().position(|v| v == "c").unwrap();
+    let extended = format!("a|{}|{}", key_for_index(base), key_for_index(c));
+    assert!(
+        compressed.values.contains(&extended),
+        "{:?}",
+        compressed.values
+    );
+    let listed = format!("a|0|1|{}", key_for_index(c));
+    assert!(!compressed.values.contains(&listed));
+
+    assert_eq!(decompress(compressed.clone()), data);
+    assert_eq!(try_decompress(&compressed), Ok(data.clone()));
+    // Without the option every schema lists its keys
+    let plain = compress(&data);
+    assert!(plain.values.iter().all(|v| *v != extended));
+}
+
+#[test]
+fn test_extend_schemas_chains_bases() {
+    let data = json!([
+        {"a": 1, "b": 1},
+        {"a": 1, "b": 1, "c": 1},
+        {"a": 1, "b": 1, "c": 1, "d": 1},
+        {"a": 1, "b": 1, "d": 1},
+        {"x": 1},
+    ]);
+    let config = Config {
+        extend_schemas: true,
+        ..Config::default()
+    };
+    let compressed = compress_with_config(&data, &config);
+    assert_eq!(decompress(compressed.clone()), data);
+    assert_eq!(try_decompress(&compressed), Ok(data.clone()));
+    let value = get_path(&compressed, "/2/d").unwrap();
+    assert_eq!(value, json!(1));
+}
+
+#[test]
+fn test_extended_schema_must_reference_earlier_base() {
+    // Schema 2 names itself as its base
+    let c = Compressed::new(
+        ["k", "n|1", "a|2|0", "o|2|1|1"].map(String::from).to_vec(),
+        "3",
+    );
+    assert_eq!(
+        try_decompress(&c),
+        Err(DecompressError::InvalidSchema { index: 2 })
+    );
+}
+
+#[test]
+fn test_sort_values_keeps_keys_stable_across_an_insert() {
+    let items: Vec<String> = (0..50).map(|i| format!("item-{i:02}")).collect();
+    let before = json!({"items": items});
+    // The new value comes first in the document but sorts last
+    let mut grown = items.clone();
+    grown.insert(0, "zebra".to_string());
+    let after = json!({"items": grown});
+
+    let config = Config {
+        sort_values: true,
+        ..Config::default()
+    };
+    let unchanged_keys = |config: &Config| {
+        let a = compress_with_config(&before, config);
+        let b = compress_with_config(&after, config);
+        a.values
+            .iter()
+            .enumerate()
+            .filter(|(i, v)| b.values.get(*i) == Some(*v))
+            .count()
+    };
+
+    let total = compress(&before).values.len();
+    // First-seen order shifts every string after the inserted one
+    assert!(unchanged_keys(&CONFIG) < total / 10);
+    assert!(unchanged_keys(&config) > total * 9 / 10);
+}
+
+#[test]
+fn test_sort_values_roundtrip() {
+    let data = json!({
+        "b": [3, 1, 2, "x", {"k": [1, null]}],
+        "a": {"k": [1, null], "z": true},
+        "c": "chunk me please",
+    });
+    for frequency_keys in [false, true] {
+        for typed_sections in [false, true] {
+            let config = Config {
+                sort_values: true,
+                frequency_keys,
+                typed_sections,
+                chunk_strings: Some(4),
+                ..Config::default()
+            };
+            assert_eq!(decompress(compress_with_config(&data, &config)), data);
+        }
+    }
+}
+
+#[test]
+fn test_sort_values_orders_scalars_by_content() {
+    let config = Config {
+        sort_values: true,
+        ..Config::default()
+    };
+    let c = compress_with_config(&json!(["b", "c", "a", [1]]), &config);
+    assert_eq!(&c.values[..4], ["a", "b", "c", "n|1"]);
+}
+
+#[test]
+fn test_empty_string_is_distinct_from_null_by_default() {
+    let data = json!({"name": "", "parent": null, "tags": ["", null]});
+    let compressed = compress(&data);
+    // The empty string has an entry of its own, null is an empty reference
+    assert!(compressed.values.iter().any(|v| v.is_empty()));
+    assert_eq!(decompress(compressed.clone()), data);
+    assert_eq!(
+        try_decompress_with_config(&compressed, &CONFIG),
+        Ok(data.clone())
+    );
+}
+
+#[test]
+fn test_treat_empty_string_as_null() {
+    let config = Config {
+        treat_empty_string_as_null: true,
+        verify_on_compress: true,
+        ..Config::default()
+    };
+    let data = json!({"name": "", "parent": null, "tags": ["", "x"], "": "key"});
+    let compressed = compress_with_config(&data, &config);
+    // Only the empty key is stored
+    let empty = compressed.values.iter().filter(|v| v.is_empty()).count();
+    assert_eq!(empty, 1);
+    assert_eq!(
+        decompress(compressed),
+        json!({"name": null, "parent": null, "tags": [null, "x"], "": "key"})
+    );
+
+    // Dropped along with null fields
+    let drop = Config {
+        null_object_policy: NullObjectPolicy::Drop,
+        ..config
+    };
+    let compressed = compress_with_config(&json!({"a": "", "b": 1}), &drop);
+    assert_eq!(decompress(compressed), json!({"b": 1}));
+    assert_eq!(
+        decompress(compress_str_with_config(r#"["", 1]"#, &config).unwrap()),
+        json!([null, 1])
+    );
+}
+
+#[test]
+fn test_treat_empty_string_as_null_when_decoding() {
+    // Empty strings from an encoder that does not apply the toggle
+    let compressed = compress(&json!({"name": "", "tags": [""]}));
+    let config = Config {
+        treat_empty_string_as_null: true,
+        ..Config::default()
+    };
+    assert_eq!(
+        try_decompress_with_config(&compressed, &config),
+        Ok(json!({"name": null, "tags": [null]}))
+    );
+}
+
+#[test]
+fn test_sort_values_gives_identical_values_across_runs() {
+    let config = Config {
+        sort_values: true,
+        ..Config::default()
+    };
+    let data = json!({
+        "users": [
+            {"name": "b", "tags": ["x", "y"], "age": 3},
+            {"name": "a", "tags": ["y", "x"], "age": 1.5},
+        ],
+        "count": 2,
+        "flags": [true, false, null],
+    });
+    let first = compress_with_config(&data, &config);
+    let second = compress_with_config(&data, &config);
+    assert_eq!(first.values, second.values);
+    assert_eq!(first.root, second.root);
+    assert_eq!(decompress(first), data);
+}
+
+#[test]
+fn test_sort_values_with_sort_key_ignores_key_order() {
+    let a = r#"{"list": [1, "one"], "nested": {"x": "one", "y": [2]}}"#;
+    let b = r#"{"nested": {"y": [2], "x": "one"}, "list": [1, "one"]}"#;
+    let config = Config {
+        sort_values: true,
+        sort_key: true,
+        ..Config::default()
+    };
+    let store = |text: &str| compress_str_with_config(text, &config).unwrap().values;
+    assert_eq!(store(a), store(b));
+}
@@ -3,7 +3,12 @@
 
 mod sample;
 
-use compress_json_rs::{compress, decompress};
+use compress_json_rs::{
+    Compressed, Config, DecodeScratch, Decoder, Key, ValueKind, array_get, array_iter, compress,
+    compress_owned, compress_str, compress_with_config, decode, decompress, decompress_into_value,
+    decompress_ordered, decompress_rows, expanded_node_count, garbage_collect, get_path, get_paths,
+    key_for_index, root_kind, set_path, try_decompress,
+};
 use serde_json::{Value, json};
 
 /// Helper to test roundtrip compression/decompression
@@ -62,6 +67,52 @@ fn test_same_array() {
     test_roundtrip("same_array", data);
 }
 
+#[test]
+fn test_empty_array_stored_once() {
+    let data = json!([[], [], {"x": []}]);
+    for compressed in [compress(&data), compress_owned(data.clone())] {
+        let empties = compressed.values.iter().filter(|v| *v == "a|").count();
+        assert_eq!(empties, 1, "{:?}", compressed.values);
+        assert_eq!(decompress(compressed), data);
+    }
+    let c = compress(&json!([[], []]));
+    assert_eq!(c.values, ["a|", "a|0|0"]);
+    assert_eq!(decompress(c), json!([[], []]));
+}
+
+#[test]
+fn test_same_array_stored_once() {
+    let data = sample::get_sample("same_array");
+    let expected = json!([1, 2, 3, 4, 5]);
+    for compressed in [
+        compress(&data),
+        compress_owned(data.clone()),
+        compress(&sample::sample()),
+    ] {
+        // One `a|` entry holds the numbers; the only other array is the schema
+        let arrays: Vec<&String> = compressed
+            .values
+            .iter()
+            .filter(|v| v.starts_with("a|"))
+            .filter(|v| decode(&compressed.values, &key_of(&compressed, v)) == expected)
+            .collect();
+        assert_eq!(arrays.len(), 1, "{:?}", compressed.values);
+    }
+
+    // Both object fields reference that same entry
+    let compressed = compress(&data);
+    let object = compressed.values.last().unwrap();
+    let refs: Vec<&str> = object.split('|').skip(2).collect();
+    assert_eq!(refs.len(), 2);
+    assert_eq!(refs[0], refs[1]);
+}
+
+/// The key of a stored entry.
+fn key_of(compressed: &Compressed, entry: &str) -> Key {
+    let index = compressed.values.iter().position(|v| v == entry).unwrap();
+    key_for_index(index).into()
+}
+
 #[test]
 fn test_collection() {
     let data = sample::get_sample("collection");
@@ -145,6 +196,32 @@ fn test_array_using_existing_values_used_by_object_key() {
     );
 }
 
+#[test]
+fn test_schema_reuses_existing_key_array() {
+    // A data array equal to an object's key list is stored once and serves
+    // as both the array value and the object's schema
+    for data in [
+        json!({"keys": ["id", "name"], "obj": {"id": 1, "name": "x"}}),
+        json!({"a_obj": {"id": 1, "name": "x"}, "keys": ["id", "name"]}),
+    ] {
+        let compressed = compress(&data);
+        let values = &compressed.values;
+        let key_of = |v: &str| {
+            let index = values.iter().position(|s| s == v).expect(v);
+            compress_json_rs::key_for_index(index)
+        };
+
+        let key_array = format!("a|{}|{}", key_of("id"), key_of("name"));
+        assert_eq!(values.iter().filter(|v| **v == key_array).count(), 1);
+
+        // The object's schema reference is the data array's key
+        let object = format!("o|{}|{}|{}", key_of(&key_array), key_of("n|1"), key_of("x"));
+        assert!(values.contains(&object), "{values:?}");
+
+        assert_eq!(decompress(compressed), data);
+    }
+}
+
 // ============================================================
 // Object.prototype conflict tests
 // ============================================================
@@ -181,6 +258,49 @@ fn test_string_as_key_and_value() {
     );
 }
 
+#[test]
+fn test_string_as_key_and_value_is_stored_once() {
+    let data = json!({"any-2": "key-and-value", "x": {"key-and-value": 1}});
+    let configs = [
+        Config::default(),
+        Config {
+            share_schemas: false,
+            ..Config::default()
+        },
+        Config {
+            extend_schemas: true,
+            sort_values: true,
+            frequency_keys: true,
+            typed_sections: true,
+            ..Config::default()
+        },
+    ];
+    let stores = configs
+        .iter()
+        .map(|config| compress_with_config(&data, config))
+        .chain([
+            compress_owned(data.clone()),
+            compress_str(&data.to_string()).unwrap(),
+        ]);
+    for c in stores {
+        let count = c.values.iter().filter(|v| *v == "key-and-value").count();
+        assert_eq!(count, 1, "{:?}", c.values);
+        assert_eq!(decompress(c), data);
+    }
+}
+
+#[test]
+fn test_escaped_string_as_key_and_value_is_stored_once() {
+    // Keys are escaped like values, so they share entries too
+    let data = json!({"n|1": "n|1", "x": {"s|y": "s|y", "": ""}});
+    let c = compress(&data);
+    for entry in ["s|n|1", "s|s|y", ""] {
+        let count = c.values.iter().filter(|v| *v == entry).count();
+        assert_eq!(count, 1, "{entry}: {:?}", c.values);
+    }
+    assert_eq!(decompress(c), data);
+}
+
 // ============================================================
 // Empty array and null handling tests
 // ============================================================
@@ -351,6 +471,26 @@ fn test_null_values_in_objects() {
     );
 }
 
+#[test]
+fn test_null_object_property_is_present() {
+    // Regression: a null property is encoded as an empty reference segment
+    // (`o|<schema>||<ref>`) and must decode to `null`, not a missing key
+    let data = json!({"a": null, "b": 1});
+    let Compressed { values, root } = compress(&data);
+    assert!(
+        values
+            .iter()
+            .any(|v| v.starts_with("o|") && v.contains("||")),
+        "Expected an empty segment for the null property: {values:?}"
+    );
+
+    let decompressed = decompress(Compressed { values, root });
+    let obj = decompressed.as_object().expect("Expected object");
+    assert_eq!(obj.len(), 2);
+    assert_eq!(obj.get("a"), Some(&Value::Null));
+    assert_eq!(obj.get("b"), Some(&json!(1)));
+}
+
 #[test]
 fn test_escaped_prefix_strings() {
     // Strings that look like encoded values should be escaped
@@ -451,7 +591,193 @@ fn test_null_root() {
     test_roundtrip("null at root", Value::Null);
 }
 
+#[test]
+fn test_primitive_roots_in_shared_store() {
+    // Scalar roots skip composite decoding; the root may sit anywhere in the
+    // values array, next to entries it does not use
+    let values: Vec<String> = ["token", "s|n|1", "n|7", "b|F", "N|+", "n|2.0", "a|0|2"]
+        .map(String::from)
+        .to_vec();
+    for (root, expected) in [
+        ("0", json!("token")),
+        ("1", json!("n|1")),
+        ("2", json!(7)),
+        ("3", json!(false)),
+        ("4", Value::Null),
+        ("5", json!(2.0)),
+        ("6", json!(["token", 7])),
+        ("", Value::Null),
+        ("_", Value::Null),
+    ] {
+        let c = Compressed::new(values.clone(), root);
+        assert_eq!(decode(&c.values, &c.root), expected, "root {root:?}");
+        assert_eq!(decompress(c), expected, "root {root:?}");
+    }
+}
+
+#[test]
+fn test_inline_scalar_root_with_empty_store() {
+    for (root, expected) in [
+        ("b|T", json!(true)),
+        ("b|F", json!(false)),
+        ("n|42", json!(42)),
+        ("n|-1.5", json!(-1.5)),
+        ("hello world", json!("hello world")),
+        ("s|hello", json!("hello")),
+        ("s|n|1", json!("n|1")),
+        ("_", Value::Null),
+    ] {
+        let c = Compressed::new(Vec::new(), root);
+        assert_eq!(decode(&c.values, &c.root), expected, "root {root:?}");
+        assert_eq!(try_decompress(&c), Ok(expected.clone()), "root {root:?}");
+        let wire = json!([[], root]);
+        assert_eq!(Compressed::try_from(wire).unwrap(), c);
+        assert_eq!(decompress(c), expected, "root {root:?}");
+    }
+}
+
+#[test]
+fn test_inline_root_rejects_composites() {
+    let c = Compressed::new(Vec::new(), "a|0");
+    assert!(try_decompress(&c).is_err());
+    assert!(Compressed::try_from(json!([[], "a|0"])).is_err());
+}
+
+#[test]
+fn test_inline_root_entry_points() {
+    let c = Compressed::new(Vec::new(), "n|5");
+    assert_eq!(get_path(&c, ""), Some(json!(5)));
+    assert_eq!(get_path(&c, "/0"), None);
+    assert_eq!(get_paths(&c, &["", "/a"]), [Some(json!(5)), None]);
+    assert_eq!(root_kind(&c), ValueKind::Number);
+    assert_eq!(decompress_ordered(c.clone()), None);
+    assert_eq!(garbage_collect(c.clone()), c);
+    assert_eq!(expanded_node_count(&c), 1);
+    assert!(array_iter(&c).is_none());
+    assert_eq!(array_get(&c, 0), Ok(None));
+    assert!(decompress_rows::<Value>(c.clone()).is_err());
+
+    let mut target = json!({"stale": true});
+    decompress_into_value(&c, &mut target);
+    assert_eq!(target, json!(5));
+
+    assert_eq!(DecodeScratch::new().decode(&c.values, &c.root), json!(5));
+    assert_eq!(Decoder::new(&c.values).decode_cached(&c.root), json!(5));
+
+    let s = Compressed::new(Vec::new(), "s|n|1");
+    assert_eq!(root_kind(&s), ValueKind::String);
+    assert_eq!(get_path(&s, ""), Some(json!("n|1")));
+}
+
 #[test]
 fn test_array_root() {
     test_roundtrip("array at root", json!([1, 2, 3]));
 }
+
+// =============================================================================
+// Separator Characters in Object Keys
+// =============================================================================
+
+#[test]
+fn test_object_key_with_pipe() {
+    // Keys live in their own store slots; only reference lists split on `|`
+    test_roundtrip("pipe in key", json!({"a|b": 1}));
+    test_roundtrip("bare pipe key", json!({"|": 1, "||": 2, "": 3}));
+    test_roundtrip(
+        "prefix-like keys",
+        json!({"o|": 1, "a|0|1": 2, "s|x": 3, "n|5": 4}),
+    );
+    test_roundtrip(
+        "pipe keys in repeated objects",
+        json!([{"a|b": 1, "c": 2}, {"a|b": 3, "c": 4}]),
+    );
+}
+
+#[test]
+fn test_object_key_with_comma_does_not_share_schema() {
+    // Regression: schemas were cached by their comma-joined keys, so
+    // `["a,b"]` and `["a", "b"]` collided and the second object reused
+    // the one-key schema of the first
+    test_roundtrip("comma key first", json!([{"a,b": 1}, {"a": 1, "b": 2}]));
+    test_roundtrip("comma key last", json!([{"a": 1, "b": 2}, {"a,b": 1}]));
+}
+
+// =============================================================================
+// Wide Objects
+// =============================================================================
+
+#[test]
+fn test_wide_object() {
+    let mut obj = serde_json::Map::new();
+    for i in 0..200 {
+        obj.insert(format!("key_{i}"), json!(i));
+    }
+    let data = Value::Object(obj);
+    test_roundtrip("200-key object", data.clone());
+    test_roundtrip("repeated 200-key object", json!([data.clone(), data]));
+}
+
+#[test]
+fn test_decompress_ordered_keeps_schema_order() {
+    // Schema lists keys unsorted, as another implementation may store them
+    let values = [
+        "zeta",
+        "alpha",
+        "mid",
+        "a|0|1|2",
+        "n|1",
+        "b|T",
+        "o|0|5",
+        "o|3|4|5|6",
+    ];
+    let c = Compressed::new(values.map(String::from).to_vec(), "7");
+    let pairs = decompress_ordered(c.clone()).unwrap();
+    let keys: Vec<&str> = pairs.iter().map(|(k, _)| k.as_str()).collect();
+    assert_eq!(keys, ["zeta", "alpha", "mid"]);
+    assert_eq!(pairs[2].1, json!({"zeta": true}));
+    assert_eq!(Value::Object(pairs.into_iter().collect()), decompress(c));
+}
+
+#[test]
+fn test_decompress_ordered_non_object_root() {
+    assert_eq!(decompress_ordered(compress(&json!({}))), Some(Vec::new()));
+    assert_eq!(decompress_ordered(compress(&json!([1, 2]))), None);
+    assert_eq!(decompress_ordered(compress(&json!(null))), None);
+}
+
+#[test]
+fn test_numeric_string_keys_are_not_indices() {
+    // "0" and "10" are also valid base-62 keys; they must stay key names
+    let data = json!({"0": "a", "10": "b"});
+    let compressed = compress(&data);
+    assert!(compressed.values.contains(&"0".to_string()));
+    assert!(compressed.values.contains(&"10".to_string()));
+    assert_eq!(decompress(compressed.clone()), data);
+    assert_eq!(
+        decompress_ordered(compressed.clone()),
+        Some(vec![
+            ("0".to_string(), json!("a")),
+            ("10".to_string(), json!("b"))
+        ])
+    );
+    assert_eq!(get_path(&compressed, "/0"), Some(json!("a")));
+    assert_eq!(get_path(&compressed, "/10"), Some(json!("b")));
+    assert_eq!(try_decompress(&compressed), Ok(data.clone()));
+
+    let mut updated = compressed;
+    set_path(&mut updated, "/1", &json!("c")).unwrap();
+    assert_eq!(decompress(updated), json!({"0": "a", "1": "c", "10": "b"}));
+}
+
+#[test]
+fn test_numeric_string_keys_in_nested_and_single_key_objects() {
+    let data = json!([{"0": {"1": 1}}, {"1": "0"}, {"0": 0, "1": 1}, ["0", "1"]]);
+    assert_eq!(decompress(compress(&data)), data);
+    let config = Config {
+        share_schemas: false,
+        numeric_strings: true,
+        ..Config::default()
+    };
+    let expected = json!([{"0": {"1": 1}}, {"1": 0}, {"0": 0, "1": 1}, [0, 1]]);
+    assert_eq!(decompress(compress_with_config(&data, &config)), expected);
+}
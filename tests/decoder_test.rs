@@ -0,0 +1,94 @@
+//! Tests for the memoizing decoder
+
+use compress_json_rs::{
+    DecodeScratch, Decoder, add_value, compress, decode, decompress, make_memory, mem_to_values,
+};
+use serde_json::{Value, json};
+
+#[test]
+fn test_decoder_matches_decode() {
+    let data = json!({
+        "name": "Alice",
+        "roles": ["admin", "user", null],
+        "profile": { "age": 30, "active": true }
+    });
+    let mut mem = make_memory();
+    let root = add_value(&mut mem, &data);
+    let values = mem_to_values(&mem);
+
+    let mut decoder = Decoder::new(&values);
+    assert_eq!(decoder.decode(&root), decode(&values, &root));
+    assert_eq!(decoder.decode_count(), 0);
+    assert_eq!(decoder.decode_cached(&root), decode(&values, &root));
+    assert_eq!(decoder.decode_cached(&root), data);
+}
+
+#[test]
+fn test_uncached_decode_through_shared_reference() {
+    let c = compress(&json!([{"id": 1}, {"id": 2}, null]));
+    let decoder = Decoder::new(&c.values);
+    // `decode` only needs `&self`, so one decoder can be shared freely
+    let by_ref = &decoder;
+    assert_eq!(by_ref.decode(&c.root), decompress(c.clone()));
+    assert_eq!(decoder.decode(&c.root), by_ref.decode(&c.root));
+    assert_eq!(decoder.decode_count(), 0);
+}
+
+#[test]
+fn test_shared_subtree_decoded_once() {
+    let shared = json!({"tags": ["x", "y", "z"], "score": 1.5});
+    let first_doc = json!({"id": 1, "meta": shared});
+    let second_doc = json!({"id": 2, "meta": shared});
+
+    let mut mem = make_memory();
+    let first = add_value(&mut mem, &first_doc);
+    let second = add_value(&mut mem, &second_doc);
+    let values = mem_to_values(&mem);
+
+    // Cost of decoding the second root on its own
+    let mut standalone = Decoder::new(&values);
+    standalone.decode_cached(&second);
+    let second_alone = standalone.decode_count();
+
+    let mut decoder = Decoder::new(&values);
+    assert_eq!(decoder.decode_cached(&first), first_doc);
+    let after_first = decoder.decode_count();
+    assert_eq!(decoder.decode_cached(&second), second_doc);
+
+    // Only the entries unique to the second root (its id, its object entry)
+    // are decoded; the shared subtree comes from the cache.
+    let second_shared = decoder.decode_count() - after_first;
+    assert!(second_shared < second_alone);
+    assert_eq!(second_shared, 2);
+
+    // Decoding an already-seen root costs nothing
+    let before = decoder.decode_count();
+    decoder.decode_cached(&first);
+    assert_eq!(decoder.decode_count(), before);
+}
+
+#[test]
+fn test_scratch_decodes_many_small_payloads() {
+    let mut scratch = DecodeScratch::new();
+    for i in 0..10_000 {
+        let data = json!({
+            "id": i,
+            "name": format!("user-{}", i % 13),
+            "tags": ["a", null, i % 3 == 0],
+            "meta": {"nested": [i, {"deep": "x"}], "empty": {}},
+            "list": []
+        });
+        let compressed = compress(&data);
+        let decoded = scratch.decode(&compressed.values, &compressed.root);
+        assert_eq!(decoded, decompress(compressed));
+    }
+}
+
+#[test]
+fn test_scratch_null_and_scalar_roots() {
+    let mut scratch = DecodeScratch::default();
+    for data in [Value::Null, json!(1.5), json!("s|x"), json!([]), json!({})] {
+        let compressed = compress(&data);
+        assert_eq!(scratch.decode(&compressed.values, &compressed.root), data);
+    }
+}
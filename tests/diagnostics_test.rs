@@ -0,0 +1,179 @@
+//! Tests for memory diagnostics
+
+mod sample;
+
+use compress_json_rs::{
+    CONFIG, Compressed, Config, Memory, add_value, compress, compress_with_config,
+    compress_with_stats, debug_dump, diff_against, make_memory, make_memory_with_config,
+    semantically_eq, value_histogram,
+};
+use serde_json::json;
+
+fn build_memory() -> Memory {
+    let mut mem = make_memory();
+    add_value(&mut mem, &sample::sample());
+    mem
+}
+
+#[test]
+fn test_debug_dump_is_deterministic() {
+    let first = debug_dump(&build_memory());
+    let second = debug_dump(&build_memory());
+    assert_eq!(first, second);
+    assert!(first.starts_with("0 = "));
+    assert!(first.contains("\nschema "));
+}
+
+#[test]
+fn test_debug_dump_lists_uncached_entries() {
+    // Without deduplication nothing is cached, but every entry is stored
+    let config = Config {
+        dedup: false,
+        ..Config::default()
+    };
+    let mut mem = make_memory_with_config(&config);
+    add_value(&mut mem, &json!(["x", "x"]));
+    assert_eq!(debug_dump(&mem), "0 = x\n1 = x\n2 = a|0|1\n");
+
+    // Evicted values stay in the store
+    let config = Config {
+        max_dictionary_len: Some(1),
+        ..Config::default()
+    };
+    let mut mem = make_memory_with_config(&config);
+    add_value(&mut mem, &json!(["a", "b"]));
+    assert_eq!(debug_dump(&mem), "0 = a\n1 = b\n2 = a|0|1\n");
+}
+
+#[test]
+fn test_value_histogram_is_deterministic() {
+    let first = value_histogram(&build_memory());
+    let second = value_histogram(&build_memory());
+    assert_eq!(first, second);
+
+    // Ordered by store index
+    let keys: Vec<&str> = first.iter().map(|(k, _)| k.as_str()).collect();
+    assert_eq!(keys[..3], ["0", "1", "2"]);
+}
+
+#[test]
+fn test_stats_flag_tiny_unique_object_as_expanded() {
+    let data = json!({"a": 1});
+    let (compressed, stats) = compress_with_stats(&data, &CONFIG);
+    assert_eq!(compressed, compress(&data));
+    assert_eq!(stats.raw_bytes, data.to_string().len());
+    assert_eq!(
+        stats.compressed_bytes,
+        serde_json::to_string(&compressed).unwrap().len()
+    );
+    assert!(stats.expanded);
+}
+
+#[test]
+fn test_stats_repetitive_data_not_expanded() {
+    let (_, stats) = compress_with_stats(&sample::sample(), &CONFIG);
+    assert!(stats.compressed_bytes < stats.raw_bytes);
+    assert!(!stats.expanded);
+}
+
+#[test]
+fn test_stats_count_entries_by_type() {
+    let rich = &sample::sample()["rich"];
+    let (compressed, stats) = compress_with_stats(rich, &CONFIG);
+    let total = stats.booleans
+        + stats.numbers
+        + stats.strings
+        + stats.arrays
+        + stats.objects
+        + stats.schemas;
+    assert_eq!(total, compressed.values.len());
+    assert!(stats.strings > 0);
+    assert_eq!(stats.booleans, 2);
+    assert_eq!(stats.objects, 2);
+    assert_eq!(stats.schemas, 2);
+}
+
+#[test]
+fn test_stats_count_array_shared_with_schema_once() {
+    // `["a", "b"]` is stored once and serves as both array and schema
+    let data = json!([["a", "b"], {"a": 1, "b": 2}]);
+    let (compressed, stats) = compress_with_stats(&data, &CONFIG);
+    assert_eq!(stats.schemas, 1);
+    assert_eq!(stats.arrays, 1);
+    assert_eq!(stats.strings, 2);
+    assert_eq!(stats.numbers, 2);
+    assert_eq!(stats.objects, 1);
+    assert_eq!(compressed.values.len(), 7);
+}
+
+#[test]
+fn test_diff_against_equal_is_none() {
+    let data = sample::sample();
+    assert_eq!(diff_against(&compress(&data), &data), None);
+}
+
+#[test]
+fn test_diff_against_reports_changed_path() {
+    let data = sample::sample();
+    let compressed = compress(&data);
+    let mut expected = data.clone();
+    expected["collection"][3]["name"] = json!("someone else");
+    let diff = diff_against(&compressed, &expected).unwrap();
+    assert_eq!(diff.lines().count(), 1);
+    assert!(diff.starts_with("/collection/3/name: expected \"someone else\", got "));
+}
+
+#[test]
+fn test_diff_against_lists_each_difference() {
+    let compressed = compress(&json!({"a": [1, 2], "b/c": true, "extra": null}));
+    let expected = json!({"a": [1, 2.0, 3], "b/c": "true", "gone": {}});
+    assert_eq!(
+        diff_against(&compressed, &expected).unwrap(),
+        [
+            "/a/1: expected 2.0, got 2",
+            "/a/2: missing, expected 3",
+            "/b~1c: expected \"true\", got true",
+            "/gone: missing, expected {}",
+            "/extra: unexpected null",
+        ]
+        .join("\n")
+    );
+    assert_eq!(
+        diff_against(&compress(&json!([1])), &json!({"a": 1})).unwrap(),
+        "(root): expected {\"a\":1}, got [1]"
+    );
+}
+
+#[test]
+fn test_semantically_eq_ignores_store_order() {
+    let data = sample::sample();
+    let plain = compress(&data);
+    let config = Config {
+        frequency_keys: true,
+        sort_values: true,
+        typed_sections: true,
+        ..Config::default()
+    };
+    let reordered = compress_with_config(&data, &config);
+    assert_ne!(plain, reordered);
+    assert!(semantically_eq(&plain, &reordered));
+    assert!(semantically_eq(&reordered, &plain));
+}
+
+#[test]
+fn test_semantically_eq_hand_ordered_stores() {
+    let a = Compressed::new(vec!["x".into(), "n|1".into(), "a|0|1".into()], "2");
+    let b = Compressed::new(vec!["n|1".into(), "x".into(), "a|1|0".into()], "2");
+    assert!(semantically_eq(&a, &b));
+
+    let swapped = Compressed::new(vec!["n|1".into(), "x".into(), "a|0|1".into()], "2");
+    assert!(!semantically_eq(&a, &swapped));
+}
+
+#[test]
+fn test_semantically_eq_different_documents() {
+    let a = compress(&json!({"id": 1}));
+    let b = compress(&json!({"id": 1.0}));
+    assert!(!semantically_eq(&a, &b));
+    assert!(semantically_eq(&a, &a.clone()));
+}
@@ -0,0 +1,48 @@
+//! Tests for diff compression
+
+use compress_json_rs::{apply_diff, compress, compress_diff};
+use serde_json::{Value, json};
+
+fn large_document(status: &str) -> Value {
+    let items: Vec<Value> = (0..100)
+        .map(|i| json!({"id": i, "label": format!("item-{i}"), "active": i % 2 == 0}))
+        .collect();
+    json!({
+        "status": status,
+        "owner": { "name": "Alice", "email": "alice@example.com" },
+        "items": items
+    })
+}
+
+#[test]
+fn test_diff_roundtrip() {
+    let base = large_document("draft");
+    let next = large_document("published");
+
+    let diff = compress_diff(&base, &next);
+    assert_eq!(apply_diff(&base, diff), next);
+}
+
+#[test]
+fn test_diff_is_small() {
+    let base = large_document("draft");
+    let next = large_document("published");
+
+    let added = compress_diff(&base, &next).values;
+    let full = compress(&next).values;
+
+    // Only the new status string and the new root object are sent
+    assert_eq!(added.len(), 2);
+    assert_eq!(added[0], "published");
+    assert!(added[1].starts_with("o|"));
+    assert!(full.len() > 100);
+}
+
+#[test]
+fn test_diff_with_unrelated_document() {
+    let base = json!({"a": 1});
+    let next = json!(["completely", "different", 1]);
+
+    let diff = compress_diff(&base, &next);
+    assert_eq!(apply_diff(&base, diff), next);
+}
@@ -0,0 +1,154 @@
+//! Tests for custom encode/decode hooks
+
+use compress_json_rs::{
+    Compressed, ExtDecoder, ExtEncoder, ExtValue, compress, compress_ext, decompress,
+    decompress_ext,
+};
+use serde_json::json;
+
+/// A calendar date, stored as `D|YYYY-MM-DD`
+#[derive(Debug, Clone, PartialEq)]
+struct Date {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+struct DateCodec;
+
+impl ExtEncoder<Date> for DateCodec {
+    fn tag(&self) -> &str {
+        "D"
+    }
+
+    fn encode(&self, d: &Date) -> Option<String> {
+        Some(format!("{:04}-{:02}-{:02}", d.year, d.month, d.day))
+    }
+}
+
+impl ExtDecoder<Date> for DateCodec {
+    fn tag(&self) -> &str {
+        "D"
+    }
+
+    fn decode(&self, payload: &str) -> Option<Date> {
+        let mut parts = payload.split('-');
+        Some(Date {
+            year: parts.next()?.parse().ok()?,
+            month: parts.next()?.parse().ok()?,
+            day: parts.next()?.parse().ok()?,
+        })
+    }
+}
+
+fn date(year: u16, month: u8, day: u8) -> ExtValue<Date> {
+    ExtValue::Custom(Date { year, month, day })
+}
+
+#[test]
+fn test_date_roundtrip() {
+    let value = ExtValue::Object(vec![
+        ("name".to_string(), ExtValue::String("launch".to_string())),
+        ("created".to_string(), date(2024, 1, 15)),
+        (
+            "history".to_string(),
+            ExtValue::Array(vec![date(2023, 12, 1), date(2024, 1, 15), ExtValue::Null]),
+        ),
+    ]);
+
+    let compressed = compress_ext(&value, &[&DateCodec]);
+    assert!(compressed.values.contains(&"D|2024-01-15".to_string()));
+    // The repeated date is stored once
+    assert_eq!(
+        compressed
+            .values
+            .iter()
+            .filter(|v| v.starts_with("D|"))
+            .count(),
+        2
+    );
+
+    assert_eq!(decompress_ext(compressed, &[&DateCodec]), value);
+}
+
+#[test]
+fn test_strings_with_custom_tag_are_escaped() {
+    let value: ExtValue<Date> = ExtValue::Array(vec![
+        ExtValue::String("D|2024-01-15".to_string()),
+        ExtValue::String("Dx|plain".to_string()),
+    ]);
+
+    let compressed = compress_ext(&value, &[&DateCodec]);
+    assert!(compressed.values.contains(&"s|D|2024-01-15".to_string()));
+    assert_eq!(decompress_ext(compressed, &[&DateCodec]), value);
+}
+
+#[test]
+fn test_plain_json_matches_compress() {
+    let data = json!({"a": [1, "x", null, true], "b": {"c": "n|1"}});
+    let value: ExtValue<Date> = data.clone().into();
+
+    let compressed = compress_ext(&value, &[&DateCodec]);
+    assert_eq!(compressed, compress(&data));
+    assert_eq!(decompress(compressed), data);
+}
+
+#[test]
+#[should_panic(expected = "unsupported data type")]
+fn test_custom_value_without_encoder_panics() {
+    compress_ext(&date(2024, 1, 1), &[]);
+}
+
+#[test]
+#[should_panic(expected = "extension tag")]
+fn test_reserved_tag_panics() {
+    struct Bad;
+    impl ExtEncoder<Date> for Bad {
+        fn tag(&self) -> &str {
+            "n"
+        }
+        fn encode(&self, _: &Date) -> Option<String> {
+            None
+        }
+    }
+    compress_ext(&date(2024, 1, 1), &[&Bad]);
+}
+
+/// Deterministic pseudo-random bytes (xorshift)
+fn random_bytes(len: usize, mut seed: u64) -> Vec<u8> {
+    (0..len)
+        .map(|_| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed as u8
+        })
+        .collect()
+}
+
+#[test]
+fn test_bytes_roundtrip() {
+    for len in [0, 1, 2, 3, 4096, 4097, 4098] {
+        let value: ExtValue<Date> = ExtValue::Array(vec![
+            ExtValue::Bytes(random_bytes(len, 0x9E37_79B9_7F4A_7C15)),
+            ExtValue::String("x|not bytes".to_string()),
+        ]);
+        let compressed = compress_ext(&value, &[]);
+        assert!(compressed.values.iter().any(|v| v.starts_with("x|")));
+        assert_eq!(decompress_ext(compressed, &[]), value, "len {len}");
+    }
+}
+
+#[test]
+fn test_bytes_decode_as_base64_in_plain_json() {
+    let value: ExtValue<Date> = ExtValue::Bytes(vec![0, 1, 2]);
+    let compressed = compress_ext(&value, &[]);
+    assert_eq!(decompress(compressed), json!("AAEC"));
+}
+
+#[test]
+fn test_inline_root() {
+    let c = Compressed::new(Vec::new(), "s|hello");
+    let value: ExtValue<Date> = decompress_ext(c, &[]);
+    assert_eq!(value, ExtValue::String("hello".to_string()));
+}
@@ -0,0 +1,70 @@
+//! Tests for the configured compressor object
+
+mod sample;
+
+use compress_json_rs::{
+    CONFIG, CompressError, CompressJson, Compressed, Config, DecompressError, compress,
+    compress_with_config, decompress, decompress_ordered,
+};
+use serde_json::json;
+
+fn sorted() -> CompressJson {
+    CompressJson::new(Config {
+        sort_key: true,
+        ..Config::default()
+    })
+}
+
+#[test]
+fn test_sort_key_roundtrip() {
+    let codec = sorted();
+    assert!(codec.config().sort_key);
+    let data = sample::sample();
+    let compressed = codec.compress(&data);
+    assert_eq!(compressed, compress_with_config(&data, codec.config()));
+    assert_eq!(codec.decompress(compressed.clone()), data);
+    assert_eq!(codec.try_decompress(&compressed), Ok(data));
+}
+
+#[test]
+fn test_sort_key_orders_stored_keys() {
+    let compressed = sorted()
+        .compress_str(r#"{"b": 1, "c": 2, "a": 3}"#)
+        .unwrap();
+    let keys: Vec<String> = decompress_ordered(compressed)
+        .unwrap()
+        .into_iter()
+        .map(|(k, _)| k)
+        .collect();
+    assert_eq!(keys, ["a", "b", "c"]);
+}
+
+#[test]
+fn test_default_matches_free_functions() {
+    let codec = CompressJson::default();
+    assert_eq!(codec, CompressJson::from(CONFIG));
+    let data = json!({"id": 1, "tags": ["x", "y"]});
+    assert_eq!(codec.compress(&data), compress(&data));
+    assert_eq!(
+        codec.decompress(compress(&data)),
+        decompress(compress(&data))
+    );
+}
+
+#[test]
+fn test_fallible_methods_use_config() {
+    let codec = CompressJson::new(Config {
+        max_value_len: Some(3),
+        max_depth: Some(2),
+        ..Config::default()
+    });
+    assert!(matches!(
+        codec.try_compress(&json!(["long"])),
+        Err(CompressError::ValueTooLong { .. })
+    ));
+    let deep: Compressed = compress(&json!([[[1]]]));
+    assert_eq!(
+        codec.try_decompress(&deep),
+        Err(DecompressError::MaxDepthExceeded { limit: 2 })
+    );
+}
@@ -0,0 +1,66 @@
+//! Tests for the gzip integration
+#![cfg(feature = "flate2")]
+
+mod sample;
+
+use compress_json_rs::{compress, compress_gzip, decompress_gzip, size_report};
+use serde_json::json;
+
+#[test]
+fn test_report_fields_are_consistent() {
+    let data = sample::sample();
+    let report = size_report(&data);
+
+    assert_eq!(
+        report.raw_bytes,
+        serde_json::to_string(&data).unwrap().len()
+    );
+    assert_eq!(
+        report.compressed_bytes,
+        serde_json::to_string(&compress(&data)).unwrap().len()
+    );
+    assert!(report.gzip_bytes > 0);
+    assert!(report.gzip_bytes < report.raw_bytes);
+    assert_eq!(
+        report.compressed_ratio(),
+        report.compressed_bytes as f64 / report.raw_bytes as f64
+    );
+}
+
+#[test]
+fn test_repetitive_data_compresses() {
+    let rows: Vec<_> = (0..100)
+        .map(|i| json!({"id": i % 5, "status": "active", "region": "HK"}))
+        .collect();
+    let report = size_report(&json!(rows));
+    assert!(report.compressed_ratio() < 0.5);
+    assert!(report.gzip_ratio() < 0.5);
+}
+
+#[test]
+fn test_gzip_roundtrip() {
+    let data = sample::sample();
+    let bytes = compress_gzip(&data);
+    assert_eq!(decompress_gzip(&bytes).unwrap(), data);
+}
+
+#[test]
+fn test_gzip_smaller_than_plain_compressed() {
+    let rows: Vec<_> = (0..200)
+        .map(|i| json!({"id": i, "name": format!("user-{i}"), "status": "active"}))
+        .collect();
+    let data = json!(rows);
+    let plain = serde_json::to_string(&compress(&data)).unwrap();
+    let gzipped = compress_gzip(&data);
+    assert!(
+        gzipped.len() < plain.len() / 2,
+        "{} vs {}",
+        gzipped.len(),
+        plain.len()
+    );
+}
+
+#[test]
+fn test_decompress_gzip_rejects_garbage() {
+    assert!(decompress_gzip(b"not gzip").is_err());
+}
@@ -0,0 +1,51 @@
+//! Tests for content hashing
+
+mod sample;
+
+use compress_json_rs::{
+    Compressed, Config, compress, compress_str_with_config, compress_with_config, content_hash,
+};
+use serde_json::json;
+
+#[test]
+fn test_reordered_keys_hash_identically() {
+    let config = Config {
+        sort_key: true,
+        ..Config::default()
+    };
+    let a = compress_str_with_config(r#"{"b": 1, "a": {"y": [1, 2], "x": "s"}}"#, &config).unwrap();
+    let b = compress_str_with_config(r#"{"a": {"x": "s", "y": [1, 2]}, "b": 1}"#, &config).unwrap();
+    assert_eq!(a, b);
+    assert_eq!(content_hash(&a), content_hash(&b));
+}
+
+#[test]
+fn test_hash_distinguishes_documents() {
+    assert_ne!(
+        content_hash(&compress(&json!({"a": 1}))),
+        content_hash(&compress(&json!({"a": 2})))
+    );
+    // Same document, different store layout
+    let canonical = compress(&json!({"a": 1, "b": 2}));
+    let other = Compressed::new(
+        ["b", "a", "a|0|1", "n|2", "n|1", "o|2|3|4"]
+            .map(String::from)
+            .to_vec(),
+        "5",
+    );
+    assert_ne!(content_hash(&canonical), content_hash(&other));
+}
+
+#[test]
+fn test_hash_matches_serialized_bytes() {
+    // Reference FNV-1a over the serialized form, as another implementation would compute it
+    let c = compress_with_config(&sample::sample(), &Config::default());
+    let text = serde_json::to_string(&c).unwrap();
+    let expected = text.bytes().fold(0xcbf29ce484222325u64, |h, b| {
+        (h ^ u64::from(b)).wrapping_mul(0x100000001b3)
+    });
+    assert_eq!(content_hash(&c), expected);
+    // Non-ASCII is hashed as raw UTF-8, like `JSON.stringify`
+    let c = compress(&json!("é"));
+    assert_eq!(serde_json::to_string(&c).unwrap(), r#"[["é"],"0"]"#);
+}
@@ -1,7 +1,7 @@
 //! Tests for helper functions
 //! Ported from compress-json/test/helpers-test.ts
 
-use compress_json_rs::{trim_undefined, trim_undefined_recursively};
+use compress_json_rs::{trim_undefined, trim_undefined_recursively, trim_undefined_with};
 use serde_json::{Map, Value, json};
 
 #[test]
@@ -68,6 +68,35 @@ fn test_trim_undefined_all_null() {
     assert!(all_null.is_empty(), "All null fields should be removed");
 }
 
+#[test]
+fn test_trim_undefined_with_keeps_nulls() {
+    let mut data: Map<String, Value> = serde_json::from_value(json!({
+        "name": "Alice",
+        "email": null,
+        "phone": "$undefined",
+        "nested": {"fax": "$undefined"}
+    }))
+    .unwrap();
+
+    trim_undefined_with(&mut data, |v| v == "$undefined");
+    assert_eq!(
+        Value::Object(data),
+        json!({"name": "Alice", "email": null, "nested": {"fax": "$undefined"}}),
+        "Only the top-level sentinel should be removed"
+    );
+}
+
+#[test]
+fn test_trim_undefined_with_null_matches_trim_undefined() {
+    let original: Map<String, Value> =
+        serde_json::from_value(json!({"a": null, "b": 0, "c": false, "d": null})).unwrap();
+    let mut with = original.clone();
+    let mut default = original;
+    trim_undefined_with(&mut with, Value::is_null);
+    trim_undefined(&mut default);
+    assert_eq!(with, default);
+}
+
 #[test]
 fn test_trim_undefined_recursively_removes_nested_nulls() {
     // Create nested structure with nulls
@@ -0,0 +1,119 @@
+//! Tests for compression and decompression instrumentation
+
+mod sample;
+
+use compress_json_rs::{
+    CONFIG, Config, Instrumentation, add_value, compress_instrumented, compress_with_config,
+    decompress, decompress_instrumented, instrument, make_memory, mem_to_values,
+};
+use serde_json::json;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+#[derive(Default)]
+struct Tally {
+    stored: AtomicUsize,
+    schemas_reused: AtomicUsize,
+    depths: Mutex<Vec<usize>>,
+}
+
+impl Instrumentation for Tally {
+    fn on_value_stored(&self, _value: &str) {
+        self.stored.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn on_schema_reused(&self, _schema: &str) {
+        self.schemas_reused.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn on_depth(&self, depth: usize) {
+        self.depths.lock().unwrap().push(depth);
+    }
+}
+
+#[test]
+fn test_stored_values_equal_store_len() {
+    let data = sample::sample();
+    for config in [
+        CONFIG,
+        Config {
+            dedup: false,
+            chunk_strings: Some(4),
+            extend_schemas: true,
+            frequency_keys: true,
+            ..CONFIG
+        },
+    ] {
+        let tally = Arc::new(Tally::default());
+        let compressed = compress_instrumented(&data, &config, tally.clone());
+        assert_eq!(
+            tally.stored.load(Ordering::Relaxed),
+            compressed.values.len()
+        );
+        assert_eq!(compressed, compress_with_config(&data, &config));
+    }
+}
+
+#[test]
+fn test_schema_reuse() {
+    let data = json!([{"id": 1, "name": "a"}, {"id": 2, "name": "b"}, {"id": 3}]);
+    let tally = Arc::new(Tally::default());
+    compress_instrumented(&data, &CONFIG, tally.clone());
+    assert_eq!(tally.schemas_reused.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn test_depths() {
+    let data = json!({"a": [1, null, {"b": "x"}], "c": true});
+    let tally = Arc::new(Tally::default());
+    let compressed = compress_instrumented(&data, &CONFIG, tally.clone());
+    let mut depths = tally.depths.lock().unwrap().clone();
+    depths.sort_unstable();
+    // The null element is written as a marker without being added
+    assert_eq!(depths, [0, 1, 1, 2, 2, 3]);
+
+    let decoded = Tally::default();
+    assert_eq!(decompress_instrumented(&compressed, &decoded), data);
+    let mut depths = decoded.depths.into_inner().unwrap();
+    depths.sort_unstable();
+    assert_eq!(depths, [0, 1, 1, 2, 2, 2, 3]);
+}
+
+#[test]
+fn test_decompress_matches_decompress() {
+    let data = sample::sample();
+    let compressed = compress_with_config(
+        &data,
+        &Config {
+            pack_int_arrays: true,
+            chunk_strings: Some(3),
+            ..CONFIG
+        },
+    );
+    let tally = Tally::default();
+    assert_eq!(
+        decompress_instrumented(&compressed, &tally),
+        decompress(compressed)
+    );
+    assert_eq!(tally.stored.load(Ordering::Relaxed), 0);
+
+    // Scalar roots, including ones written in place of the root key
+    for root in [json!("text"), json!(1.5), json!(null)] {
+        let compressed = compress_with_config(&root, &CONFIG);
+        let tally = Tally::default();
+        assert_eq!(decompress_instrumented(&compressed, &tally), root);
+        assert_eq!(tally.depths.into_inner().unwrap(), [0]);
+    }
+}
+
+#[test]
+fn test_memory_is_uninstrumented_until_set() {
+    let tally = Arc::new(Tally::default());
+    let mut mem = make_memory();
+    add_value(&mut mem, &json!(["a", "b"]));
+    instrument(&mut mem, tally.clone());
+    add_value(&mut mem, &json!(["b", "c"]));
+    assert_eq!(mem_to_values(&mem).len(), 5);
+    // Only "c" and the second array are new
+    assert_eq!(tally.stored.load(Ordering::Relaxed), 2);
+}
@@ -0,0 +1,44 @@
+//! Tests for the Key newtype
+
+use compress_json_rs::{Compressed, Key, ParseKeyError, compress, decode};
+use serde_json::json;
+
+#[test]
+fn test_key_display_parse_roundtrip() {
+    for text in ["0", "9", "A", "z", "10", "zz", "_", ""] {
+        let key: Key = text.parse().unwrap();
+        assert_eq!(key.as_str(), text);
+        assert_eq!(key.to_string().parse::<Key>(), Ok(key));
+    }
+}
+
+#[test]
+fn test_key_parse_rejects_non_keys() {
+    for text in ["a|0", "-1", "1.5", " 0", "__"] {
+        let err: ParseKeyError = text.parse::<Key>().unwrap_err();
+        assert!(err.to_string().contains(text));
+    }
+}
+
+#[test]
+fn test_key_null() {
+    assert!(Key::default().is_null());
+    assert!(Key::from("_").is_null());
+    assert!(!Key::from("0").is_null());
+    assert_eq!(compress(&json!(null)).root, Key::default());
+}
+
+#[test]
+fn test_key_serializes_as_string() {
+    let key = Key::from("1A");
+    assert_eq!(serde_json::to_string(&key).unwrap(), "\"1A\"");
+    let back: Key = serde_json::from_str("\"1A\"").unwrap();
+    assert_eq!(back, key);
+}
+
+#[test]
+fn test_parsed_key_decodes() {
+    let Compressed { values, root } = compress(&json!(["x", "y"]));
+    let key: Key = root.to_string().parse().unwrap();
+    assert_eq!(decode(&values, &key), json!(["x", "y"]));
+}
@@ -0,0 +1,48 @@
+//! Tests for root value kind inspection
+
+use compress_json_rs::{Compressed, Config, ValueKind, compress, compress_with_config, root_kind};
+use serde_json::json;
+
+#[test]
+fn test_root_kind_of_each_type() {
+    assert_eq!(root_kind(&compress(&json!(null))), ValueKind::Null);
+    assert_eq!(root_kind(&compress(&json!(true))), ValueKind::Bool);
+    assert_eq!(root_kind(&compress(&json!(false))), ValueKind::Bool);
+    assert_eq!(root_kind(&compress(&json!(42))), ValueKind::Number);
+    assert_eq!(root_kind(&compress(&json!(-1.5))), ValueKind::Number);
+    assert_eq!(root_kind(&compress(&json!("hello"))), ValueKind::String);
+    assert_eq!(root_kind(&compress(&json!([]))), ValueKind::Array);
+    assert_eq!(root_kind(&compress(&json!([1, 2]))), ValueKind::Array);
+    assert_eq!(root_kind(&compress(&json!({}))), ValueKind::Object);
+    assert_eq!(root_kind(&compress(&json!({"a": 1}))), ValueKind::Object);
+}
+
+#[test]
+fn test_root_kind_empty_root_is_null() {
+    let c = Compressed::new(vec!["a|".to_string()], String::new());
+    assert_eq!(root_kind(&c), ValueKind::Null);
+
+    let c = Compressed::new(vec![], "_".to_string());
+    assert_eq!(root_kind(&c), ValueKind::Null);
+}
+
+#[test]
+fn test_root_kind_prefixed_strings() {
+    // Escaped strings must not be mistaken for the prefix they escape
+    assert_eq!(root_kind(&compress(&json!("a|b"))), ValueKind::String);
+    assert_eq!(root_kind(&compress(&json!("o|"))), ValueKind::String);
+
+    let config = Config {
+        chunk_strings: Some(4),
+        ..Config::default()
+    };
+    let c = compress_with_config(&json!("a long chunked string"), &config);
+    assert!(c.values[c.values.len() - 1].starts_with("c|"));
+    assert_eq!(root_kind(&c), ValueKind::String);
+}
+
+#[test]
+fn test_root_kind_special_number() {
+    let c = Compressed::new(vec!["N|+".to_string()], "0".to_string());
+    assert_eq!(root_kind(&c), ValueKind::Number);
+}
@@ -0,0 +1,44 @@
+//! Tests for the line-oriented text form
+
+mod sample;
+
+use compress_json_rs::{compress, compress_to_lines, decompress_from_lines};
+use serde_json::{Value, json};
+
+#[test]
+fn test_roundtrip_sample() {
+    let data = sample::sample();
+    let text = compress_to_lines(&data);
+    assert_eq!(text.lines().count(), compress(&data).store_len() + 1);
+    assert_eq!(decompress_from_lines(&text), data);
+}
+
+#[test]
+fn test_embedded_newlines_and_backslashes() {
+    let data = json!({
+        "poem": "roses\nviolets\r\n",
+        "path": "C:\\new\\root",
+        "literal": "\\n is not a newline",
+        "end": "trailing\\",
+    });
+    let text = compress_to_lines(&data);
+    let compressed = compress(&data);
+    assert_eq!(text.split('\n').count(), compressed.store_len() + 1);
+    assert!(text.contains("roses\\nviolets\\r\\n"));
+    assert!(text.contains("C:\\\\new\\\\root"));
+    assert_eq!(decompress_from_lines(&text), data);
+}
+
+#[test]
+fn test_root_is_last_line() {
+    let text = compress_to_lines(&json!(["x", true]));
+    assert_eq!(text, "x\nb|T\na|0|1\n2");
+    assert_eq!(compress_to_lines(&Value::Null), "");
+    assert_eq!(decompress_from_lines(""), Value::Null);
+    assert_eq!(decompress_from_lines("\n0"), json!(""));
+}
+
+#[test]
+fn test_unknown_escapes_are_kept() {
+    assert_eq!(decompress_from_lines("a\\tb\n0"), json!("a\\tb"));
+}
@@ -0,0 +1,178 @@
+//! Tests for memory snapshots
+
+mod sample;
+
+use compress_json_rs::{
+    Config, DecompressError, MemorySnapshot, add_compressed, add_value, compress,
+    compress_with_config, debug_dump, decode, decompress, dictionary_len, make_memory,
+    make_memory_with_config, mem_to_values, restore, snapshot,
+};
+use serde_json::json;
+
+#[test]
+fn test_restore_yields_pre_add_state() {
+    let mut mem = make_memory();
+    let root = add_value(&mut mem, &sample::sample());
+    let saved = snapshot(&mem);
+    let before_values = mem_to_values(&mem);
+    let before_dump = debug_dump(&mem);
+
+    add_value(&mut mem, &json!({"extra": [1, 2, 3], "name": "new"}));
+    assert_ne!(mem_to_values(&mem), before_values);
+
+    let restored = restore(saved).unwrap();
+    assert_eq!(mem_to_values(&restored), before_values);
+    assert_eq!(debug_dump(&restored), before_dump);
+    assert_eq!(decode(&mem_to_values(&restored), &root), sample::sample());
+}
+
+#[test]
+fn test_restored_memory_continues_like_original() {
+    let mut mem = make_memory();
+    add_value(
+        &mut mem,
+        &json!([{"id": 1, "tag": "a"}, {"id": 2, "tag": "b"}]),
+    );
+    let mut fork = restore(snapshot(&mem)).unwrap();
+
+    let next = json!([{"id": 3, "tag": "a"}, "b", {"other": true}]);
+    assert_eq!(add_value(&mut fork, &next), add_value(&mut mem, &next));
+    assert_eq!(mem_to_values(&fork), mem_to_values(&mem));
+}
+
+#[test]
+fn test_snapshot_serde_roundtrip_keeps_config() {
+    let config = Config {
+        sort_key: true,
+        chunk_strings: Some(4),
+        ..Config::default()
+    };
+    let mut mem = make_memory_with_config(&config);
+    add_value(&mut mem, &json!({"b": "long string", "a": 1}));
+    let saved = snapshot(&mem);
+
+    let text = serde_json::to_string(&saved).unwrap();
+    let loaded: MemorySnapshot = serde_json::from_str(&text).unwrap();
+    assert_eq!(loaded, saved);
+
+    // The chunking config survives, so the same string reuses its entry
+    let mut restored = restore(loaded).unwrap();
+    let len = mem_to_values(&restored).len();
+    add_value(&mut restored, &json!("long string"));
+    assert_eq!(mem_to_values(&restored).len(), len);
+}
+
+#[test]
+fn test_restore_rejects_corrupted_schema() {
+    let mut mem = make_memory();
+    add_value(&mut mem, &json!({"a": 1}));
+    let mut saved = serde_json::to_value(snapshot(&mem)).unwrap();
+    // The object's schema reference points past the store
+    saved["values"] = json!(["a", "n|1", "o|9|1"]);
+    let corrupted: MemorySnapshot = serde_json::from_value(saved).unwrap();
+    assert_eq!(
+        restore(corrupted).err(),
+        Some(DecompressError::IndexOutOfBounds { index: 9, len: 3 })
+    );
+}
+
+#[test]
+fn test_repeated_long_string_stored_once() {
+    let long = "lorem ipsum ".repeat(90);
+    let data = serde_json::Value::Array(vec![json!(long); 5000]);
+    let compressed = compress(&data);
+    // The string and the array
+    assert_eq!(compressed.store_len(), 2);
+    assert_eq!(decompress(compressed), data);
+}
+
+#[test]
+fn test_long_strings_differing_between_samples_stay_distinct() {
+    // Same length, start and end; they differ only in one middle byte
+    let mut a = "x".repeat(1000);
+    let mut b = a.clone();
+    a.replace_range(100..101, "a");
+    b.replace_range(100..101, "b");
+    let data = json!([a, b, a, b]);
+    let compressed = compress(&data);
+    assert_eq!(compressed.store_len(), 3);
+    assert_eq!(decompress(compressed), data);
+}
+
+#[test]
+fn test_restored_memory_matches_long_strings() {
+    let long = json!("y".repeat(300));
+    let mut mem = make_memory();
+    let key = add_value(&mut mem, &long);
+    let mut resumed = restore(snapshot(&mem)).unwrap();
+    assert_eq!(add_value(&mut resumed, &long), key);
+    assert_eq!(mem_to_values(&resumed).len(), 1);
+}
+
+#[test]
+fn test_add_compressed_shares_existing_entries() {
+    let data = sample::sample();
+    let part = compress(&data);
+
+    let mut mem = make_memory();
+    let first = add_value(&mut mem, &data);
+    let len = mem_to_values(&mem).len();
+    // Every entry of the part is already stored
+    assert_eq!(add_compressed(&mut mem, &part), first);
+    assert_eq!(mem_to_values(&mem).len(), len);
+
+    let mut mem = make_memory();
+    add_value(&mut mem, &json!(["unrelated", 42]));
+    let key = add_compressed(&mut mem, &part);
+    assert_eq!(decode(&mem_to_values(&mem), &key), data);
+}
+
+#[test]
+fn test_max_dictionary_len_evicts_least_recently_used() {
+    let config = Config {
+        max_dictionary_len: Some(2),
+        ..Config::default()
+    };
+    let mut mem = make_memory_with_config(&config);
+    let a = add_value(&mut mem, &json!("a"));
+    let b = add_value(&mut mem, &json!("b"));
+    // Using "a" again makes "b" the least recently used
+    assert_eq!(add_value(&mut mem, &json!("a")), a);
+    add_value(&mut mem, &json!("c"));
+    assert_eq!(dictionary_len(&mem), 2);
+    assert_eq!(add_value(&mut mem, &json!("a")), a);
+
+    // "b" was evicted, so it is stored again under a new key
+    let b_again = add_value(&mut mem, &json!("b"));
+    assert_ne!(b_again, b);
+    let values = mem_to_values(&mem);
+    assert_eq!(values, ["a", "b", "c", "b"]);
+    assert_eq!(decode(&values, &b), json!("b"));
+    assert_eq!(decode(&values, &b_again), json!("b"));
+}
+
+#[test]
+fn test_max_dictionary_len_roundtrips_each_record() {
+    let config = Config {
+        max_dictionary_len: Some(8),
+        ..Config::default()
+    };
+    let long = "x".repeat(300);
+    let mut mem = make_memory_with_config(&config);
+    let records: Vec<_> = (0..500)
+        .map(|i| json!({"id": i % 37, "name": format!("{long}{}", i % 5), "tags": ["t", i]}))
+        .collect();
+    let keys: Vec<_> = records.iter().map(|r| add_value(&mut mem, r)).collect();
+    assert!(dictionary_len(&mem) <= 8);
+    let values = mem_to_values(&mem);
+    for (record, key) in records.iter().zip(&keys) {
+        assert_eq!(&decode(&values, key), record);
+    }
+    // Same output as unbounded compression when nothing needs evicting
+    let roomy = Config {
+        max_dictionary_len: Some(10_000),
+        ..Config::default()
+    };
+    let data = sample::sample();
+    assert_eq!(compress_with_config(&data, &roomy), compress(&data));
+}
@@ -1,7 +1,8 @@
 //! Tests for number encoding/decoding functionality
 //! Ported from compress-json/test/number-test.ts
 
-use compress_json_rs::{compress, decompress};
+use compress_json_rs::codec::{decode_num, encode_num};
+use compress_json_rs::{Compressed, DecodeScratch, Decoder, compress, decompress, try_decompress};
 use serde_json::json;
 
 /// Test that a number roundtrips correctly through compress/decompress
@@ -152,3 +153,167 @@ fn test_all_numbers_comprehensive() {
         test_number_roundtrip(x);
     }
 }
+
+#[test]
+fn test_key_alphabet_constants() {
+    use compress_json_rs::{KEY_ALPHABET, KEY_BASE, key_for_index};
+
+    assert_eq!(KEY_BASE, 62);
+    assert_eq!(KEY_ALPHABET.len(), KEY_BASE);
+
+    // The last single-character key, then the first two-character key
+    assert_eq!(key_for_index(61), "z");
+    assert_eq!(key_for_index(62), "10");
+    assert_eq!(key_for_index(62).len(), 2);
+}
+
+/// Exponential values from the tests above
+const EXPONENTIAL: [f64; 5] = [1.23456789123789e22, 1.2e-9, 1.2e-10, 1e21, 2e-13];
+
+#[test]
+fn test_exponential_codec_string_roundtrip() {
+    for x in EXPONENTIAL.into_iter().flat_map(|x| [x, -x]) {
+        let encoded = encode_num(x);
+        let text = encoded.strip_prefix("n|").unwrap();
+        // Plain decimal digits, which any JSON number parser accepts
+        assert!(
+            text.bytes()
+                .all(|b| b.is_ascii_digit() || b == b'.' || b == b'-'),
+            "unexpected rendering {encoded}"
+        );
+        assert_eq!(decode_num(&encoded), x, "codec roundtrip failed for {x}");
+        assert_eq!(encode_num(decode_num(&encoded)), encoded);
+    }
+}
+
+#[test]
+fn test_decode_exponent_notation_from_other_implementations() {
+    // JavaScript's `String(n)` switches to exponent form for these
+    for (text, x) in [
+        ("n|1e+21", 1e21),
+        ("n|2e-13", 2e-13),
+        ("n|1.2e-10", 1.2e-10),
+        ("n|1.23456789123789e+22", 1.23456789123789e22),
+        ("n|-1E+21", -1e21),
+    ] {
+        assert_eq!(decode_num(text), x);
+        let c = Compressed::new(vec![text.to_string()], "0");
+        assert_eq!(
+            decompress(c).as_f64(),
+            Some(x),
+            "decompress failed for {text}"
+        );
+    }
+}
+
+#[test]
+fn test_integer_decodes_as_integer_number() {
+    let c = compress(&json!(42));
+    assert_eq!(c.values, ["n|42"]);
+    for decoded in [
+        decompress(c.clone()),
+        Decoder::new(&c.values).decode(&c.root),
+        DecodeScratch::new().decode(&c.values, &c.root),
+        try_decompress(&c).unwrap(),
+    ] {
+        assert!(decoded.is_i64(), "expected an integer, got {decoded:?}");
+        assert_eq!(decoded.to_string(), "42");
+    }
+}
+
+#[test]
+fn test_whole_valued_float_keeps_float_kind() {
+    // Stored as `n|42.0`, so the decoded number is a float like the input
+    let c = compress(&json!(42.0));
+    assert_eq!(c.values, ["n|42.0"]);
+    for decoded in [
+        decompress(c.clone()),
+        Decoder::new(&c.values).decode(&c.root),
+        DecodeScratch::new().decode(&c.values, &c.root),
+        try_decompress(&c).unwrap(),
+    ] {
+        assert!(decoded.is_f64(), "expected a float, got {decoded:?}");
+        assert_eq!(decoded, json!(42.0));
+    }
+
+    let decoded = decompress(Compressed::new(vec!["n|-7".to_string()], "0"));
+    assert_eq!(decoded.as_i64(), Some(-7));
+    let decoded = decompress(Compressed::new(vec![format!("n|{}", u64::MAX)], "0"));
+    assert_eq!(decoded.as_u64(), Some(u64::MAX));
+    let decoded = decompress(Compressed::new(vec!["n|42.5".to_string()], "0"));
+    assert!(decoded.is_f64());
+}
+
+#[test]
+fn test_number_kind_roundtrip() {
+    let data = json!([1, 1.0, -3, -3.0, 0, 0.0, 1e21, 1.5, 1u64 << 63]);
+    let compressed = compress(&data);
+    // `1` and `1.0` are distinct entries
+    assert!(compressed.values.contains(&"n|1".to_string()));
+    assert!(compressed.values.contains(&"n|1.0".to_string()));
+
+    let restored = decompress(compressed);
+    assert_eq!(restored, data);
+    for (original, decoded) in data
+        .as_array()
+        .unwrap()
+        .iter()
+        .zip(restored.as_array().unwrap())
+    {
+        assert_eq!(decoded.is_i64(), original.is_i64(), "{original}");
+        assert_eq!(decoded.is_u64(), original.is_u64(), "{original}");
+        assert_eq!(decoded.is_f64(), original.is_f64(), "{original}");
+    }
+}
+
+#[test]
+fn test_encode_num_table() {
+    // Around the magnitudes where `JSON.stringify` and `{:e}`-style
+    // formatters switch to exponent form; the codec never does
+    let table: &[(f64, &str)] = &[
+        (0.0, "n|0"),
+        (-0.0, "n|-0"),
+        (1.0, "n|1"),
+        (-1.0, "n|-1"),
+        (0.5, "n|0.5"),
+        (0.1 + 0.2, "n|0.30000000000000004"),
+        (1e10, "n|10000000000"),
+        (1e15, "n|1000000000000000"),
+        (1e16, "n|10000000000000000"),
+        (1.5e16, "n|15000000000000000"),
+        (1e20, "n|100000000000000000000"),
+        (1e21, "n|1000000000000000000000"),
+        (1.23456789123789e22, "n|12345678912378900000000"),
+        (9007199254740993.0, "n|9007199254740992"),
+        (123456.789, "n|123456.789"),
+        (1e-4, "n|0.0001"),
+        (1e-5, "n|0.00001"),
+        (1e-6, "n|0.000001"),
+        (1e-7, "n|0.0000001"),
+        (1.5e-7, "n|0.00000015"),
+        (-2e-13, "n|-0.0000000000002"),
+        (5e-324, &format!("n|0.{}5", "0".repeat(323))),
+        (f64::MAX, &format!("n|17976931348623157{}", "0".repeat(292))),
+    ];
+    for &(x, expected) in table {
+        assert_eq!(encode_num(x), expected, "{x:e}");
+        assert_eq!(decode_num(expected).to_bits(), x.to_bits(), "{expected}");
+    }
+}
+
+#[test]
+fn test_integers_near_u64_max_stay_integers() {
+    // `f64` rounds these up to 2^64, beyond the integer range; they are
+    // kept at `u64::MAX` unless stored exactly
+    for i in [u64::MAX, u64::MAX - 1] {
+        let expected = if cfg!(feature = "arbitrary_precision") {
+            i
+        } else {
+            u64::MAX
+        };
+        let decoded = decompress(compress(&json!(i)));
+        assert_eq!(decoded.as_u64(), Some(expected), "{i}");
+    }
+    let decoded = decompress(compress(&json!(i64::MIN)));
+    assert_eq!(decoded.as_i64(), Some(i64::MIN));
+}
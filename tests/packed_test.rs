@@ -0,0 +1,203 @@
+//! Tests for packed integer arrays
+
+use compress_json_rs::{
+    Compressed, Config, DecompressError, ValueKind, array_get, array_iter, compress,
+    compress_with_config, compress_with_report, decompress, decompress_rows, decompress_safe,
+    get_path, get_paths, root_kind, set_path, try_compress_with_config, try_decompress_with_config,
+};
+use serde_json::{Value, json};
+
+const PACKED: Config = Config {
+    pack_int_arrays: true,
+    ..compress_json_rs::CONFIG
+};
+
+/// 1000 increasing 64-bit IDs in a few clusters with small gaps.
+fn clustered_ids() -> Vec<u64> {
+    let mut ids = Vec::with_capacity(1000);
+    let mut id = 1_700_000_000_000_000_000u64;
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    for i in 0..1000 {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        // A new cluster every 250 IDs
+        id += if i % 250 == 0 {
+            1 << 40
+        } else {
+            1 + state % 20
+        };
+        ids.push(id);
+    }
+    ids
+}
+
+#[test]
+fn test_clustered_ids_roundtrip_exactly_in_a_much_smaller_store() {
+    let ids = clustered_ids();
+    let data = json!({"ids": ids});
+
+    let packed = compress_with_config(&data, &PACKED);
+    let restored = decompress(packed.clone());
+    assert_eq!(restored, data);
+    let restored: Vec<u64> = serde_json::from_value(restored["ids"].clone()).unwrap();
+    assert_eq!(restored, ids);
+
+    // Without packing, IDs beyond 2^53 are rounded unless stored as text,
+    // so measure against the JSON text itself
+    let json_len = data.to_string().len();
+    let packed_len = serde_json::to_string(&packed).unwrap().len();
+    assert!(packed_len * 5 < json_len, "{packed_len} vs {json_len}");
+    // One entry for the IDs instead of one per element
+    assert!(packed.store_len() < 5);
+}
+
+#[test]
+fn test_entry_format() {
+    let c = compress_with_config(&json!([1000, 1001, 1005, 998]), &PACKED);
+    assert_eq!(c.values, ["d|G8|1|4|-7"]);
+    assert_eq!(decompress(c), json!([1000, 1001, 1005, 998]));
+}
+
+#[test]
+fn test_extremes_and_signs() {
+    let data = json!([i64::MIN, u64::MAX, 0, -1, i64::MAX, 1]);
+    let c = compress_with_config(&data, &PACKED);
+    assert!(c.values[0].starts_with("d|"));
+    assert_eq!(decompress(c), data);
+}
+
+#[test]
+fn test_only_all_integer_arrays_are_packed() {
+    let data = json!({
+        "floats": [1, 2.5],
+        "nulls": [1, null],
+        "strings": [1, "2"],
+        "empty": [],
+        "nested": [[1, 2], [3]],
+    });
+    let c = compress_with_config(&data, &PACKED);
+    let packed: Vec<&String> = c.values.iter().filter(|v| v.starts_with("d|")).collect();
+    assert_eq!(packed, ["d|1|1", "d|3"]);
+    assert_eq!(decompress(c), data);
+}
+
+#[test]
+fn test_equal_arrays_share_one_entry() {
+    let data = json!([[7, 8, 9], [7, 8, 9]]);
+    let c = compress_with_config(&data, &PACKED);
+    assert_eq!(c.values, ["d|7|1|1", "a|0|0"]);
+}
+
+#[test]
+fn test_disabled_by_default() {
+    let c = compress(&json!([1, 2, 3]));
+    assert!(!c.values.iter().any(|v| v.starts_with("d|")));
+}
+
+#[test]
+fn test_strings_with_packed_prefix_are_escaped() {
+    let data = json!(["d|1|2", [1, 2]]);
+    let c = compress_with_config(&data, &PACKED);
+    assert!(c.values.contains(&"s|d|1|2".to_string()));
+    assert_eq!(decompress(c), data);
+}
+
+#[test]
+fn test_large_ids_are_exact_and_not_reported() {
+    let config = Config {
+        error_on_precision_loss: true,
+        ..PACKED
+    };
+    let data = json!({"ids": [9007199254740993u64, 9007199254740995u64]});
+    let c = try_compress_with_config(&data, &config).unwrap();
+    assert_eq!(decompress(c), data);
+    let (_, warnings) = compress_with_report(&data, &PACKED);
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn test_with_renumbering_and_verify() {
+    let config = Config {
+        frequency_keys: true,
+        sort_values: true,
+        typed_sections: true,
+        verify_on_compress: true,
+        ..PACKED
+    };
+    let data = json!([{"tags": [3, 1, 2]}, {"tags": [3, 1, 2]}, "x", 1.5]);
+    assert_eq!(decompress(compress_with_config(&data, &config)), data);
+}
+
+#[test]
+fn test_readers() {
+    let data = json!({"ids": [10, 20, 30]});
+    let c = compress_with_config(&data, &PACKED);
+    assert_eq!(get_path(&c, "/ids"), Some(json!([10, 20, 30])));
+    assert_eq!(get_path(&c, "/ids/1"), Some(json!(20)));
+    assert_eq!(get_path(&c, "/ids/3"), None);
+    assert_eq!(get_path(&c, "/ids/1/x"), None);
+    assert_eq!(
+        get_paths(&c, &["/ids/0", "/ids/2", "/ids/x"]),
+        [Some(json!(10)), Some(json!(30)), None]
+    );
+    assert_eq!(decompress_safe(&c), Ok(data));
+
+    let root = compress_with_config(&json!([4, 5, 6]), &PACKED);
+    assert_eq!(root_kind(&root), ValueKind::Array);
+    assert_eq!(array_get(&root, 1), Ok(Some(json!(5))));
+    assert_eq!(array_get(&root, 3), Ok(None));
+    let items: Vec<Value> = array_iter(&root).unwrap().collect();
+    assert_eq!(items, [json!(4), json!(5), json!(6)]);
+    let rows: Vec<i32> = decompress_rows(root).unwrap();
+    assert_eq!(rows, [4, 5, 6]);
+}
+
+#[test]
+fn test_set_path_unpacks_the_array() {
+    let mut c = compress_with_config(&json!({"ids": [1, 2, 3]}), &PACKED);
+    set_path(&mut c, "/ids/1", &json!("two")).unwrap();
+    assert_eq!(decompress(c.clone()), json!({"ids": [1, "two", 3]}));
+    assert!(set_path(&mut c, "/ids/5", &json!(0)).is_err());
+
+    let mut c = compress_with_config(&json!({"ids": [1, 2, 3]}), &PACKED);
+    assert!(set_path(&mut c, "/ids/0/x", &json!(0)).is_err());
+}
+
+#[test]
+fn test_checked_decoding() {
+    let c = compress_with_config(&json!([1, 2, 3, 4]), &PACKED);
+    let limits = Config {
+        max_array_len: Some(3),
+        ..Config::default()
+    };
+    assert_eq!(
+        try_decompress_with_config(&c, &limits),
+        Err(DecompressError::MaxArrayLenExceeded { index: 0, limit: 3 })
+    );
+    let limits = Config {
+        max_nodes: Some(4),
+        ..Config::default()
+    };
+    assert_eq!(
+        try_decompress_with_config(&c, &limits),
+        Err(DecompressError::MaxNodesExceeded { limit: 4 })
+    );
+    let limits = Config {
+        max_depth: Some(1),
+        ..Config::default()
+    };
+    assert_eq!(
+        try_decompress_with_config(&c, &limits),
+        Err(DecompressError::MaxDepthExceeded { limit: 1 })
+    );
+
+    for malformed in ["d|", "d|1||2", "d|-", "d|1|!", "d|zzzzzzzzzzzzzzzzzzzzzzzz"] {
+        let c = Compressed::new(vec![malformed.to_string()], "0");
+        assert_eq!(
+            decompress_safe(&c),
+            Err(DecompressError::InvalidEntry { index: 0 }),
+            "{malformed}"
+        );
+    }
+}
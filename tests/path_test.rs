@@ -0,0 +1,232 @@
+//! Tests for reading and updating JSON Pointer paths
+
+use compress_json_rs::{
+    Config, PathError, compress, compress_with_config, decompress, garbage_collect, get_path,
+    get_paths, set_path, splice_path,
+};
+use serde_json::json;
+
+#[test]
+fn test_set_nested_field_changes_only_that_field() {
+    let data = json!({
+        "meta": {"version": 1, "owner": "ops"},
+        "items": [{"id": 1, "tags": ["a", "b"]}, {"id": 2, "tags": ["c"]}],
+    });
+    let mut c = compress(&data);
+    let before = c.values.clone();
+
+    set_path(&mut c, "/items/1/tags/0", &json!("z")).unwrap();
+
+    let mut expected = data.clone();
+    expected["items"][1]["tags"][0] = json!("z");
+    assert_eq!(decompress(c.clone()), expected);
+
+    // Existing entries are kept as they are; only new ones are appended
+    assert_eq!(c.values[..before.len()], before[..]);
+}
+
+#[test]
+fn test_set_path_shares_untouched_subtrees() {
+    let data = json!({"big": {"list": [1, 2, 3, 4]}, "small": 1});
+    let mut c = compress(&data);
+    let len = c.values.len();
+
+    set_path(&mut c, "/small", &json!(9)).unwrap();
+
+    // The new number and the re-encoded root object; `big` is not re-added
+    assert_eq!(c.values.len(), len + 2);
+    assert_eq!(get_path(&c, "/big"), Some(json!({"list": [1, 2, 3, 4]})));
+}
+
+#[test]
+fn test_set_path_adds_object_key() {
+    let mut c = compress(&json!({"a": {}, "b": {"x": 1}}));
+    set_path(&mut c, "/a/new", &json!([true])).unwrap();
+    set_path(&mut c, "/b/y", &json!(null)).unwrap();
+    assert_eq!(
+        decompress(c),
+        json!({"a": {"new": [true]}, "b": {"x": 1, "y": null}})
+    );
+}
+
+#[test]
+fn test_set_path_root_and_null_elements() {
+    let mut c = compress(&json!([1, null, 3]));
+    set_path(&mut c, "/0", &json!(null)).unwrap();
+    assert_eq!(decompress(c.clone()), json!([null, null, 3]));
+
+    set_path(&mut c, "", &json!({"replaced": true})).unwrap();
+    assert_eq!(decompress(c), json!({"replaced": true}));
+}
+
+#[test]
+fn test_set_path_escaped_tokens() {
+    let mut c = compress(&json!({"a/b": {"m~n": 1}}));
+    assert_eq!(get_path(&c, "/a~1b/m~0n"), Some(json!(1)));
+    set_path(&mut c, "/a~1b/m~0n", &json!(2)).unwrap();
+    assert_eq!(decompress(c), json!({"a/b": {"m~n": 2}}));
+}
+
+#[test]
+fn test_set_path_errors_leave_value_unchanged() {
+    let data = json!({"list": [1, 2], "n": 5, "empty": []});
+    let mut c = compress(&data);
+    let original = c.clone();
+
+    assert_eq!(
+        set_path(&mut c, "list", &json!(0)),
+        Err(PathError::InvalidPointer {
+            pointer: "list".to_string()
+        })
+    );
+    for (pointer, resolved) in [
+        ("/list/2", "/list/2"),
+        ("/list/01", "/list/01"),
+        ("/list/-", "/list/-"),
+        ("/n/x", "/n/x"),
+        ("/empty/0", "/empty/0"),
+        ("/missing/x", "/missing"),
+    ] {
+        assert_eq!(
+            set_path(&mut c, pointer, &json!(0)),
+            Err(PathError::NotFound {
+                pointer: resolved.to_string()
+            }),
+            "{pointer}"
+        );
+    }
+    assert_eq!(c, original);
+}
+
+#[test]
+fn test_get_path() {
+    let c = compress(&json!({"a": [{"b": "deep"}], "n": null}));
+    assert_eq!(get_path(&c, ""), Some(decompress(c.clone())));
+    assert_eq!(get_path(&c, "/a/0/b"), Some(json!("deep")));
+    assert_eq!(get_path(&c, "/n"), Some(json!(null)));
+    assert_eq!(get_path(&c, "/n/x"), None);
+    assert_eq!(get_path(&c, "/a/1"), None);
+    assert_eq!(get_path(&c, "a"), None);
+}
+
+#[test]
+fn test_get_paths_overlapping() {
+    let data = json!({
+        "user": {"name": "Alice", "address": {"city": "Paris", "zip": "75001"}},
+        "items": [{"id": 1}, {"id": 2}],
+        "a/b": null,
+    });
+    let c = compress(&data);
+    let pointers = [
+        "/user/address/city",
+        "/user",
+        "/user/address/zip",
+        "/user/name",
+        "/items/1/id",
+        "/items/1",
+        "/user/missing/x",
+        "/user/missing",
+        "/a~1b",
+        "",
+        "bad",
+        "/items/1/id",
+    ];
+    let results = get_paths(&c, &pointers);
+    assert_eq!(results.len(), pointers.len());
+    for (pointer, result) in pointers.iter().zip(&results) {
+        assert_eq!(result, &get_path(&c, pointer), "{pointer}");
+        assert_eq!(result, &data.pointer(pointer).cloned(), "{pointer}");
+    }
+    assert_eq!(results[0], Some(json!("Paris")));
+    assert_eq!(results[4], Some(json!(2)));
+    assert_eq!(results[6], None);
+}
+
+#[test]
+fn test_garbage_collect_after_set_path() {
+    let data = json!({"user": {"name": "Alice", "age": 30}, "tags": ["a", "b"]});
+    let mut c = compress(&data);
+    set_path(&mut c, "/user/name", &json!("Bob")).unwrap();
+    let edited = c.values.len();
+    assert!(c.values.contains(&"Alice".to_string()));
+
+    let collected = garbage_collect(c.clone());
+    assert!(!collected.values.contains(&"Alice".to_string()));
+    // "Alice", the old user object and the old root are gone
+    assert_eq!(collected.values.len(), edited - 3);
+    assert_eq!(decompress(collected.clone()), decompress(c));
+    // Already compact: nothing more to drop
+    assert_eq!(garbage_collect(collected.clone()), collected);
+}
+
+#[test]
+fn test_garbage_collect_keeps_fresh_compression() {
+    let data = json!([{"id": 1, "s": "x"}, {"id": 1, "s": "x"}, null, "y"]);
+    let c = compress(&data);
+    assert_eq!(garbage_collect(c.clone()), c);
+    assert_eq!(
+        garbage_collect(compress(&json!(null))),
+        compress(&json!(null))
+    );
+}
+
+#[test]
+fn test_splice_pre_compressed_object_into_document() {
+    let profile = json!({
+        "name": "Alice",
+        "roles": ["admin", "ops"],
+        "address": {"city": "Oslo", "zip": null},
+    });
+    let part = compress(&profile);
+    let mut doc = compress(&json!({
+        "owner": "ops",
+        "teams": [{"name": "core", "lead": null}],
+    }));
+
+    splice_path(&mut doc, "/teams/0/lead", &part).unwrap();
+    splice_path(&mut doc, "/backup", &part).unwrap();
+    assert_eq!(
+        decompress(garbage_collect(doc.clone())),
+        json!({
+            "owner": "ops",
+            "teams": [{"name": "core", "lead": profile}],
+            "backup": profile,
+        })
+    );
+    // Both copies share the spliced entries, and "ops" and "name" are
+    // shared with the document
+    let once = garbage_collect(doc.clone()).store_len();
+    splice_path(&mut doc, "/backup", &part).unwrap();
+    assert_eq!(garbage_collect(doc.clone()).store_len(), once);
+}
+
+#[test]
+fn test_splice_part_with_forward_references() {
+    // Renumbered stores can reference entries after the referencing one
+    let config = Config {
+        frequency_keys: true,
+        ..Config::default()
+    };
+    let part_value = json!([{"k": "v"}, {"k": "v"}, ["v", null, []]]);
+    let part = compress_with_config(&part_value, &config);
+    let mut doc = compress(&json!({"data": 0}));
+    splice_path(&mut doc, "", &part).unwrap();
+    assert_eq!(decompress(doc), part_value);
+}
+
+#[test]
+fn test_splice_scalars_and_errors() {
+    let mut doc = compress(&json!({"a": [1, 2]}));
+    splice_path(&mut doc, "/a/1", &compress(&json!("two"))).unwrap();
+    splice_path(&mut doc, "/b", &compress(&json!(null))).unwrap();
+    assert_eq!(decompress(doc.clone()), json!({"a": [1, "two"], "b": null}));
+
+    let before = doc.clone();
+    assert_eq!(
+        splice_path(&mut doc, "/a/5", &compress(&json!(1))),
+        Err(PathError::NotFound {
+            pointer: "/a/5".to_string()
+        })
+    );
+    assert_eq!(doc, before);
+}
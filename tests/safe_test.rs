@@ -0,0 +1,643 @@
+//! Tests for fallible decompression of untrusted input
+
+mod sample;
+
+use compress_json_rs::{
+    Compressed, Config, DecompressError, array_get, compress, decompress, decompress_safe,
+    key_for_index, try_decompress, try_decompress_with_config, validate, validate_reader,
+};
+use serde_json::{Value, json};
+use std::io::{self, Read};
+
+fn store(values: &[&str], root: &str) -> Compressed {
+    Compressed::new(
+        values.iter().map(|v| v.to_string()).collect(),
+        root.to_string(),
+    )
+}
+
+#[test]
+fn test_matches_decompress_on_valid_data() {
+    let data = sample::sample();
+    let compressed = compress(&data);
+    let expected = decompress(compressed.clone());
+    assert_eq!(try_decompress(&compressed), Ok(expected.clone()));
+    assert_eq!(
+        try_decompress_with_config(&compressed, &Config::untrusted()),
+        Ok(expected)
+    );
+}
+
+#[test]
+fn test_invalid_references() {
+    assert_eq!(
+        try_decompress(&store(&["a|0|5"], "0")),
+        Err(DecompressError::Cycle { index: 0 })
+    );
+    assert_eq!(
+        try_decompress(&store(&["x", "a|0|5"], "1")),
+        Err(DecompressError::IndexOutOfBounds { index: 5, len: 2 })
+    );
+    assert_eq!(
+        try_decompress(&store(&["a|$"], "0")),
+        Err(DecompressError::InvalidKey {
+            key: "$".to_string()
+        })
+    );
+    assert_eq!(
+        try_decompress(&store(&[], "zzzzzzzzzzzzzzzzzzzz")),
+        Err(DecompressError::InvalidKey {
+            key: "zzzzzzzzzzzzzzzzzzzz".to_string()
+        })
+    );
+}
+
+#[test]
+fn test_indirect_cycle() {
+    // 0 -> 1 -> 0
+    assert_eq!(
+        try_decompress(&store(&["a|1", "a|0"], "0")),
+        Err(DecompressError::Cycle { index: 0 })
+    );
+}
+
+#[test]
+fn test_malformed_entries() {
+    for (values, root) in [
+        (vec!["n|abc"], "0"),
+        (vec!["n|inf"], "0"),
+        (vec!["N|1"], "0"),
+        (vec!["N|"], "0"),
+        (vec!["c|0"], "0"),
+        (vec!["n|1", "c|0"], "1"),
+        // Schema with fewer keys than values
+        (vec!["k", "a|0", "o|1|0|0"], "2"),
+    ] {
+        let result = try_decompress(&store(&values, root));
+        assert!(
+            matches!(result, Err(DecompressError::InvalidEntry { .. })),
+            "{values:?}: {result:?}"
+        );
+    }
+}
+
+#[test]
+fn test_invalid_schema() {
+    // Schema resolving to a number
+    assert_eq!(
+        try_decompress(&store(&["n|1", "o|0|0"], "1")),
+        Err(DecompressError::InvalidSchema { index: 0 })
+    );
+    // Schema array holding a non-string key
+    assert_eq!(
+        try_decompress(&store(&["k", "b|T", "a|0|1", "o|2|0|0"], "3")),
+        Err(DecompressError::InvalidSchema { index: 2 })
+    );
+    // Schema resolving to an object
+    assert_eq!(
+        try_decompress(&store(&["k", "o|0|0", "o|1|0"], "2")),
+        Err(DecompressError::InvalidSchema { index: 1 })
+    );
+}
+
+#[test]
+fn test_long_schema_chain() {
+    // Each schema extends the previous one by a key: 200,000 links
+    const LINKS: usize = 200_000;
+    let mut values = vec!["k".to_string(), "n|1".to_string(), "a|0".to_string()];
+    for i in 2..2 + LINKS {
+        values.push(format!("a|{}|0", key_for_index(i)));
+    }
+    let schema = key_for_index(values.len() - 1);
+    values.push(format!("o|{schema}|1"));
+    let object = key_for_index(values.len() - 1);
+    // Many objects share the schema, which is resolved once
+    values.push(format!("a|{}", vec![object.as_str(); 500].join("|")));
+    let c = Compressed::new(values, key_for_index(LINKS + 4));
+
+    assert_eq!(validate(&c), Ok(()));
+    assert_eq!(
+        decompress_safe(&c),
+        Err(DecompressError::MaxObjectKeysExceeded {
+            index: LINKS + 3,
+            limit: 10_000
+        })
+    );
+    assert_eq!(try_decompress(&c), Ok(json!(vec![json!({"k": 1}); 500])));
+}
+
+#[test]
+fn test_shared_subtree_is_not_a_cycle() {
+    // The same entry referenced twice by siblings is fine
+    let c = store(&["x", "a|0|0", "a|1|1"], "2");
+    assert_eq!(try_decompress(&c), Ok(json!([["x", "x"], ["x", "x"]])));
+}
+
+#[test]
+fn test_untrusted_rejects_exponential_expansion() {
+    // Each level references the previous one twice: 2^40 leaves
+    let mut values = vec!["x".to_string()];
+    for i in 0..40 {
+        let key = key_for_index(i);
+        values.push(format!("a|{key}|{key}"));
+    }
+    let malicious = Compressed::new(values, key_for_index(40));
+
+    assert_eq!(
+        try_decompress_with_config(&malicious, &Config::untrusted()),
+        Err(DecompressError::MaxNodesExceeded { limit: 1_000_000 })
+    );
+}
+
+#[test]
+fn test_untrusted_rejects_deep_nesting() {
+    let mut data = json!("leaf");
+    for _ in 0..200 {
+        data = json!([data]);
+    }
+    let compressed = compress(&data);
+
+    assert_eq!(
+        try_decompress_with_config(&compressed, &Config::untrusted()),
+        Err(DecompressError::MaxDepthExceeded { limit: 128 })
+    );
+    assert_eq!(try_decompress(&compressed), Ok(data));
+}
+
+#[test]
+fn test_limits_are_exact() {
+    let data = json!([1, [2, null]]);
+    let compressed = compress(&data);
+    let config = |max_depth, max_nodes| Config {
+        max_depth,
+        max_nodes,
+        ..Config::default()
+    };
+
+    // 5 values, 3 levels
+    assert_eq!(
+        try_decompress_with_config(&compressed, &config(Some(3), Some(5))),
+        Ok(data)
+    );
+    assert_eq!(
+        try_decompress_with_config(&compressed, &config(Some(2), None)),
+        Err(DecompressError::MaxDepthExceeded { limit: 2 })
+    );
+    assert_eq!(
+        try_decompress_with_config(&compressed, &config(None, Some(4))),
+        Err(DecompressError::MaxNodesExceeded { limit: 4 })
+    );
+}
+
+#[test]
+fn test_error_display() {
+    let err = DecompressError::IndexOutOfBounds { index: 5, len: 2 };
+    assert_eq!(
+        err.to_string(),
+        "reference to index 5 is out of bounds (len 2)"
+    );
+    let _: &dyn std::error::Error = &err;
+    assert_eq!(try_decompress(&store(&[], "")), Ok(Value::Null));
+}
+
+#[test]
+fn test_lenient_ignores_trailing_separators() {
+    let lenient = Config {
+        lenient: true,
+        ..Config::default()
+    };
+    // `{"a": 1, "b": 2}` with a stray separator after the object refs
+    let c = store(&["a", "b", "a|0|1", "n|1", "n|2", "o|2|3|4|", "a|5|3"], "6");
+    assert_eq!(
+        try_decompress_with_config(&c, &lenient),
+        Ok(json!([{"a": 1, "b": 2}, 1]))
+    );
+    // Strict mode rejects the surplus object reference
+    assert_eq!(
+        try_decompress(&c),
+        Err(DecompressError::InvalidEntry { index: 5 })
+    );
+}
+
+#[test]
+fn test_lenient_keeps_meaningful_references() {
+    let lenient = Config {
+        lenient: true,
+        ..Config::default()
+    };
+    // An empty reference within the schema is still a `null` value
+    let c = store(&["a", "b", "a|0|1", "n|1", "o|2|3|"], "4");
+    assert_eq!(
+        try_decompress_with_config(&c, &lenient),
+        Ok(json!({"a": 1, "b": null}))
+    );
+    // A non-empty surplus reference is an error even when lenient
+    let c = store(&["a", "a|0", "n|1", "o|1|2|2"], "3");
+    assert_eq!(
+        try_decompress_with_config(&c, &lenient),
+        Err(DecompressError::InvalidEntry { index: 3 })
+    );
+    // `_` marks a real null element, so it is kept
+    let c = store(&["n|1", "a|0|_"], "1");
+    assert_eq!(
+        try_decompress_with_config(&c, &lenient),
+        Ok(json!([1, null]))
+    );
+}
+
+#[test]
+fn test_lenient_keeps_trailing_array_null() {
+    let lenient = Config {
+        lenient: true,
+        ..Config::default()
+    };
+    // A trailing empty array reference is a `null` element, as in strict mode
+    let c = store(&["n|1", "a|0|"], "1");
+    assert_eq!(
+        try_decompress_with_config(&c, &lenient),
+        Ok(json!([1, null]))
+    );
+    assert_eq!(try_decompress_with_config(&c, &lenient), try_decompress(&c));
+    let c = store(&["n|1", "a|0||"], "1");
+    assert_eq!(
+        try_decompress_with_config(&c, &lenient),
+        Ok(json!([1, null, null]))
+    );
+}
+
+#[test]
+fn test_lenient_numbers() {
+    let lenient = Config {
+        lenient: true,
+        ..Config::default()
+    };
+    let c = store(
+        &["n|+42", "n|1_000", "n|-2_500.5", "n|+1e+3", "a|0|1|2|3"],
+        "4",
+    );
+    assert_eq!(
+        try_decompress_with_config(&c, &lenient),
+        Ok(json!([42, 1000, -2500.5, 1000.0]))
+    );
+    // The panicking decoder takes a leading `+` whatever the configuration
+    assert_eq!(decompress(store(&["n|+42"], "0")), json!(42));
+    // Strict mode reports each lenient form
+    for entry in ["n|+42", "n|1_000"] {
+        let c = store(&["n|1", entry, "a|0|1"], "2");
+        assert_eq!(
+            try_decompress(&c),
+            Err(DecompressError::InvalidEntry { index: 1 }),
+            "{entry}"
+        );
+        assert!(validate(&c).is_err());
+    }
+    // Separators must sit between digits
+    for entry in ["n|_1", "n|1_", "n|1__0", "n|++1", "n|+"] {
+        let c = store(&[entry, "a|0"], "1");
+        assert_eq!(
+            try_decompress_with_config(&c, &lenient),
+            Err(DecompressError::InvalidEntry { index: 0 }),
+            "{entry}"
+        );
+    }
+    // Exponent signs were always valid
+    let c = store(&["n|1e+3", "a|0"], "1");
+    assert_eq!(try_decompress(&c), Ok(json!([1000.0])));
+}
+
+#[test]
+fn test_array_get_element() {
+    let rows: Vec<Value> = (0..10)
+        .map(|i| json!({"id": i, "name": format!("row {i}")}))
+        .collect();
+    let c = compress(&Value::Array(rows.clone()));
+    assert_eq!(array_get(&c, 5), Ok(Some(rows[5].clone())));
+    assert_eq!(array_get(&c, 0), Ok(Some(rows[0].clone())));
+    assert_eq!(array_get(&c, 9), Ok(Some(rows[9].clone())));
+    assert_eq!(array_get(&c, 10), Ok(None));
+}
+
+#[test]
+fn test_array_get_skips_siblings() {
+    // Every element but the sixth is broken, so decoding any of them fails
+    let mut values = vec!["ok", "n|x"];
+    let root = format!("a|1|1|1|1|1|0|1|1|1|1|{}", key_for_index(99));
+    values.push(&root);
+    let c = store(&values, "2");
+    assert_eq!(array_get(&c, 5), Ok(Some(json!("ok"))));
+    assert_eq!(
+        array_get(&c, 4),
+        Err(DecompressError::InvalidEntry { index: 1 })
+    );
+    assert_eq!(
+        array_get(&c, 10),
+        Err(DecompressError::IndexOutOfBounds { index: 99, len: 3 })
+    );
+}
+
+#[test]
+fn test_array_get_null_and_non_arrays() {
+    let c = compress(&json!([1, null]));
+    assert_eq!(array_get(&c, 1), Ok(Some(Value::Null)));
+    assert_eq!(array_get(&compress(&json!([])), 0), Ok(None));
+    assert_eq!(array_get(&compress(&json!({"0": 1})), 0), Ok(None));
+    assert_eq!(array_get(&compress(&Value::Null), 0), Ok(None));
+    assert_eq!(
+        array_get(&store(&["a|0"], "0"), 0),
+        Err(DecompressError::Cycle { index: 0 })
+    );
+    assert_eq!(
+        array_get(&store(&[], "5"), 0),
+        Err(DecompressError::IndexOutOfBounds { index: 5, len: 0 })
+    );
+}
+
+#[test]
+fn test_max_array_len_rejects_huge_entry() {
+    // One entry claiming a million elements, all the same scalar
+    let huge = format!("a|{}", vec!["0"; 1_000_000].join("|"));
+    let c = store(&["x", &huge], "1");
+    let config = Config {
+        max_array_len: Some(1000),
+        ..Config::default()
+    };
+    assert_eq!(
+        try_decompress_with_config(&c, &config),
+        Err(DecompressError::MaxArrayLenExceeded {
+            index: 1,
+            limit: 1000
+        })
+    );
+    assert_eq!(
+        try_decompress_with_config(&c, &Config::untrusted()),
+        Err(DecompressError::MaxArrayLenExceeded {
+            index: 1,
+            limit: 100_000
+        })
+    );
+    let small = store(&["x", "a|0|0|0"], "1");
+    let limited = Config {
+        max_array_len: Some(3),
+        ..Config::default()
+    };
+    assert_eq!(
+        try_decompress_with_config(&small, &limited),
+        Ok(json!(["x", "x", "x"]))
+    );
+}
+
+#[test]
+fn test_max_object_keys_checks_refs_and_schema() {
+    let config = Config {
+        max_object_keys: Some(2),
+        ..Config::default()
+    };
+    let data = json!({"a": 1, "b": 2});
+    assert_eq!(
+        try_decompress_with_config(&compress(&data), &config),
+        Ok(data)
+    );
+    let wide = compress(&json!({"a": 1, "b": 2, "c": 3}));
+    assert!(matches!(
+        try_decompress_with_config(&wide, &config),
+        Err(DecompressError::MaxObjectKeysExceeded { limit: 2, .. })
+    ));
+    // A schema with many keys, even if the object lists few values
+    let c = store(&["a", "b", "c", "a|0|1|2", "o|3|0"], "4");
+    assert_eq!(
+        try_decompress_with_config(&c, &config),
+        Err(DecompressError::MaxObjectKeysExceeded { index: 4, limit: 2 })
+    );
+    assert_eq!(
+        DecompressError::MaxObjectKeysExceeded { index: 4, limit: 2 }.to_string(),
+        "object at entry 4 exceeds the limit of 2 keys"
+    );
+}
+
+/// Deterministic pseudo-random numbers (xorshift)
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+/// Pieces random entries are assembled from: every prefix, edge-case
+/// numbers and keys, separators and non-ASCII text.
+const PIECES: &[&str] = &[
+    "a|",
+    "o|",
+    "c|",
+    "d|",
+    "n|",
+    "N|",
+    "s|",
+    "x|",
+    "b|",
+    "T",
+    "F",
+    "+",
+    "-",
+    "0",
+    "1",
+    "2",
+    "3",
+    "z",
+    "Z",
+    "_",
+    "|",
+    "",
+    "-0",
+    "1e999",
+    "-1e999",
+    "NaN",
+    "9999999999999999999999",
+    "zzzzzzzzzzzz",
+    "0.5",
+    ".",
+    "e",
+    "=",
+    "AAEC",
+    "/",
+    "é",
+    "日本",
+    "\\",
+    "\"",
+];
+
+/// A random store of up to 8 entries and a random root.
+fn random_store(rng: &mut Rng) -> Compressed {
+    let piece = |rng: &mut Rng| PIECES[rng.below(PIECES.len())];
+    let len = rng.below(9);
+    let values = (0..len)
+        .map(|_| {
+            let parts = rng.below(6);
+            (0..parts).map(|_| piece(rng)).collect::<String>()
+        })
+        .collect();
+    let root = match rng.below(3) {
+        0 => key_for_index(rng.below(len + 2)),
+        _ => (0..rng.below(3)).map(|_| piece(rng)).collect::<String>(),
+    };
+    Compressed::new(values, root)
+}
+
+#[test]
+fn test_decompress_safe_never_panics_on_random_stores() {
+    let mut rng = Rng(0x2545_F491_4F6C_DD1D);
+    let (mut ok, mut err) = (0, 0);
+    for _ in 0..50_000 {
+        match decompress_safe(&random_store(&mut rng)) {
+            Ok(_) => ok += 1,
+            Err(_) => err += 1,
+        }
+    }
+    // The generator reaches both outcomes
+    assert!(ok > 1_000 && err > 1_000, "ok {ok}, err {err}");
+}
+
+#[test]
+fn test_validate_agrees_with_try_decompress_on_random_stores() {
+    let mut rng = Rng(0xD1B5_4A32_D192_ED03);
+    for _ in 0..50_000 {
+        let c = random_store(&mut rng);
+        assert_eq!(validate(&c).is_ok(), try_decompress(&c).is_ok(), "{c:?}");
+    }
+}
+
+#[test]
+fn test_decompress_safe_never_panics_on_mutated_payloads() {
+    let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+    let base = compress(&sample::sample());
+    for _ in 0..5_000 {
+        let mut c = base.clone();
+        let i = rng.below(c.values.len());
+        let mut entry: Vec<char> = c.values[i].chars().collect();
+        let at = rng.below(entry.len() + 1);
+        match rng.below(3) {
+            0 if at < entry.len() => {
+                entry.remove(at);
+            }
+            1 => entry.insert(
+                at,
+                PIECES[rng.below(PIECES.len())]
+                    .chars()
+                    .next()
+                    .unwrap_or('|'),
+            ),
+            _ => entry.truncate(at),
+        }
+        c.values[i] = entry.into_iter().collect();
+        let _ = decompress_safe(&c);
+    }
+}
+
+#[test]
+fn test_decompress_safe_bounds_deep_and_wide_stores() {
+    // Each entry nests the previous one, far deeper than the limit
+    let mut values = vec!["n|1".to_string()];
+    values.extend((0..10_000).map(|i| format!("a|{}", key_for_index(i))));
+    let deep = Compressed::new(values, key_for_index(10_000));
+    assert!(matches!(
+        decompress_safe(&deep),
+        Err(DecompressError::MaxDepthExceeded { .. })
+    ));
+
+    // Each entry references the previous one twice, doubling the output
+    let mut values = vec!["n|1".to_string()];
+    values.extend((0..40).map(|i| format!("a|{0}|{0}", key_for_index(i))));
+    let wide = Compressed::new(values, key_for_index(40));
+    assert!(decompress_safe(&wide).is_err());
+}
+
+/// Hands out its input a few bytes per read, like a slow socket.
+struct Trickle<'a>(&'a [u8]);
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.0.len()).min(3);
+        buf[..n].copy_from_slice(&self.0[..n]);
+        self.0 = &self.0[n..];
+        Ok(n)
+    }
+}
+
+#[test]
+fn test_validate_reader_streamed_payloads() {
+    let valid = serde_json::to_vec(&compress(&sample::sample())).unwrap();
+    assert_eq!(validate_reader(Trickle(&valid)), Ok(()));
+
+    // The second element of the root array points past the store
+    let dangling = br#"[["a","b","a|0|3"],"2"]"#;
+    assert_eq!(
+        validate_reader(Trickle(dangling)),
+        Err(DecompressError::IndexOutOfBounds { index: 3, len: 3 })
+    );
+
+    let truncated = &valid[..valid.len() / 2];
+    assert!(matches!(
+        validate_reader(Trickle(truncated)),
+        Err(DecompressError::Parse { .. })
+    ));
+}
+
+#[test]
+fn test_validate_visits_shared_entries_once() {
+    // Expands to 2^60 leaves, but has only 61 entries to check
+    let mut values = vec!["n|1".to_string()];
+    values.extend((0..60).map(|i| format!("a|{0}|{0}", key_for_index(i))));
+    let wide = Compressed::new(values, key_for_index(60));
+    assert_eq!(validate(&wide), Ok(()));
+
+    let cycle = store(&["n|1", "a|0|2", "a|1"], "2");
+    assert_eq!(validate(&cycle), Err(DecompressError::Cycle { index: 2 }));
+    assert_eq!(validate(&store(&[], "")), Ok(()));
+    assert_eq!(validate(&store(&[], "b|T")), Ok(()));
+}
+
+#[test]
+fn test_validate_rejects_deep_nesting() {
+    // A chain of 200,000 nested arrays, which would overflow the stack of
+    // `decompress`
+    let mut values = vec!["a|".to_string()];
+    values.extend((0..200_000).map(|i| format!("a|{}", key_for_index(i))));
+    let deep = Compressed::new(values, key_for_index(200_000));
+    assert_eq!(
+        validate(&deep),
+        Err(DecompressError::MaxDepthExceeded { limit: 128 })
+    );
+
+    // Agrees with `decompress_safe` at the limit, whatever the innermost value
+    for leaf in [json!([]), json!([null]), json!([1, 2]), json!({"k": null})] {
+        for levels in 125..=128 {
+            let mut data = leaf.clone();
+            for _ in 0..levels {
+                data = json!([data]);
+            }
+            let c = compress(&data);
+            assert_eq!(
+                validate(&c).is_ok(),
+                decompress_safe(&c).is_ok(),
+                "{levels} levels around {leaf}"
+            );
+        }
+    }
+
+    // An entry checked shallow is still rejected when reached deeper
+    let mut values = vec!["a|".to_string()];
+    values.extend((0..127).map(|i| format!("a|{}", key_for_index(i))));
+    values.push(format!("a|{}|{}", key_for_index(126), key_for_index(127)));
+    let shared = Compressed::new(values, key_for_index(128));
+    assert_eq!(
+        validate(&shared),
+        Err(DecompressError::MaxDepthExceeded { limit: 128 })
+    );
+}
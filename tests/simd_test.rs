@@ -0,0 +1,201 @@
+//! Tests for compressing simd-json values
+#![cfg(feature = "simd-json")]
+
+mod sample;
+
+use compress_json_rs::{
+    CompressError, Compressed, Config, Warning, compress, compress_simd, compress_simd_with_config,
+    compress_simd_with_report, compress_with_config, decompress, decompress_simd,
+    try_compress_simd,
+};
+use serde_json::json;
+use simd_json::OwnedValue;
+
+/// Parse JSON text with simd-json.
+fn parse(text: &str) -> OwnedValue {
+    let mut bytes = text.as_bytes().to_vec();
+    simd_json::to_owned_value(&mut bytes).unwrap()
+}
+
+#[test]
+fn test_simd_roundtrip() {
+    let value = parse(
+        r#"{"id": 1, "ratio": -2.5, "big": 18446744073709551615, "neg": -7, "ok": true,
+            "none": null, "items": [null, "a|b", "", [], {}], "nested": {"x": [1, 1]}}"#,
+    );
+    // `big` rounds to 2^64 through f64 and is kept at `u64::MAX`
+    assert_eq!(decompress_simd(compress_simd(&value)), value);
+}
+
+#[test]
+fn test_simd_matches_serde_compression() {
+    let data = sample::sample();
+    let value = parse(&data.to_string());
+    let compressed = compress_simd(&value);
+    assert_eq!(compressed, compress(&data));
+    assert_eq!(decompress(compressed), data);
+}
+
+#[test]
+fn test_decompress_simd_inline_root() {
+    let c = Compressed::new(Vec::new(), "n|5");
+    assert_eq!(decompress_simd(c), OwnedValue::from(5u64));
+}
+
+#[test]
+fn test_simd_applies_store_passes() {
+    let data = sample::sample();
+    let value = parse(&data.to_string());
+    for config in [
+        Config {
+            sort_values: true,
+            ..Config::default()
+        },
+        Config {
+            sort_values: true,
+            frequency_keys: true,
+            ..Config::default()
+        },
+        Config {
+            typed_sections: true,
+            ..Config::default()
+        },
+    ] {
+        let compressed = compress_simd_with_config(&value, &config);
+        assert_eq!(compressed, compress_with_config(&data, &config));
+        assert_eq!(decompress(compressed), data);
+    }
+}
+
+#[test]
+fn test_simd_with_config() {
+    let config = Config {
+        chunk_strings: Some(4),
+        numeric_strings: true,
+        ..Config::default()
+    };
+    let value = parse(r#"["a long string value", "123"]"#);
+    let compressed = compress_simd_with_config(&value, &config);
+    assert!(compressed.values.iter().any(|v| v.starts_with("c|")));
+    assert_eq!(
+        compressed,
+        compress_with_config(&json!(["a long string value", "123"]), &config)
+    );
+    assert_eq!(
+        decompress_simd(compressed),
+        parse(r#"["a long string value", 123]"#)
+    );
+}
+
+#[test]
+fn test_try_compress_reports_nested_path() {
+    let mut value =
+        parse(r#"{"metrics": [{"value": 1}, {"value": 2}, {"value": 3}, {"value": 4}]}"#);
+    value["metrics"][3]["value"] = OwnedValue::from(f64::NEG_INFINITY);
+    let config = Config {
+        error_on_infinite: true,
+        ..Config::default()
+    };
+    let err = try_compress_simd(&value, &config).unwrap_err();
+    assert_eq!(
+        err,
+        CompressError::UnsupportedValue {
+            path: "/metrics/3/value".to_string(),
+            value: "-Infinity".to_string(),
+        }
+    );
+    assert!(err.to_string().contains("/metrics/3/value"));
+}
+
+#[test]
+fn test_try_compress_escapes_keys_and_honours_config() {
+    let mut value = parse(r#"{"a/b": {"~": [0, 0]}}"#);
+    value["a/b"]["~"][1] = OwnedValue::from(f64::NAN);
+    let rejecting = Config {
+        error_on_nan: true,
+        ..Config::default()
+    };
+    assert!(matches!(
+        try_compress_simd(&value, &rejecting),
+        Err(CompressError::UnsupportedValue { path, .. }) if path == "/a~1b/~0/1"
+    ));
+
+    // Preserved or nulled NaN is not an error
+    let preserving = Config {
+        preserve_nan: true,
+        ..rejecting
+    };
+    assert_eq!(
+        try_compress_simd(&value, &preserving),
+        Ok(compress_simd_with_config(&value, &preserving))
+    );
+    assert_eq!(
+        try_compress_simd(&value, &Config::default()),
+        Ok(compress_simd(&value))
+    );
+}
+
+#[test]
+fn test_try_compress_reports_rounded_integer() {
+    let value = parse(r#"{"ids": [1, 9007199254740993]}"#);
+    assert_eq!(
+        try_compress_simd(&value, &Config::strict()),
+        Err(CompressError::UnsupportedValue {
+            path: "/ids/1".to_string(),
+            value: "9007199254740993".to_string(),
+        })
+    );
+    assert!(try_compress_simd(&value, &Config::default()).is_ok());
+}
+
+#[test]
+fn test_try_compress_reports_long_string() {
+    let value = parse(r#"{"rows": [{"note": "far too long"}]}"#);
+    let config = Config {
+        max_value_len: Some(8),
+        ..Config::default()
+    };
+    assert_eq!(
+        try_compress_simd(&value, &config),
+        Err(CompressError::ValueTooLong {
+            path: "/rows/0/note".to_string(),
+            len: 12,
+            limit: 8,
+        })
+    );
+}
+
+#[test]
+fn test_report_nan_replaced_with_null() {
+    let value = OwnedValue::from(vec![
+        OwnedValue::from(1.5),
+        OwnedValue::from(f64::NAN),
+        OwnedValue::from("x"),
+    ]);
+    let (compressed, warnings) = compress_simd_with_report(&value, &Config::default());
+    assert_eq!(
+        warnings,
+        vec![Warning::ReplacedWithNull {
+            path: "/1".to_string(),
+            value: "NaN".to_string(),
+        }]
+    );
+    assert_eq!(warnings[0].to_string(), "NaN at /1 replaced with null");
+    assert_eq!(decompress(compressed), json!([1.5, null, "x"]));
+}
+
+#[test]
+fn test_report_skips_preserved_values() {
+    let mut value = simd_json::json!({"a": [0.5]});
+    value["a"][0] = OwnedValue::from(f64::NEG_INFINITY);
+    let preserving = Config {
+        preserve_infinite: true,
+        ..Config::default()
+    };
+    assert!(compress_simd_with_report(&value, &preserving).1.is_empty());
+    let (_, warnings) = compress_simd_with_report(&value, &Config::default());
+    assert_eq!(
+        warnings[0].to_string(),
+        "-Infinity at /a/0 replaced with null"
+    );
+}
@@ -0,0 +1,124 @@
+//! Tests for source spans of compressed JSON text
+
+use compress_json_rs::{
+    CONFIG, Config, compress_str, compress_str_with_config, compress_str_with_spans, decompress,
+    key_for_index,
+};
+use serde_json::json;
+
+/// Index of the store entry equal to `entry`.
+fn entry_index(values: &[String], entry: &str) -> usize {
+    values.iter().position(|v| v == entry).unwrap()
+}
+
+#[test]
+fn test_string_span_matches_first_occurrence() {
+    let json = r#"{
+        "users": [
+            {"name": "Alice", "role": "admin"},
+            {"name": "Bob", "role": "admin"}
+        ]
+    }"#;
+    let (compressed, spans) = compress_str_with_spans(json, &CONFIG).unwrap();
+    assert_eq!(spans.len(), compressed.store_len());
+
+    let start = json.find(r#""admin""#).unwrap();
+    let admin = entry_index(&compressed.values, "admin");
+    assert_eq!(spans[admin], Some(start..start + 7));
+
+    let alice = entry_index(&compressed.values, "Alice");
+    let span = spans[alice].clone().unwrap();
+    assert_eq!(&json[span], r#""Alice""#);
+}
+
+#[test]
+fn test_same_output_as_compress_str() {
+    let json = r#"{"b": [1, 2.5, true, null], "a": {"x": "y"}}"#;
+    let (compressed, spans) = compress_str_with_spans(json, &CONFIG).unwrap();
+    assert_eq!(compressed, compress_str(json).unwrap());
+
+    // The root spans the whole document
+    let last = compressed.store_len() - 1;
+    assert_eq!(compressed.root.as_str(), key_for_index(last));
+    assert_eq!(spans[last], Some(0..json.len()));
+    assert_eq!(
+        decompress(compressed),
+        json!({"b": [1, 2.5, true, null], "a": {"x": "y"}})
+    );
+}
+
+#[test]
+fn test_composite_and_scalar_spans() {
+    let json = r#"[ [1, 2] , 2 , [1, 2] ]"#;
+    let (compressed, spans) = compress_str_with_spans(json, &CONFIG).unwrap();
+
+    let inner = entry_index(&compressed.values, "a|0|1");
+    assert_eq!(spans[inner], Some(2..8));
+    // `2` first appears inside the first array
+    let two = entry_index(&compressed.values, "n|2");
+    assert_eq!(spans[two], Some(6..7));
+}
+
+#[test]
+fn test_schema_entries_have_no_span() {
+    let json = r#"{"id": 1, "ok": true}"#;
+    let (compressed, spans) = compress_str_with_spans(json, &CONFIG).unwrap();
+
+    let schema = entry_index(&compressed.values, "a|0|1");
+    let key = entry_index(&compressed.values, "id");
+    assert_eq!(spans[schema], None);
+    assert_eq!(spans[key], None);
+}
+
+#[test]
+fn test_spans_follow_renumbered_store() {
+    let json = r#"["rare", "common", "common", "common"]"#;
+    let config = Config {
+        frequency_keys: true,
+        typed_sections: true,
+        ..Config::default()
+    };
+    let (compressed, spans) = compress_str_with_spans(json, &config).unwrap();
+    assert_eq!(compressed, compress_str_with_config(json, &config).unwrap());
+
+    let common = entry_index(&compressed.values, "common");
+    assert_eq!(spans[common], Some(9..17));
+    let rare = entry_index(&compressed.values, "rare");
+    assert_eq!(spans[rare], Some(1..7));
+}
+
+#[test]
+fn test_escaped_keys_and_duplicates() {
+    let json = r#"{"a\"b": "v", "k": "old", "k": "new"}"#;
+    let (compressed, spans) = compress_str_with_spans(json, &CONFIG).unwrap();
+
+    let v = entry_index(&compressed.values, "v");
+    assert_eq!(&json[spans[v].clone().unwrap()], r#""v""#);
+    // The stored value is the last one for a repeated key
+    let new = entry_index(&compressed.values, "new");
+    assert_eq!(&json[spans[new].clone().unwrap()], r#""new""#);
+    assert!(!compressed.values.iter().any(|e| e == "old"));
+}
+
+#[test]
+fn test_invalid_json_is_an_error() {
+    assert!(compress_str_with_spans("[1,", &CONFIG).is_err());
+}
+
+#[test]
+fn test_spans_follow_every_store_pass() {
+    let json = r#"{"b": ["x", "y", "y"], "a": ["y", 2, true, "z"], "c": {"d": "x"}}"#;
+    let config = Config {
+        sort_values: true,
+        frequency_keys: true,
+        typed_sections: true,
+        verify_on_compress: true,
+        ..Config::default()
+    };
+    let (compressed, spans) = compress_str_with_spans(json, &config).unwrap();
+    assert_eq!(compressed, compress_str_with_config(json, &config).unwrap());
+    for s in ["x", "y", "z"] {
+        let span = spans[entry_index(&compressed.values, s)].clone().unwrap();
+        assert_eq!(json[span], format!("\"{s}\""));
+    }
+}
@@ -28,9 +28,9 @@ fn test_special_value_decoding() {
 
     // Decode each special value
     // Note: In JSON, these become null because JSON doesn't support Infinity/NaN
-    let decoded_inf = decode(&values, "0");
-    let decoded_neg_inf = decode(&values, "1");
-    let decoded_nan = decode(&values, "2");
+    let decoded_inf = decode(&values, &"0".into());
+    let decoded_neg_inf = decode(&values, &"1".into());
+    let decoded_nan = decode(&values, &"2".into());
 
     // JSON representation is null (JSON spec limitation)
     assert!(decoded_inf.is_null());
@@ -87,7 +87,7 @@ fn test_compressed_format_contains_special_encoding() {
     use compress_json_rs::decode;
 
     // Decode the array
-    let decoded = decode(&values, "4");
+    let decoded = decode(&values, &"4".into());
 
     // The array should contain nulls (JSON representation) but the
     // encoded form preserves the original special values
@@ -168,11 +168,11 @@ fn test_default_config_nullifies_special_values() {
 
     // Empty key represents null
     let values = vec!["n|42".to_string()];
-    let decoded_null = decode(&values, "");
+    let decoded_null = decode(&values, &"".into());
     assert!(decoded_null.is_null());
 
     // "_" also represents null
-    let decoded_null2 = decode(&values, "_");
+    let decoded_null2 = decode(&values, &"_".into());
     assert!(decoded_null2.is_null());
 }
 
@@ -191,7 +191,7 @@ fn test_cross_platform_decoding_compatibility() {
         "a|0|1|2|3|4".to_string(), // 5: array of all values
     ];
 
-    let decoded = decode(&values, "5");
+    let decoded = decode(&values, &"5".into());
     let arr = decoded.as_array().unwrap();
 
     // Special values become null in JSON output
@@ -0,0 +1,154 @@
+//! Tests for incremental array compression
+
+use compress_json_rs::{
+    ArrayCompressor, ArrayWriter, Compressed, Config, compress, compress_with_config, decompress,
+};
+use serde_json::{Value, json};
+
+fn element(i: usize) -> Value {
+    match i % 4 {
+        0 => json!({"id": i, "kind": "event", "tags": ["a", "b"]}),
+        1 => json!(format!("item-{}", i % 7)),
+        2 => Value::Null,
+        _ => json!(i as f64 / 4.0),
+    }
+}
+
+#[test]
+fn test_push_1000_matches_compress() {
+    let mut array = ArrayCompressor::new();
+    for i in 0..1000 {
+        array.push(&element(i));
+    }
+    assert_eq!(array.len(), 1000);
+
+    let expected = Value::Array((0..1000).map(element).collect());
+    let compressed = array.finish();
+    assert_eq!(compressed, compress(&expected));
+    assert_eq!(decompress(compressed), expected);
+}
+
+#[test]
+fn test_empty_array() {
+    let array = ArrayCompressor::default();
+    assert!(array.is_empty());
+    let compressed = array.finish();
+    assert_eq!(compressed, compress(&json!([])));
+    assert_eq!(decompress(compressed), json!([]));
+}
+
+#[test]
+fn test_with_config() {
+    let config = Config {
+        numeric_strings: true,
+        ..Config::default()
+    };
+    let mut array = ArrayCompressor::with_config(&config);
+    array.push(&json!("42"));
+    array.push(&json!("x"));
+    assert_eq!(
+        array.finish(),
+        compress_with_config(&json!(["42", "x"]), &config)
+    );
+}
+
+#[test]
+fn test_store_passes_match_compress() {
+    let elements: Vec<Value> = (0..40).map(element).collect();
+    let passes = [
+        Config {
+            sort_values: true,
+            ..Config::default()
+        },
+        Config {
+            frequency_keys: true,
+            ..Config::default()
+        },
+        Config {
+            typed_sections: true,
+            ..Config::default()
+        },
+        Config {
+            sort_values: true,
+            frequency_keys: true,
+            typed_sections: true,
+            verify_on_compress: true,
+            ..Config::default()
+        },
+    ];
+    for config in passes {
+        let mut array = ArrayCompressor::with_config(&config);
+        for value in &elements {
+            array.push(value);
+        }
+        let expected = compress_with_config(&Value::Array(elements.clone()), &config);
+        assert_eq!(array.finish(), expected, "{config:?}");
+    }
+}
+
+#[test]
+fn test_writer_output_matches_compress() {
+    let mut array = ArrayWriter::new(Vec::new());
+    for i in 0..1000 {
+        array.push(&element(i)).unwrap();
+    }
+    assert_eq!(array.len(), 1000);
+    let bytes = array.finish().unwrap();
+
+    let expected = Value::Array((0..1000).map(element).collect());
+    let compressed: Compressed = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(compressed, compress(&expected));
+    assert_eq!(decompress(compressed), expected);
+}
+
+#[test]
+fn test_writer_empty_and_escaped_entries() {
+    let bytes = ArrayWriter::new(Vec::new()).finish().unwrap();
+    assert_eq!(bytes, br#"[["a|"],"0"]"#);
+
+    let mut array = ArrayWriter::new(Vec::new());
+    array.push(&json!("quote \" and \\ slash")).unwrap();
+    array.push(&json!([])).unwrap();
+    let compressed: Compressed = serde_json::from_slice(&array.finish().unwrap()).unwrap();
+    assert_eq!(decompress(compressed), json!(["quote \" and \\ slash", []]));
+}
+
+#[test]
+fn test_writer_dedups_long_strings_after_flush() {
+    // Entries are written out as they are created, so later repeats are
+    // matched without the stored copy
+    let long = json!({"body": "z".repeat(500)});
+    let mut array = ArrayWriter::new(Vec::new());
+    for _ in 0..3 {
+        array.push(&long).unwrap();
+    }
+    let compressed: Compressed = serde_json::from_slice(&array.finish().unwrap()).unwrap();
+    assert_eq!(compressed, compress(&json!([long, long, long])));
+}
+
+#[test]
+fn test_bounded_dictionary_for_endless_streams() {
+    let config = Config {
+        max_dictionary_len: Some(64),
+        ..Config::default()
+    };
+    let record = |i: usize| json!({"id": i, "user": format!("user-{i}"), "kind": "event"});
+    let mut array = ArrayWriter::with_config(Vec::new(), &config);
+    for i in 0..5_000 {
+        array.push(&record(i)).unwrap();
+        assert!(array.dictionary_len() <= 64);
+    }
+    let bytes = array.finish().unwrap();
+    let compressed: Compressed = serde_json::from_slice(&bytes).unwrap();
+    let restored = decompress(compressed);
+    for i in 0..5_000 {
+        assert_eq!(restored[i], record(i));
+    }
+
+    // Without a bound, the dictionary keeps every unique value
+    let mut array = ArrayWriter::new(Vec::new());
+    for i in 0..5_000 {
+        array.push(&record(i)).unwrap();
+    }
+    assert!(array.dictionary_len() > 10_000);
+}
@@ -0,0 +1,123 @@
+#![cfg(feature = "serde_transcode")]
+
+use compress_json_rs::{
+    Compressed, Config, compress, decompress, transcode, transcode_with_config,
+};
+use serde_json::{Value, json};
+use std::io::Cursor;
+
+fn transcoded(text: &str, config: &Config) -> Compressed {
+    let bytes = transcode_with_config(Cursor::new(text), Vec::new(), config).unwrap();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+#[test]
+fn test_transcode_large_document() {
+    let items: Vec<Value> = (0..2000)
+        .map(|i| {
+            json!({
+                "id": i,
+                "name": format!("item-{}", i % 50),
+                "price": i as f64 / 4.0,
+                "active": i % 3 == 0,
+                "tags": ["a", "b", i % 7],
+                "meta": null,
+                "empty": {},
+                "none": [],
+            })
+        })
+        .collect();
+    let value = json!({"items": items, "total": 2000, "owner": {"name": "Alice"}});
+    let text = serde_json::to_string(&value).unwrap();
+
+    let bytes = transcode(Cursor::new(text.as_bytes()), Vec::new()).unwrap();
+    let compressed: Compressed = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(decompress(compressed), decompress(compress(&value)));
+}
+
+#[test]
+fn test_transcode_scalars() {
+    for value in [
+        json!(null),
+        json!(true),
+        json!(-42),
+        json!(1.5),
+        json!(""),
+        json!("text"),
+        json!([]),
+        json!({}),
+        json!([null, [null]]),
+    ] {
+        let compressed = transcoded(&value.to_string(), &Config::default());
+        assert_eq!(decompress(compressed), value, "{value}");
+    }
+}
+
+#[test]
+fn test_transcode_matches_compress_for_sorted_keys() {
+    let text = r#"{"b": [1, 2, {"y": 1, "x": 2}], "a": "b"}"#;
+    let value: Value = serde_json::from_str(text).unwrap();
+    let config = Config {
+        sort_key: true,
+        ..Config::default()
+    };
+    let compressed = transcoded(text, &config);
+    assert_eq!(decompress(compressed.clone()), value);
+    assert_eq!(compressed.values.len(), compress(&value).values.len());
+}
+
+#[test]
+fn test_transcode_duplicate_key_keeps_last() {
+    let compressed = transcoded(r#"{"a": 1, "b": 2, "a": 3}"#, &Config::default());
+    assert_eq!(decompress(compressed), json!({"a": 3, "b": 2}));
+}
+
+#[test]
+fn test_transcode_invalid_json() {
+    assert!(transcode(Cursor::new("[1, 2"), Vec::new()).is_err());
+    assert!(transcode(Cursor::new("[1] 2"), Vec::new()).is_err());
+}
+
+// With `arbitrary_precision`, serde_json itself reads this key as a number
+#[cfg(not(feature = "arbitrary_precision"))]
+#[test]
+fn test_transcode_keeps_number_token_fields() {
+    for value in [
+        json!({"$serde_json::private::Number": 1, "a": 2}),
+        json!({"$serde_json::private::Number": "5"}),
+    ] {
+        let compressed = transcoded(&value.to_string(), &Config::default());
+        assert_eq!(decompress(compressed), value);
+    }
+}
+
+#[test]
+fn test_transcode_strict_duplicate_keys() {
+    let config = Config {
+        strict_duplicate_keys: true,
+        ..Config::default()
+    };
+    let err =
+        transcode_with_config(Cursor::new(r#"{"a": 1, "a": 2}"#), Vec::new(), &config).unwrap_err();
+    assert!(err.to_string().contains("duplicate key `a`"), "{err}");
+    assert_eq!(
+        decompress(transcoded(r#"{"a": 1, "b": {"a": 2}}"#, &config)),
+        json!({"a": 1, "b": {"a": 2}})
+    );
+}
+
+#[test]
+fn test_transcode_max_value_len() {
+    let config = Config {
+        max_value_len: Some(4),
+        ..Config::default()
+    };
+    for text in [r#"["short", "toolong"]"#, r#"{"toolong": 1}"#] {
+        let err = transcode_with_config(Cursor::new(text), Vec::new(), &config).unwrap_err();
+        assert!(err.to_string().contains("exceeds max_value_len 4"), "{err}");
+    }
+    assert_eq!(
+        decompress(transcoded(r#"{"ab": "cd"}"#, &config)),
+        json!({"ab": "cd"})
+    );
+}
@@ -0,0 +1,89 @@
+//! Tests for URL-safe state tokens
+#![cfg(feature = "base64")]
+
+mod sample;
+
+use compress_json_rs::{DecompressError, compress, compress_to_urlsafe, decompress_from_urlsafe};
+use serde_json::{Value, json};
+
+fn is_urlsafe(token: &str) -> bool {
+    token
+        .bytes()
+        .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+}
+
+#[test]
+fn test_roundtrip() {
+    for data in [
+        sample::sample(),
+        json!({"filters": ["open", "mine"], "page": 2, "q": "a+b/c=d?e&f"}),
+        json!(null),
+        json!(""),
+        json!([]),
+        json!({}),
+        json!("unicode: é€😀"),
+    ] {
+        let token = compress_to_urlsafe(&data);
+        assert_eq!(decompress_from_urlsafe(&token), Ok(data));
+    }
+}
+
+#[test]
+fn test_output_is_urlsafe() {
+    // Bytes that map to `+` and `/` in standard base64
+    let data = json!({"s": "\u{3ff}\u{ffff}~~~>>>???", "n": [0.1, -2e-300]});
+    let token = compress_to_urlsafe(&data);
+    assert!(is_urlsafe(&token), "{token}");
+    assert!(!token.contains('='));
+
+    let sample_token = compress_to_urlsafe(&sample::sample());
+    assert!(is_urlsafe(&sample_token));
+}
+
+#[test]
+fn test_large_input() {
+    let rows: Vec<Value> = (0..5_000)
+        .map(|i| json!({"id": i, "name": format!("user-{i}"), "active": i % 3 == 0}))
+        .collect();
+    let data = Value::Array(rows);
+    let token = compress_to_urlsafe(&data);
+    assert!(is_urlsafe(&token));
+    // About a third larger than the serialized compressed form
+    let json_len = serde_json::to_string(&compress(&data)).unwrap().len();
+    assert_eq!(token.len(), (json_len * 4).div_ceil(3));
+    assert_eq!(decompress_from_urlsafe(&token), Ok(data));
+}
+
+#[test]
+fn test_malformed_tokens() {
+    for token in ["", "W1tdLCIwIl0=", "a", "W1t+", "not a token", "e30"] {
+        assert!(
+            matches!(
+                decompress_from_urlsafe(token),
+                Err(DecompressError::Parse { .. })
+            ),
+            "{token}"
+        );
+    }
+    // `[["a|0"],"0"]`, an array that contains itself
+    assert_eq!(
+        decompress_from_urlsafe("W1siYXwwIl0sIjAiXQ"),
+        Err(DecompressError::Cycle { index: 0 })
+    );
+}
+
+#[test]
+fn test_rejects_non_canonical_tokens() {
+    // `[["n|1"],"0"]`
+    assert_eq!(decompress_from_urlsafe("W1sibnwxIl0sIjAiXQ"), Ok(json!(1)));
+    // Same bytes, but with unused trailing bits set, or padded
+    for token in ["W1sibnwxIl0sIjAiXR", "W1sibnwxIl0sIjAiXQ=="] {
+        assert!(
+            matches!(
+                decompress_from_urlsafe(token),
+                Err(DecompressError::Parse { .. })
+            ),
+            "{token}"
+        );
+    }
+}
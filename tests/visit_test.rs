@@ -0,0 +1,159 @@
+//! Tests for the visitor-based decompression API
+
+mod sample;
+
+use compress_json_rs::{
+    Compressed, Config, DecodeVisitor, compress, compress_with_config, decompress,
+    decompress_visit, expanded_node_count, key_for_index,
+};
+use serde_json::{Map, Number, Value, json};
+
+#[derive(Default)]
+struct NodeCounter {
+    nodes: usize,
+}
+
+impl DecodeVisitor for NodeCounter {
+    fn null(&mut self) {
+        self.nodes += 1;
+    }
+    fn bool(&mut self, _b: bool) {
+        self.nodes += 1;
+    }
+    fn number(&mut self, _n: &Number) {
+        self.nodes += 1;
+    }
+    fn string(&mut self, _s: &str) {
+        self.nodes += 1;
+    }
+    fn begin_array(&mut self, _len: usize) {
+        self.nodes += 1;
+    }
+    fn begin_object(&mut self, _len: usize) {
+        self.nodes += 1;
+    }
+}
+
+/// Rebuilds a `Value` from events to check their order and content
+#[derive(Default)]
+struct Builder {
+    stack: Vec<(Value, Option<String>)>,
+    key: Option<String>,
+    result: Option<Value>,
+}
+
+impl Builder {
+    fn push(&mut self, v: Value) {
+        match self.stack.last_mut() {
+            Some((Value::Array(arr), _)) => arr.push(v),
+            Some((Value::Object(map), _)) => {
+                map.insert(self.key.take().unwrap(), v);
+            }
+            Some(_) => unreachable!(),
+            None => self.result = Some(v),
+        }
+    }
+    fn close(&mut self) {
+        let (v, key) = self.stack.pop().unwrap();
+        self.key = key;
+        self.push(v);
+    }
+}
+
+impl DecodeVisitor for Builder {
+    fn null(&mut self) {
+        self.push(Value::Null);
+    }
+    fn bool(&mut self, b: bool) {
+        self.push(Value::Bool(b));
+    }
+    fn number(&mut self, n: &Number) {
+        self.push(Value::Number(n.clone()));
+    }
+    fn string(&mut self, s: &str) {
+        self.push(Value::String(s.to_string()));
+    }
+    fn begin_array(&mut self, len: usize) {
+        let key = self.key.take();
+        self.stack
+            .push((Value::Array(Vec::with_capacity(len)), key));
+    }
+    fn end_array(&mut self) {
+        self.close();
+    }
+    fn begin_object(&mut self, _len: usize) {
+        let key = self.key.take();
+        self.stack.push((Value::Object(Map::new()), key));
+    }
+    fn key(&mut self, key: &str) {
+        self.key = Some(key.to_string());
+    }
+    fn end_object(&mut self) {
+        self.close();
+    }
+}
+
+#[test]
+fn test_node_count_matches_expanded_node_count() {
+    let compressed = compress(&sample::sample());
+    let mut counter = NodeCounter::default();
+    decompress_visit(&compressed, &mut counter);
+
+    assert_eq!(counter.nodes, expanded_node_count(&compressed));
+}
+
+#[test]
+fn test_expanded_node_count_small() {
+    assert_eq!(expanded_node_count(&compress(&Value::Null)), 1);
+    assert_eq!(expanded_node_count(&compress(&json!([]))), 1);
+    assert_eq!(expanded_node_count(&compress(&json!({"a": [1, 1]}))), 4);
+}
+
+#[test]
+fn test_visit_inline_root() {
+    let compressed = Compressed::new(Vec::new(), "n|5");
+    let mut builder = Builder::default();
+    decompress_visit(&compressed, &mut builder);
+    assert_eq!(builder.result, Some(json!(5)));
+    assert_eq!(expanded_node_count(&compressed), 1);
+}
+
+#[test]
+fn test_expanded_node_count_saturates_on_shared_dag() {
+    // Each array holds the previous one twice, doubling the expanded size
+    let mut values = vec!["n|1".to_string()];
+    for i in 0..70 {
+        let key = key_for_index(i);
+        values.push(format!("a|{key}|{key}"));
+    }
+    let compressed = Compressed::new(values, key_for_index(70));
+    assert_eq!(expanded_node_count(&compressed), usize::MAX);
+}
+
+#[test]
+fn test_events_rebuild_original() {
+    let data = sample::sample();
+    let compressed = compress(&data);
+    let mut builder = Builder::default();
+    decompress_visit(&compressed, &mut builder);
+
+    assert_eq!(builder.result, Some(decompress(compressed)));
+}
+
+#[test]
+fn test_packed_int_arrays() {
+    let config = Config {
+        pack_int_arrays: true,
+        ..Config::default()
+    };
+    let data = json!({"ids": [5, 3, 9], "rows": [[1, 2], [], [-1]]});
+    let compressed = compress_with_config(&data, &config);
+    let mut builder = Builder::default();
+    decompress_visit(&compressed, &mut builder);
+    assert_eq!(builder.result, Some(data));
+
+    let mut counter = NodeCounter::default();
+    decompress_visit(&compressed, &mut counter);
+    assert_eq!(counter.nodes, 12);
+    assert_eq!(expanded_node_count(&compressed), 12);
+}
@@ -0,0 +1,64 @@
+//! Tests for writing the wire form into a caller-provided buffer
+
+mod sample;
+
+use compress_json_rs::{Compressed, compress, compress_to_writer_string, decompress};
+use serde_json::{Deserializer, Value, json};
+
+#[test]
+fn test_two_compressions_in_one_buffer() {
+    let first = json!({"id": 1, "tags": ["a", "b"]});
+    let second = json!([{"id": 2}, {"id": 3}]);
+    let mut buf = String::new();
+    compress_to_writer_string(&first, &mut buf);
+    compress_to_writer_string(&second, &mut buf);
+
+    let parsed: Vec<Compressed> = Deserializer::from_str(&buf)
+        .into_iter()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(parsed.len(), 2);
+    let mut parsed = parsed.into_iter();
+    assert_eq!(decompress(parsed.next().unwrap()), first);
+    assert_eq!(decompress(parsed.next().unwrap()), second);
+}
+
+#[test]
+fn test_matches_serde_json() {
+    let escapes: String = (0u8..0x80).map(char::from).collect();
+    for data in [
+        sample::sample(),
+        json!([
+            escapes,
+            "\u{7f}\u{80}é€😀",
+            "s|\"quoted\"",
+            "back\\slash",
+            "",
+            null
+        ]),
+        json!("root"),
+        json!(null),
+        json!({}),
+    ] {
+        let mut buf = String::new();
+        compress_to_writer_string(&data, &mut buf);
+        assert_eq!(buf, serde_json::to_string(&compress(&data)).unwrap());
+        let parsed: Compressed = serde_json::from_str(&buf).unwrap();
+        assert_eq!(decompress(parsed), data);
+    }
+}
+
+#[test]
+fn test_reused_buffer_keeps_its_capacity() {
+    let data: Value = sample::sample();
+    let mut buf = String::new();
+    compress_to_writer_string(&data, &mut buf);
+    let capacity = buf.capacity();
+    let len = buf.len();
+    for _ in 0..10 {
+        buf.clear();
+        compress_to_writer_string(&data, &mut buf);
+        assert_eq!(buf.len(), len);
+        assert_eq!(buf.capacity(), capacity);
+    }
+}